@@ -1,26 +1,16 @@
 use anyhow::{Context, Result as AnyResult};
-use serde::{Deserialize, Serialize};
-use serdeio::{DataFormat, read_records_from_file, write_records_to_writer};
-
-#[derive(Debug, Deserialize, Serialize)]
-struct User {
-    id: u32,
-    name: String,
-    items: Vec<String>,
-}
+use serdeio::{DataFormat, convert_reader};
 
 pub fn main() -> AnyResult<()> {
     // get input file path from argv
     let args: Vec<String> = std::env::args().collect();
     let input_file_path = &args[1];
 
-    // read json to memory
-    let users: Vec<User> = read_records_from_file(input_file_path, DataFormat::Auto)
-        .context("Failed to read records from file")?;
-
-    // write to stdout in json lines format
-    let writer = std::io::stdout();
-    write_records_to_writer(writer, &users, DataFormat::JsonLines).unwrap();
+    // stream the records of the json array to stdout in json lines format
+    let reader = std::fs::File::open(input_file_path).context("Failed to open input file")?;
+    let writer = std::io::stdout().lock();
+    convert_reader(reader, DataFormat::Json, writer, DataFormat::JsonLines)
+        .context("Failed to convert records")?;
 
     Ok(())
 }
@@ -0,0 +1,1266 @@
+use std::{
+    io::Cursor,
+    path::Path,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use futures_util::StreamExt;
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::{
+    fs::File,
+    io::{
+        AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
+        BufReader, BufWriter,
+    },
+};
+
+use crate::{
+    Error,
+    backend::json::{Position, from_slice},
+    compression, detect,
+    options::{ReadOptions, WriteOptions},
+    read::{
+        read_record_from_reader_with_options, read_records_from_reader_with_options,
+        read_records_iter_from_reader_with_options,
+    },
+    types::{Compression, DataFormat, DataFormatError, resolve_format},
+    write::{
+        RecordWriter, write_record_to_writer_with_options, write_records_to_writer_with_options,
+    },
+};
+
+async fn read_to_buffer(mut reader: impl AsyncRead + Unpin) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::new();
+    reader.read_to_end(&mut buffer).await?;
+    Ok(buffer)
+}
+
+async fn write_from_buffer(
+    mut writer: impl AsyncWrite + Unpin,
+    buffer: &[u8],
+) -> Result<(), Error> {
+    writer.write_all(buffer).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Reads the content of a file through [`open_stream`], so that it is
+/// decompressed as it is read.
+async fn read_file(
+    path: &Path,
+    data_format: DataFormat,
+) -> Result<(Cursor<Vec<u8>>, DataFormat), Error> {
+    let (reader, data_format) = open_stream(path, data_format).await?;
+    Ok((Cursor::new(read_to_buffer(reader).await?), data_format))
+}
+
+/// Reads up to [`detect::SAMPLE_SIZE`] leading bytes of `reader`.
+async fn read_sample(reader: &mut (impl AsyncRead + Unpin)) -> Result<Vec<u8>, Error> {
+    let mut sample = Vec::new();
+    reader
+        .take(detect::SAMPLE_SIZE)
+        .read_to_end(&mut sample)
+        .await?;
+    Ok(sample)
+}
+
+type BoxedAsyncRead<'a> = Box<dyn AsyncRead + Unpin + Send + 'a>;
+
+/// Asynchronous counterpart of `detect::sniff_reader`: detects the
+/// compression and data format of `reader` from a sample of its content.
+async fn sniff_reader<'a>(
+    mut reader: impl AsyncRead + Unpin + Send + 'a,
+) -> Result<(BoxedAsyncRead<'a>, DataFormat), Error> {
+    let sample = read_sample(&mut reader).await?;
+    let compression = detect::detect_compression(&sample);
+    let reader = BufReader::new(Cursor::new(sample).chain(reader));
+    let mut reader = compression::async_decoder(reader, compression);
+
+    let sample = read_sample(&mut reader).await?;
+    let format = detect::detect_format(&sample).ok_or(DataFormatError::Undetected)?;
+    Ok((Box::new(Cursor::new(sample).chain(reader)), format))
+}
+
+/// Opens a file for streaming, decompressing it when its extension names a
+/// compression codec, and resolves its data format as `detect::resolve_or_sniff`
+/// does.
+async fn open_stream(
+    path: &Path,
+    data_format: DataFormat,
+) -> Result<(BoxedAsyncRead<'static>, DataFormat), Error> {
+    let file = BufReader::new(File::open(path).await?);
+    let reader = compression::async_decoder(file, Compression::from_path(path));
+    match resolve_format(path, data_format) {
+        Ok(data_format) => Ok((reader, data_format)),
+        Err(Error::DataFormat(e)) => sniff_reader(reader)
+            .await
+            .map_err(|error| detect::unresolved(path, e, error)),
+        Err(e) => Err(e),
+    }
+}
+
+type BoxedAsyncWrite = Box<dyn AsyncWrite + Unpin + Send>;
+
+/// Creates a file for buffered writing, compressing it when its extension
+/// names a compression codec. The writer must be shut down.
+async fn create_file(path: &Path) -> Result<BoxedAsyncWrite, Error> {
+    let file = BufWriter::new(File::create(path).await?);
+    Ok(compression::async_encoder(
+        file,
+        Compression::from_path(path),
+    ))
+}
+
+/// Collects the output of a synchronous writer until it is taken to be
+/// written asynchronously.
+#[derive(Clone, Default)]
+struct Pending(Arc<Mutex<Vec<u8>>>);
+
+impl Pending {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl std::io::Write for Pending {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut pending = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Asynchronously reads a single record from a reader and deserializes it into the specified type.
+///
+/// The input is read to the end without blocking the runtime and then decoded
+/// in memory. See [`read_record_from_reader`] for the supported formats.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for single records,
+/// or if reading or deserialization fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use serdeio::{read_record_from_reader_async, DataFormat};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// # async fn run() {
+/// let json_data = br#"{"name": "Alice", "age": 30}"#;
+/// let user: User = read_record_from_reader_async(&json_data[..], DataFormat::Json)
+///     .await
+///     .unwrap();
+/// assert_eq!(user.name, "Alice");
+/// # }
+/// ```
+pub async fn read_record_from_reader_async<T: DeserializeOwned>(
+    reader: impl AsyncRead + Unpin,
+    data_format: DataFormat,
+) -> Result<T, Error> {
+    read_record_from_reader_with_options_async(reader, data_format, &ReadOptions::default()).await
+}
+
+/// Asynchronously reads a single record from a reader with backend settings
+/// from `options`.
+///
+/// See [`read_record_from_reader_async`] for how the input is read.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for single records,
+/// or if reading or deserialization fails.
+pub async fn read_record_from_reader_with_options_async<T: DeserializeOwned>(
+    reader: impl AsyncRead + Unpin,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<T, Error> {
+    let buffer = read_to_buffer(reader).await?;
+    read_record_from_reader_with_options(Cursor::new(buffer), data_format, options)
+}
+
+/// Asynchronously reads multiple records from a reader and deserializes them into a vector.
+///
+/// The input is read to the end without blocking the runtime and then decoded
+/// in memory. See [`read_records_from_reader`] for the supported formats, and
+/// [`read_records_stream_from_reader`] for a streaming alternative.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for multiple records,
+/// or if reading or deserialization fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use serdeio::{read_records_from_reader_async, DataFormat};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// # async fn run() {
+/// let json_data = br#"[{"name": "Alice", "age": 30}, {"name": "Bob", "age": 25}]"#;
+/// let users: Vec<User> = read_records_from_reader_async(&json_data[..], DataFormat::Json)
+///     .await
+///     .unwrap();
+/// assert_eq!(users.len(), 2);
+/// # }
+/// ```
+pub async fn read_records_from_reader_async<T: DeserializeOwned>(
+    reader: impl AsyncRead + Unpin,
+    data_format: DataFormat,
+) -> Result<Vec<T>, Error> {
+    read_records_from_reader_with_options_async(reader, data_format, &ReadOptions::default()).await
+}
+
+/// Asynchronously reads multiple records from a reader with backend settings
+/// from `options`.
+///
+/// See [`read_records_from_reader_async`] for how the input is read.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for multiple records,
+/// or if reading or deserialization fails.
+pub async fn read_records_from_reader_with_options_async<T: DeserializeOwned>(
+    reader: impl AsyncRead + Unpin,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<Vec<T>, Error> {
+    let buffer = read_to_buffer(reader).await?;
+    read_records_from_reader_with_options(Cursor::new(buffer), data_format, options)
+}
+
+/// Asynchronously writes a single record to a writer in the specified data format.
+///
+/// The record is serialized in memory and then written without blocking the
+/// runtime. See [`write_record_to_writer`] for the supported formats.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for single records,
+/// or if serialization or writing fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde::Serialize;
+/// use serdeio::{write_record_to_writer_async, DataFormat};
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// # async fn run() {
+/// let user = User { name: "Alice".to_string(), age: 30 };
+/// let mut buffer = Vec::new();
+/// write_record_to_writer_async(&mut buffer, &user, DataFormat::Json)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+pub async fn write_record_to_writer_async<T: Serialize>(
+    writer: impl AsyncWrite + Unpin,
+    record: &T,
+    data_format: DataFormat,
+) -> Result<(), Error> {
+    write_record_to_writer_with_options_async(writer, record, data_format, &WriteOptions::default())
+        .await
+}
+
+/// Asynchronously writes a single record to a writer with backend settings
+/// from `options`.
+///
+/// See [`write_record_to_writer_async`] for how the output is written.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for single records,
+/// or if serialization or writing fails.
+pub async fn write_record_to_writer_with_options_async<T: Serialize>(
+    writer: impl AsyncWrite + Unpin,
+    record: &T,
+    data_format: DataFormat,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let mut buffer = Vec::new();
+    write_record_to_writer_with_options(&mut buffer, record, data_format, options)?;
+    write_from_buffer(writer, &buffer).await
+}
+
+/// Asynchronously writes multiple records to a writer in the specified data format.
+///
+/// The records are serialized in memory and then written without blocking the
+/// runtime. See [`write_records_to_writer`] for the supported formats.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for multiple records,
+/// or if serialization or writing fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde::Serialize;
+/// use serdeio::{write_records_to_writer_async, DataFormat};
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// # async fn run() {
+/// let users = vec![
+///     User { name: "Alice".to_string(), age: 30 },
+///     User { name: "Bob".to_string(), age: 25 },
+/// ];
+/// let mut buffer = Vec::new();
+/// write_records_to_writer_async(&mut buffer, &users, DataFormat::JsonLines)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+pub async fn write_records_to_writer_async<'a, T: Serialize + 'a>(
+    writer: impl AsyncWrite + Unpin,
+    records: impl IntoIterator<Item = &'a T>,
+    data_format: DataFormat,
+) -> Result<(), Error> {
+    write_records_to_writer_with_options_async(
+        writer,
+        records,
+        data_format,
+        &WriteOptions::default(),
+    )
+    .await
+}
+
+/// Asynchronously writes multiple records to a writer with backend settings
+/// from `options`.
+///
+/// See [`write_records_to_writer_async`] for how the output is written.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for multiple records,
+/// or if serialization or writing fails.
+pub async fn write_records_to_writer_with_options_async<'a, T: Serialize + 'a>(
+    writer: impl AsyncWrite + Unpin,
+    records: impl IntoIterator<Item = &'a T>,
+    data_format: DataFormat,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let mut buffer = Vec::new();
+    write_records_to_writer_with_options(&mut buffer, records, data_format, options)?;
+    write_from_buffer(writer, &buffer).await
+}
+
+/// Asynchronously reads a single record from a file and deserializes it into the specified type.
+///
+/// The data format is automatically inferred from the file extension.
+/// If `data_format` is set to a format other than `Auto`, that format is used as-is and the file extension is ignored.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the extension is unknown,
+/// or deserialization fails.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serde::Deserialize;
+/// use serdeio::{read_record_from_file_async, DataFormat};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// # async fn run() {
+/// let user: User = read_record_from_file_async("user.json", DataFormat::Auto)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+pub async fn read_record_from_file_async<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+) -> Result<T, Error> {
+    read_record_from_file_with_options_async(path, data_format, &ReadOptions::default()).await
+}
+
+/// Asynchronously reads a single record from a file with backend settings
+/// from `options`.
+///
+/// See [`read_record_from_file_async`] for how the format is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the extension is unknown,
+/// or deserialization fails.
+pub async fn read_record_from_file_with_options_async<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<T, Error> {
+    let path = path.as_ref();
+    read_file(path, data_format)
+        .await
+        .and_then(|(rdr, final_format)| {
+            read_record_from_reader_with_options(rdr, final_format, options)
+        })
+        .map_err(|e| e.with_path(path))
+}
+
+/// Asynchronously reads multiple records from a file and deserializes them into a vector.
+///
+/// The data format is automatically inferred from the file extension.
+/// If `data_format` is set to a format other than `Auto`, that format is used as-is and the file extension is ignored.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the extension is unknown,
+/// or deserialization fails.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serde::Deserialize;
+/// use serdeio::{read_records_from_file_async, DataFormat};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// # async fn run() {
+/// let users: Vec<User> = read_records_from_file_async("users.json", DataFormat::Auto)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+pub async fn read_records_from_file_async<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+) -> Result<Vec<T>, Error> {
+    read_records_from_file_with_options_async(path, data_format, &ReadOptions::default()).await
+}
+
+/// Asynchronously reads multiple records from a file with backend settings
+/// from `options`.
+///
+/// See [`read_records_from_file_async`] for how the format is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the extension is unknown,
+/// or deserialization fails.
+pub async fn read_records_from_file_with_options_async<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<Vec<T>, Error> {
+    let path = path.as_ref();
+    read_file(path, data_format)
+        .await
+        .and_then(|(rdr, final_format)| {
+            read_records_from_reader_with_options(rdr, final_format, options)
+        })
+        .map_err(|e| e.with_path(path))
+}
+
+/// Asynchronously writes a single record to a file in the data format inferred from the file extension.
+///
+/// The data format is automatically inferred from the file extension.
+/// If `data_format` is set to a format other than `Auto`, that format is used as-is and the file extension is ignored.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created, the extension is unknown,
+/// or serialization fails.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serde::Serialize;
+/// use serdeio::{write_record_to_file_async, DataFormat};
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// # async fn run() {
+/// let user = User { name: "Alice".to_string(), age: 30 };
+/// write_record_to_file_async("user.json", &user, DataFormat::Auto)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+pub async fn write_record_to_file_async<T: Serialize>(
+    path: impl AsRef<Path>,
+    record: &T,
+    data_format: DataFormat,
+) -> Result<(), Error> {
+    write_record_to_file_with_options_async(path, record, data_format, &WriteOptions::default())
+        .await
+}
+
+/// Asynchronously writes a single record to a file with backend settings
+/// from `options`.
+///
+/// See [`write_record_to_file_async`] for how the format is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created, the extension is unknown,
+/// or serialization fails.
+pub async fn write_record_to_file_with_options_async<T: Serialize>(
+    path: impl AsRef<Path>,
+    record: &T,
+    data_format: DataFormat,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let path = path.as_ref();
+    let write = async {
+        let final_format = resolve_format(path, data_format)?;
+        let mut buffer = Vec::new();
+        write_record_to_writer_with_options(&mut buffer, record, final_format, options)?;
+        let mut file = create_file(path).await?;
+        file.write_all(&buffer).await?;
+        file.shutdown().await?;
+        Ok(())
+    };
+    write.await.map_err(|e: Error| e.with_path(path))
+}
+
+/// Asynchronously writes multiple records to a file in the data format inferred from the file extension.
+///
+/// The data format is automatically inferred from the file extension.
+/// If `data_format` is set to a format other than `Auto`, that format is used as-is and the file extension is ignored.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created, the extension is unknown,
+/// or serialization fails.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serde::Serialize;
+/// use serdeio::{write_records_to_file_async, DataFormat};
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// # async fn run() {
+/// let users = vec![
+///     User { name: "Alice".to_string(), age: 30 },
+///     User { name: "Bob".to_string(), age: 25 },
+/// ];
+/// write_records_to_file_async("users.jsonl", &users, DataFormat::Auto)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+pub async fn write_records_to_file_async<'a, T: Serialize + 'a, I: IntoIterator<Item = &'a T>>(
+    path: impl AsRef<Path>,
+    records: I,
+    data_format: DataFormat,
+) -> Result<(), Error> {
+    write_records_to_file_with_options_async(path, records, data_format, &WriteOptions::default())
+        .await
+}
+
+/// Asynchronously writes multiple records to a file with backend settings
+/// from `options`.
+///
+/// See [`write_records_to_file_async`] for how the format is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created, the extension is unknown,
+/// or serialization fails.
+pub async fn write_records_to_file_with_options_async<
+    'a,
+    T: Serialize + 'a,
+    I: IntoIterator<Item = &'a T>,
+>(
+    path: impl AsRef<Path>,
+    records: I,
+    data_format: DataFormat,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let path = path.as_ref();
+    let write = async {
+        let final_format = resolve_format(path, data_format)?;
+        // Each record is serialized as by `write_records_to_writer` and
+        // written out before the next one.
+        let pending = Pending::default();
+        let mut writer = RecordWriter::with_options(pending.clone(), final_format, options)?;
+        let mut file = create_file(path).await?;
+        for record in records {
+            writer.write(record)?;
+            file.write_all(&pending.take()).await?;
+        }
+        writer.finish()?;
+        file.write_all(&pending.take()).await?;
+        file.shutdown().await?;
+        Ok(())
+    };
+    write.await.map_err(|e: Error| e.with_path(path))
+}
+
+/// Asynchronous stream of records decoded lazily from an underlying reader.
+///
+/// Returned by [`read_records_stream_from_reader`] and [`read_records_stream_from_file`].
+/// This is the asynchronous counterpart of [`crate::RecordIter`].
+pub struct RecordStream<'a, T> {
+    inner: Pin<Box<dyn Stream<Item = Result<T, Error>> + Send + 'a>>,
+}
+
+impl<'a, T: 'a> RecordStream<'a, T> {
+    fn map_err(self, f: impl Fn(Error) -> Error + Send + 'a) -> Self {
+        Self {
+            inner: Box::pin(self.inner.map(move |result| result.map_err(&f))),
+        }
+    }
+}
+
+impl<T> Stream for RecordStream<'_, T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+type BoxedStream<'a, T> = Pin<Box<dyn Stream<Item = Result<T, Error>> + Send + 'a>>;
+
+fn jsonlines_stream<'a, T: DeserializeOwned + Send + 'a>(
+    reader: impl AsyncBufRead + Unpin + Send + 'a,
+) -> impl Stream<Item = Result<T, Error>> + Send + 'a {
+    struct State<R> {
+        reader: R,
+        line: Vec<u8>,
+        position: Position,
+        index: usize,
+        done: bool,
+    }
+
+    let state = State {
+        reader,
+        line: Vec::new(),
+        position: Position::start(),
+        index: 0,
+        done: false,
+    };
+    futures_util::stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+        loop {
+            state.line.clear();
+            let start = state.position;
+            match state.reader.read_until(b'\n', &mut state.line).await {
+                Ok(0) => return None,
+                Ok(_) => state.position.advance(&state.line),
+                Err(e) => {
+                    // An I/O error would recur on every poll.
+                    state.done = true;
+                    return Some((Err(start.attach(e.into())), state));
+                }
+            }
+            if state.line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let index = state.index;
+            state.index += 1;
+            let record = from_slice(&state.line, start).map_err(|e| e.with_record(index));
+            return Some((record, state));
+        }
+    })
+}
+
+/// Yields CSV rows, or TSV rows when `escaped` is set, as soon as each one
+/// has been read in full.
+#[cfg(feature = "csv")]
+fn csv_stream<'a, T: DeserializeOwned + Send + 'a>(
+    reader: impl AsyncBufRead + Unpin + Send + 'a,
+    options: &crate::options::CsvReadOptions,
+    escaped: bool,
+) -> impl Stream<Item = Result<T, Error>> + Send + 'a {
+    struct State<R, T> {
+        reader: R,
+        rows: crate::backend::csv::RowFeed<T>,
+        done: bool,
+    }
+
+    let state = State {
+        reader,
+        rows: crate::backend::csv::RowFeed::new(options, escaped),
+        done: false,
+    };
+    futures_util::stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+        while !state.rows.is_ready() {
+            match state.reader.fill_buf().await {
+                Ok(input) => {
+                    let taken = state.rows.feed(input);
+                    state.reader.consume(taken);
+                }
+                Err(e) => {
+                    // An I/O error would recur on every poll.
+                    state.done = true;
+                    return Some((Err(e.into()), state));
+                }
+            }
+        }
+        let row = state.rows.next_row()?;
+        Some((row.map(|(_, row)| row), state))
+    })
+}
+
+/// Reads the whole input and then yields the records that
+/// [`read_records_iter_from_reader_with_options`] decodes from it.
+fn buffered_stream<'a, T: DeserializeOwned + Send + 'a>(
+    reader: impl AsyncRead + Unpin + Send + 'a,
+    data_format: DataFormat,
+    options: ReadOptions,
+) -> BoxedStream<'a, T> {
+    let records = async move {
+        let records = read_to_buffer(reader).await.and_then(|buffer| {
+            read_records_iter_from_reader_with_options(Cursor::new(buffer), data_format, &options)
+        });
+        let records: Vec<_> = match records {
+            Ok(records) => records.collect(),
+            Err(e) => vec![Err(e)],
+        };
+        futures_util::stream::iter(records)
+    };
+    Box::pin(futures_util::stream::once(records).flatten())
+}
+
+fn records_stream<'a, T: DeserializeOwned + Send + 'a>(
+    reader: impl AsyncRead + Unpin + Send + 'a,
+    data_format: DataFormat,
+    options: ReadOptions,
+) -> Result<RecordStream<'a, T>, Error> {
+    let inner: BoxedStream<'a, T> = match data_format {
+        DataFormat::Auto => {
+            // The format is only known once a sample has been read, which
+            // happens on the first poll.
+            let records = async move {
+                let records = sniff_reader(reader)
+                    .await
+                    .and_then(|(reader, data_format)| records_stream(reader, data_format, options));
+                match records {
+                    Ok(records) => records.inner,
+                    Err(e) => Box::pin(futures_util::stream::iter([Err(e)])),
+                }
+            };
+            return Ok(RecordStream {
+                inner: Box::pin(futures_util::stream::once(records).flatten()),
+            });
+        }
+        DataFormat::JsonLines => Box::pin(jsonlines_stream(BufReader::new(reader))),
+        DataFormat::Json => buffered_stream(reader, data_format, options),
+        #[cfg(feature = "csv")]
+        DataFormat::Csv | DataFormat::Tsv => Box::pin(csv_stream(
+            BufReader::new(reader),
+            &options.csv,
+            data_format == DataFormat::Tsv,
+        )),
+        #[cfg(feature = "yaml")]
+        DataFormat::Yaml => buffered_stream(reader, data_format, options),
+        #[cfg(feature = "messagepack")]
+        DataFormat::MessagePack => buffered_stream(reader, data_format, options),
+        #[cfg(feature = "toml")]
+        DataFormat::Toml => buffered_stream(reader, data_format, options),
+        #[allow(unreachable_patterns)]
+        _ => return Err(Error::UnsupportedFormat(data_format)),
+    };
+    Ok(RecordStream { inner }.map_err(move |e| e.with_format(data_format)))
+}
+
+/// Asynchronously reads multiple records from a reader, yielding them one at a time.
+///
+/// JSON Lines records, and CSV and TSV rows, are decoded as soon as their
+/// line or row has been read, so arbitrarily large inputs are processed with
+/// bounded memory. Any other format is read to the end and decoded as
+/// [`crate::read_records_iter_from_reader`] does before the first record is
+/// yielded.
+///
+/// # Supported Formats
+///
+/// - JSON (always available)
+/// - JSON Lines (always available, streamed line by line)
+/// - CSV (requires `csv` feature, streamed row by row)
+/// - TSV (requires `csv` feature, streamed row by row)
+/// - YAML (requires `yaml` feature)
+/// - MessagePack (requires `messagepack` feature)
+/// - TOML (requires `toml` feature)
+///
+/// With `DataFormat::Auto` the format, and any compression, is detected from
+/// the content when the stream is first polled; see [`crate::detect_format`].
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for multiple records.
+/// Decoding errors of individual records, and a failure to detect the format,
+/// are yielded by the stream. The stream ends after an I/O error.
+///
+/// # Examples
+///
+/// ```rust
+/// use futures_util::StreamExt;
+/// use serde::Deserialize;
+/// use serdeio::{read_records_stream_from_reader, DataFormat};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// # async fn run() {
+/// let jsonl_data = b"{\"name\": \"Alice\", \"age\": 30}\n{\"name\": \"Bob\", \"age\": 25}\n";
+/// let mut users =
+///     read_records_stream_from_reader::<User>(&jsonl_data[..], DataFormat::JsonLines).unwrap();
+/// while let Some(user) = users.next().await {
+///     println!("{}", user.unwrap().name);
+/// }
+/// # }
+/// ```
+pub fn read_records_stream_from_reader<'a, T: DeserializeOwned + Send + 'a>(
+    reader: impl AsyncRead + Unpin + Send + 'a,
+    data_format: DataFormat,
+) -> Result<RecordStream<'a, T>, Error> {
+    read_records_stream_from_reader_with_options(reader, data_format, &ReadOptions::default())
+}
+
+/// Asynchronously reads multiple records from a reader with backend settings
+/// from `options`, yielding them one at a time.
+///
+/// See [`read_records_stream_from_reader`] for the supported formats.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for multiple records.
+/// Decoding errors of individual records are yielded by the stream.
+pub fn read_records_stream_from_reader_with_options<'a, T: DeserializeOwned + Send + 'a>(
+    reader: impl AsyncRead + Unpin + Send + 'a,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<RecordStream<'a, T>, Error> {
+    records_stream(reader, data_format, options.clone())
+}
+
+/// Asynchronously reads multiple records from a file, yielding them one at a time.
+///
+/// The data format is automatically inferred from the file extension.
+/// If `data_format` is set to a format other than `Auto`, that format is used as-is and the file extension is ignored.
+/// When the extension is missing or unknown, the format is detected from the
+/// content instead.
+///
+/// # Supported Formats
+///
+/// - JSON (.json)
+/// - JSON Lines (.jsonl, .jsl)
+/// - CSV (.csv) - requires `csv` feature
+/// - TSV (.tsv, .tab) - requires `csv` feature
+/// - YAML (.yaml, .yml) - requires `yaml` feature
+/// - MessagePack (.msgpack, .mpack, .mpk) - requires `messagepack` feature
+/// - TOML (.toml) - requires `toml` feature
+///
+/// A trailing compression extension (`.gz`, `.zst`, `.bz2`, `.xz`) is
+/// decompressed on the fly when the matching feature is enabled. See
+/// [`read_records_stream_from_reader`] for which formats are streamed.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the format can be determined
+/// neither from the extension nor from the content, or the data format is not
+/// supported for multiple records.
+/// Decoding errors of individual records are yielded by the stream.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use futures_util::StreamExt;
+/// use serde::Deserialize;
+/// use serdeio::{read_records_stream_from_file, DataFormat};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// # async fn run() {
+/// let mut users = read_records_stream_from_file::<User>("users.jsonl", DataFormat::Auto)
+///     .await
+///     .unwrap();
+/// while let Some(user) = users.next().await {
+///     println!("{}", user.unwrap().name);
+/// }
+/// # }
+/// ```
+pub async fn read_records_stream_from_file<T: DeserializeOwned + Send + 'static>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+) -> Result<RecordStream<'static, T>, Error> {
+    read_records_stream_from_file_with_options(path, data_format, &ReadOptions::default()).await
+}
+
+/// Asynchronously reads multiple records from a file with backend settings
+/// from `options`, yielding them one at a time.
+///
+/// See [`read_records_stream_from_file`] for how the format is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the format can be determined
+/// neither from the extension nor from the content, or the data format is not
+/// supported for multiple records.
+/// Decoding errors of individual records are yielded by the stream.
+pub async fn read_records_stream_from_file_with_options<T: DeserializeOwned + Send + 'static>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<RecordStream<'static, T>, Error> {
+    let path = path.as_ref();
+    let (reader, final_format) = open_stream(path, data_format)
+        .await
+        .map_err(|e| e.with_path(path))?;
+    let path = path.to_path_buf();
+    Ok(
+        read_records_stream_from_reader_with_options(reader, final_format, options)?
+            .map_err(move |e| e.with_path(path.clone())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+    struct TestRecord {
+        name: String,
+        value: i32,
+    }
+
+    fn records() -> Vec<TestRecord> {
+        vec![
+            TestRecord {
+                name: "test1".to_string(),
+                value: 1,
+            },
+            TestRecord {
+                name: "test2\nwith \"quotes\", and commas".to_string(),
+                value: 2,
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_async() {
+        let records = records();
+        let mut buffer = Vec::new();
+        write_records_to_writer_async(&mut buffer, &records, DataFormat::Json)
+            .await
+            .unwrap();
+        let read: Vec<TestRecord> = read_records_from_reader_async(&buffer[..], DataFormat::Json)
+            .await
+            .unwrap();
+        assert_eq!(records, read);
+
+        let mut buffer = Vec::new();
+        write_record_to_writer_async(&mut buffer, &records[0], DataFormat::Json)
+            .await
+            .unwrap();
+        let read: TestRecord = read_record_from_reader_async(&buffer[..], DataFormat::Json)
+            .await
+            .unwrap();
+        assert_eq!(records[0], read);
+    }
+
+    #[tokio::test]
+    async fn test_jsonlines_stream() {
+        let records = records();
+        let mut buffer = Vec::new();
+        write_records_to_writer_async(&mut buffer, &records, DataFormat::JsonLines)
+            .await
+            .unwrap();
+        buffer.extend_from_slice(b"\n\nnot json\n");
+        let results: Vec<Result<TestRecord, Error>> =
+            read_records_stream_from_reader(&buffer[..], DataFormat::JsonLines)
+                .unwrap()
+                .collect()
+                .await;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &records[0]);
+        assert_eq!(results[1].as_ref().unwrap(), &records[1]);
+        assert!(matches!(
+            results[2].as_ref().map_err(Error::kind),
+            Err(Error::Json(_))
+        ));
+    }
+
+    #[cfg(feature = "csv")]
+    #[tokio::test]
+    async fn test_csv_stream() {
+        let records = records();
+        let mut buffer = Vec::new();
+        write_records_to_writer_async(&mut buffer, &records, DataFormat::Csv)
+            .await
+            .unwrap();
+        let read: Vec<TestRecord> = read_records_stream_from_reader(&buffer[..], DataFormat::Csv)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(records, read);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn test_compressed_file_stream() {
+        let records = records();
+        let path = std::env::temp_dir().join(format!(
+            "serdeio_async_stream_test_{}.jsonl.gz",
+            std::process::id()
+        ));
+        write_records_to_file_async(&path, &records, DataFormat::Auto)
+            .await
+            .unwrap();
+        let read: Result<Vec<TestRecord>, Error> =
+            read_records_stream_from_file(&path, DataFormat::Auto)
+                .await
+                .unwrap()
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(records, read.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_file_round_trip_async() {
+        let records = records();
+        #[allow(unused_mut)]
+        let mut extensions = vec!["json", "jsonl"];
+        #[cfg(feature = "csv")]
+        extensions.extend(["csv", "tsv"]);
+        #[cfg(feature = "yaml")]
+        extensions.push("yaml");
+        #[cfg(feature = "messagepack")]
+        extensions.push("msgpack");
+        #[cfg(feature = "gzip")]
+        extensions.push("jsonl.gz");
+        for extension in extensions {
+            let path = std::env::temp_dir().join(format!(
+                "serdeio_async_file_test_{}.{extension}",
+                std::process::id()
+            ));
+            write_records_to_file_async(&path, &records, DataFormat::Auto)
+                .await
+                .unwrap();
+            let read: Vec<TestRecord> = read_records_from_file_async(&path, DataFormat::Auto)
+                .await
+                .unwrap();
+            let written = std::fs::read(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(read, records, "{extension}");
+
+            if let Ok(data_format) = DataFormat::try_from(extension) {
+                let mut expected = Vec::new();
+                crate::write_records_to_writer(&mut expected, &records, data_format).unwrap();
+                assert_eq!(written, expected, "{extension}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_stream_sniffs_format() {
+        let records = records();
+        let path =
+            std::env::temp_dir().join(format!("serdeio_async_sniff_test_{}", std::process::id()));
+        let mut buffer = Vec::new();
+        write_records_to_writer_async(&mut buffer, &records, DataFormat::JsonLines)
+            .await
+            .unwrap();
+        std::fs::write(&path, buffer).unwrap();
+        let read: Vec<TestRecord> = read_records_stream_from_file(&path, DataFormat::Auto)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(records, read);
+
+        let missing = std::env::temp_dir().join("serdeio_async_missing_test.jsonl");
+        let error = read_records_stream_from_file::<TestRecord>(&missing, DataFormat::Auto)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(error.context().unwrap().path(), Some(missing.as_path()));
+    }
+
+    #[cfg(feature = "csv")]
+    #[tokio::test]
+    async fn test_csv_stream_rows() {
+        let data = "id,name\n1,5\" screen\n2,bar\n3,baz,extra\n";
+        let results: Vec<Result<(u32, String), Error>> =
+            read_records_stream_from_reader(data.as_bytes(), DataFormat::Csv)
+                .unwrap()
+                .collect()
+                .await;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &(1, "5\" screen".to_string()));
+        assert_eq!(results[1].as_ref().unwrap(), &(2, "bar".to_string()));
+        let error = results[2].as_ref().unwrap_err();
+        assert!(matches!(error.kind(), Error::Csv(_)));
+        assert_eq!(error.context().unwrap().format(), Some(DataFormat::Csv));
+    }
+
+    #[cfg(feature = "csv")]
+    #[tokio::test]
+    async fn test_csv_stream_reads_rows_as_they_arrive() {
+        let records = records();
+        let data = "name,value\ntest1,1\n\"test2\nwith \"\"quotes\"\", and commas\",2\n";
+        let (head, tail) = data.split_at(data.find('"').unwrap());
+        let (mut writer, reader) = tokio::io::duplex(64);
+        let mut stream =
+            read_records_stream_from_reader::<TestRecord>(reader, DataFormat::Csv).unwrap();
+
+        // The first row is yielded while the rest of the input is still
+        // to come.
+        writer.write_all(head.as_bytes()).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), records[0]);
+
+        let write = async move {
+            for chunk in tail.as_bytes().chunks(3) {
+                writer.write_all(chunk).await.unwrap();
+                tokio::task::yield_now().await;
+            }
+        };
+        let (_, rest) = tokio::join!(write, stream.collect::<Vec<_>>());
+        let rest: Vec<TestRecord> = rest.into_iter().map(Result::unwrap).collect();
+        assert_eq!(rest, records[1..]);
+    }
+
+    #[cfg(feature = "csv")]
+    #[tokio::test]
+    async fn test_stream_with_options() {
+        let mut options = ReadOptions::default();
+        options.csv.delimiter = b';';
+        let read: Vec<TestRecord> = read_records_stream_from_reader_with_options(
+            &b"name;value\ntest1;1\n"[..],
+            DataFormat::Csv,
+            &options,
+        )
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+        .await;
+        assert_eq!(read, records()[..1]);
+
+        let mut buffer = Vec::new();
+        write_records_to_writer_async(&mut buffer, &records(), DataFormat::Tsv)
+            .await
+            .unwrap();
+        let read: Vec<TestRecord> = read_records_stream_from_reader(&buffer[..], DataFormat::Tsv)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(read, records());
+    }
+
+    #[tokio::test]
+    async fn test_stream_sniffs_format() {
+        let mut buffer = Vec::new();
+        write_records_to_writer_async(&mut buffer, &records(), DataFormat::Json)
+            .await
+            .unwrap();
+        let read: Vec<TestRecord> = read_records_stream_from_reader(&buffer[..], DataFormat::Auto)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(read, records());
+
+        let results: Vec<Result<TestRecord, Error>> =
+            read_records_stream_from_reader(&b"\x01\x02"[..], DataFormat::Auto)
+                .unwrap()
+                .collect()
+                .await;
+        assert!(matches!(
+            results[..],
+            [Err(Error::DataFormat(DataFormatError::Undetected))]
+        ));
+    }
+
+    struct FailingReader;
+
+    impl AsyncRead for FailingReader {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            _: &mut tokio::io::ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Err(std::io::Error::other("broken pipe")))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_ends_after_io_error() {
+        for data_format in [DataFormat::JsonLines, DataFormat::Json] {
+            let reader = Cursor::new(b"{\"name\": \"test1\", \"value\": 1}\n").chain(FailingReader);
+            let results: Vec<Result<TestRecord, Error>> =
+                read_records_stream_from_reader(reader, data_format)
+                    .unwrap()
+                    .collect()
+                    .await;
+            let errors = results.iter().filter(|result| result.is_err()).count();
+            assert_eq!(errors, 1, "{data_format}");
+            assert!(matches!(
+                results.last().unwrap().as_ref().map_err(Error::kind),
+                Err(Error::Io(_))
+            ));
+        }
+    }
+}
@@ -1,9 +1,13 @@
+pub mod field_path;
 pub mod json;
 pub mod jsonlines;
 
 #[cfg(feature = "csv")]
 pub mod csv;
 
+#[cfg(feature = "csv")]
+pub mod flatten;
+
 #[cfg(feature = "yaml")]
 pub mod yaml;
 
@@ -1,38 +1,749 @@
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    io::{BufReader, BufWriter, Read, Write},
+    marker::PhantomData,
+};
 
+#[cfg(feature = "schema")]
+use indexmap::IndexMap;
 use serde::{Serialize, de::DeserializeOwned};
 
-use crate::Error;
+use super::{
+    field_path,
+    flatten::{self, Node},
+};
+use crate::{
+    Error,
+    options::{CsvQuoteStyle, CsvReadOptions, CsvTerminator, CsvTrim, CsvWriteOptions},
+};
 
-pub fn read<T: DeserializeOwned>(reader: impl Read) -> Result<Vec<T>, Error> {
-    let mut rdr = csv::Reader::from_reader(BufReader::new(reader));
-    let mut records: Vec<T> = Vec::new();
-    for result in rdr.deserialize() {
-        let record: T = result?;
-        records.push(record);
+pub fn read<T: DeserializeOwned>(
+    reader: impl Read,
+    options: &CsvReadOptions,
+) -> Result<Vec<T>, Error> {
+    read_iter(reader, options).collect()
+}
+
+pub fn read_iter<R: Read, T: DeserializeOwned>(
+    reader: R,
+    options: &CsvReadOptions,
+) -> impl Iterator<Item = Result<T, Error>> + use<R, T> {
+    read_located_iter(reader, options).map(|row| row.map(|(_, row)| row))
+}
+
+/// Reads rows together with the one-based line each one starts on.
+pub fn read_located_iter<R: Read, T: DeserializeOwned>(
+    reader: R,
+    options: &CsvReadOptions,
+) -> impl Iterator<Item = Result<(Option<u64>, T), Error>> + use<R, T> {
+    let reader = reader_builder(options).from_reader(BufReader::new(reader));
+    RowIter::new(reader, options, false)
+}
+
+/// Reads tab-separated values.
+///
+/// Fields are never quoted. Instead, tabs, line breaks and backslashes within
+/// a field are written as the escape sequences `\t`, `\n`, `\r` and `\\`.
+/// The delimiter and quoting settings of `options` are ignored.
+pub fn read_tsv<T: DeserializeOwned>(
+    reader: impl Read,
+    options: &CsvReadOptions,
+) -> Result<Vec<T>, Error> {
+    read_tsv_iter(reader, options).collect()
+}
+
+pub fn read_tsv_iter<R: Read, T: DeserializeOwned>(
+    reader: R,
+    options: &CsvReadOptions,
+) -> impl Iterator<Item = Result<T, Error>> + use<R, T> {
+    read_tsv_located_iter(reader, options).map(|row| row.map(|(_, row)| row))
+}
+
+pub fn read_tsv_located_iter<R: Read, T: DeserializeOwned>(
+    reader: R,
+    options: &CsvReadOptions,
+) -> impl Iterator<Item = Result<(Option<u64>, T), Error>> + use<R, T> {
+    let reader = tsv_builder(options).from_reader(BufReader::new(reader));
+    RowIter::new(reader, options, true)
+}
+
+fn reader_builder(options: &CsvReadOptions) -> csv::ReaderBuilder {
+    let mut builder = csv::ReaderBuilder::new();
+    builder
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .quoting(options.quoting)
+        .escape(options.escape)
+        .double_quote(options.double_quote)
+        .has_headers(options.has_headers)
+        .comment(options.comment)
+        .flexible(options.flexible)
+        .trim(match options.trim {
+            CsvTrim::None => csv::Trim::None,
+            CsvTrim::Headers => csv::Trim::Headers,
+            CsvTrim::Fields => csv::Trim::Fields,
+            CsvTrim::All => csv::Trim::All,
+        })
+        .terminator(terminator(options.terminator));
+    builder
+}
+
+fn tsv_builder(options: &CsvReadOptions) -> csv::ReaderBuilder {
+    let mut builder = reader_builder(options);
+    builder.delimiter(b'\t').quoting(false);
+    builder
+}
+
+fn terminator(terminator: CsvTerminator) -> csv::Terminator {
+    match terminator {
+        CsvTerminator::Crlf => csv::Terminator::CRLF,
+        CsvTerminator::Any(byte) => csv::Terminator::Any(byte),
+    }
+}
+
+/// Attaches the position `csv` reports, and the header of the column that
+/// failed to deserialize. Its record count includes the `header_rows`, which
+/// are not records.
+fn locate(error: csv::Error, headers: Option<&csv::StringRecord>, header_rows: u64) -> Error {
+    let position = error.position().cloned();
+    let error = with_column(error, headers);
+    match position {
+        Some(position) => at_position(error, &position, header_rows),
+        None => error,
+    }
+}
+
+fn at_position(error: Error, position: &csv::Position, header_rows: u64) -> Error {
+    error
+        .with_record(position.record().saturating_sub(header_rows) as usize)
+        .with_position(Some(position.line()), None, Some(position.byte()))
+}
+
+/// Deserializes a row whose dotted headers name the fields of nested records.
+fn unflatten<T: DeserializeOwned>(
+    headers: &csv::StringRecord,
+    row: &csv::StringRecord,
+) -> Result<T, Error> {
+    let node = Node::unflatten(headers.iter().zip(row.iter()))?;
+    field_path::deserialize(node).map_err(|e| e.attach(Error::Flatten))
+}
+
+/// Deserializes a row from its cells keyed by header, as the cells are typed
+/// by [`flatten::cell_value`], rebuilding nested records from dotted headers
+/// when `unflatten` is set.
+///
+/// A plain row that does not deserialize is read again by `csv`, so that it
+/// fails with the same [`Error::Csv`] as an unchecked read, naming the column.
+#[cfg(feature = "schema")]
+pub(crate) fn deserialize_cells<T: DeserializeOwned>(
+    cells: &IndexMap<String, String>,
+    unflatten: bool,
+) -> Result<T, Error> {
+    let pairs = cells
+        .iter()
+        .map(|(column, cell)| (column.as_str(), cell.as_str()));
+    if unflatten {
+        return field_path::deserialize(Node::unflatten(pairs)?)
+            .map_err(|e| e.attach(Error::Flatten));
+    }
+    let node = Node::Branch(
+        pairs
+            .map(|(column, cell)| (column, Node::Leaf(cell)))
+            .collect(),
+    );
+    field_path::deserialize(node).or_else(|_| {
+        let headers: csv::StringRecord = cells.keys().collect();
+        let row: csv::StringRecord = cells.values().collect();
+        row.deserialize(Some(&headers))
+            .map_err(|e| with_column(e, Some(&headers)))
+    })
+}
+
+/// Converts a `csv` error, naming the column that failed to deserialize by
+/// its header, or by its index when there is none.
+pub(crate) fn with_column(error: csv::Error, headers: Option<&csv::StringRecord>) -> Error {
+    let field = match error.kind() {
+        csv::ErrorKind::Deserialize { err, .. } => {
+            err.field()
+                .map(|index| match headers.and_then(|h| h.get(index as usize)) {
+                    Some(header) => header.to_string(),
+                    None => format!("[{index}]"),
+                })
+        }
+        _ => None,
+    };
+    match field {
+        Some(field) => Error::from(error).with_field(field),
+        None => Error::from(error),
+    }
+}
+
+/// Re-encodes a row as CSV text for error reports.
+fn raw_row(row: &csv::StringRecord, delimiter: u8) -> String {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(Vec::new());
+    let _ = writer.write_record(row);
+    let bytes = writer.into_inner().unwrap_or_default();
+    String::from_utf8_lossy(&bytes).trim_end().to_string()
+}
+
+/// Resolves the escape sequences of a TSV field.
+fn unescape(field: &str) -> Cow<'_, str> {
+    if !field.contains('\\') {
+        return Cow::Borrowed(field);
+    }
+    let mut unescaped = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            // Unknown escapes are kept verbatim.
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    Cow::Owned(unescaped)
+}
+
+fn unescape_row(row: &csv::StringRecord) -> csv::StringRecord {
+    let mut unescaped: csv::StringRecord = row.iter().map(unescape).collect();
+    unescaped.set_position(row.position().cloned());
+    unescaped
+}
+
+/// Deserializes one row at a time against the header row.
+struct RowIter<R, T> {
+    reader: csv::Reader<R>,
+    delimiter: u8,
+    /// Whether fields are TSV-escaped rather than quoted.
+    escaped: bool,
+    flatten: bool,
+    header_rows: u64,
+    headers: Option<csv::StringRecord>,
+    row: csv::StringRecord,
+    done: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: Read, T: DeserializeOwned> RowIter<R, T> {
+    fn new(reader: csv::Reader<R>, options: &CsvReadOptions, escaped: bool) -> Self {
+        Self {
+            delimiter: if escaped { b'\t' } else { options.delimiter },
+            escaped,
+            flatten: options.flatten,
+            header_rows: u64::from(options.has_headers),
+            headers: None,
+            row: csv::StringRecord::new(),
+            done: false,
+            reader,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the current row as it appeared in the input.
+    fn raw(&self) -> String {
+        if self.escaped {
+            self.row.iter().collect::<Vec<_>>().join("\t")
+        } else {
+            raw_row(&self.row, self.delimiter)
+        }
+    }
+}
+
+impl<R: Read, T: DeserializeOwned> Iterator for RowIter<R, T> {
+    type Item = Result<(Option<u64>, T), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.flatten && !self.reader.has_headers() {
+            self.done = true;
+            let message = "flattened columns are named by the header row, which is disabled";
+            return Some(Err(Error::Flatten(serde::de::Error::custom(message))));
+        }
+        if self.headers.is_none() && self.reader.has_headers() {
+            match self.reader.headers() {
+                Ok(headers) if self.escaped => self.headers = Some(unescape_row(headers)),
+                Ok(headers) => self.headers = Some(headers.clone()),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(locate(e, None, self.header_rows)));
+                }
+            }
+        }
+        let headers = self.headers.as_ref();
+        let header_rows = self.header_rows;
+        match self.reader.read_record(&mut self.row) {
+            Ok(true) => {
+                let row = if self.escaped {
+                    Cow::Owned(unescape_row(&self.row))
+                } else {
+                    Cow::Borrowed(&self.row)
+                };
+                let result = match headers {
+                    Some(headers) if self.flatten => {
+                        unflatten(headers, &row).map_err(|e| match row.position() {
+                            Some(position) => at_position(e, position, header_rows),
+                            None => e,
+                        })
+                    }
+                    _ => row
+                        .deserialize(headers)
+                        .map_err(|e| locate(e, headers, header_rows)),
+                };
+                let line = row.position().map(csv::Position::line);
+                Some(
+                    result
+                        .map(|record| (line, record))
+                        .map_err(|e| e.with_raw(self.raw())),
+                )
+            }
+            Ok(false) => {
+                self.done = true;
+                None
+            }
+            // A row with the wrong number of fields is read in full, so the
+            // reader can carry on with the next one.
+            Err(e) if matches!(e.kind(), csv::ErrorKind::UnequalLengths { .. }) => {
+                Some(Err(locate(e, headers, header_rows).with_raw(self.raw())))
+            }
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    csv::ErrorKind::Utf8 { .. } | csv::ErrorKind::Deserialize { .. }
+                ) =>
+            {
+                Some(Err(locate(e, headers, header_rows)))
+            }
+            // Anything else, such as an I/O error, would recur on every call.
+            Err(e) => {
+                self.done = true;
+                Some(Err(locate(e, headers, header_rows)))
+            }
+        }
+    }
+}
+
+/// Reads rows from input that arrives in chunks, such as from an async
+/// reader.
+///
+/// Each row is read as by [`read_located_iter`], once it has arrived in
+/// full, so that only the row being read is held in memory.
+#[cfg(feature = "async")]
+pub(crate) struct RowFeed<T> {
+    rows: RowIter<Chunks, T>,
+    /// Parses the input as `rows` does, to find where each row ends.
+    splitter: csv_core::Reader,
+    scratch: Vec<u8>,
+    scratch_ends: Vec<usize>,
+    /// Rows that have arrived in full but have not been read yet.
+    complete: usize,
+    /// Rows the next read takes, counting the header row.
+    needed: usize,
+}
+
+#[cfg(feature = "async")]
+impl<T: DeserializeOwned> RowFeed<T> {
+    /// Creates a feed of CSV rows, or of TSV rows when `escaped` is set.
+    pub(crate) fn new(options: &CsvReadOptions, escaped: bool) -> Self {
+        let builder = if escaped {
+            tsv_builder(options)
+        } else {
+            reader_builder(options)
+        };
+        let mut splitter = csv_core::ReaderBuilder::new();
+        splitter
+            .delimiter(if escaped { b'\t' } else { options.delimiter })
+            .quote(options.quote)
+            .quoting(options.quoting && !escaped)
+            .escape(options.escape)
+            .double_quote(options.double_quote)
+            .comment(options.comment)
+            .terminator(match options.terminator {
+                CsvTerminator::Crlf => csv_core::Terminator::CRLF,
+                CsvTerminator::Any(byte) => csv_core::Terminator::Any(byte),
+            });
+        Self {
+            rows: RowIter::new(builder.from_reader(Chunks::default()), options, escaped),
+            splitter: splitter.build(),
+            scratch: vec![0; 1024],
+            scratch_ends: vec![0; 64],
+            complete: 0,
+            needed: 1 + usize::from(options.has_headers),
+        }
+    }
+
+    /// Takes `input` up to the end of the next row and returns how many of
+    /// its bytes were taken. Empty input marks the end of the input.
+    pub(crate) fn feed(&mut self, input: &[u8]) -> usize {
+        let chunks = self.rows.reader.get_mut();
+        if input.is_empty() {
+            chunks.finished = true;
+            return 0;
+        }
+        let mut taken = 0;
+        while taken < input.len() {
+            // The fields are read by `rows`, so the splitter's copy of them is
+            // overwritten; its DFA only tracks where it is within the row.
+            let (result, read, _, _) = self.splitter.read_record(
+                &input[taken..],
+                &mut self.scratch,
+                &mut self.scratch_ends,
+            );
+            taken += read;
+            match result {
+                csv_core::ReadRecordResult::Record => {
+                    self.complete += 1;
+                    break;
+                }
+                csv_core::ReadRecordResult::InputEmpty | csv_core::ReadRecordResult::End => break,
+                csv_core::ReadRecordResult::OutputFull
+                | csv_core::ReadRecordResult::OutputEndsFull => {}
+            }
+        }
+        chunks.buffer.extend(&input[..taken]);
+        taken
+    }
+
+    /// Whether the next row can be read without more input.
+    pub(crate) fn is_ready(&self) -> bool {
+        self.rows.reader.get_ref().finished || self.complete >= self.needed
+    }
+
+    /// Reads the next row, which must be ready.
+    pub(crate) fn next_row(&mut self) -> Option<Result<(Option<u64>, T), Error>> {
+        let row = self.rows.next();
+        self.complete = self.complete.saturating_sub(self.needed);
+        self.needed = 1;
+        row
+    }
+}
+
+/// The input a [`RowFeed`] has taken and its rows have not read yet.
+#[cfg(feature = "async")]
+#[derive(Default)]
+struct Chunks {
+    buffer: std::collections::VecDeque<u8>,
+    finished: bool,
+}
+
+#[cfg(feature = "async")]
+impl Read for Chunks {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.buffer.is_empty() && !self.finished {
+            // Rows are only read once they have arrived in full.
+            return Err(std::io::ErrorKind::WouldBlock.into());
+        }
+        self.buffer.read(buf)
     }
-    Ok(records)
 }
 
 pub fn write<'a, T: Serialize + 'a>(
     writer: impl Write,
     records: impl IntoIterator<Item = &'a T>,
+    options: &CsvWriteOptions,
 ) -> Result<(), Error> {
-    let mut wtr = csv::Writer::from_writer(BufWriter::new(writer));
+    let mut wtr = SeqWriter::new(writer, options);
     for record in records {
-        wtr.serialize(record)?;
+        wtr.write(record)?;
     }
-    wtr.flush()?;
+    wtr.finish()?;
     Ok(())
 }
 
+/// Writes records as tab-separated values, escaping tabs, line breaks and
+/// backslashes within fields. See [`read_tsv`] for the escaping rules.
+pub fn write_tsv<'a, T: Serialize + 'a>(
+    writer: impl Write,
+    records: impl IntoIterator<Item = &'a T>,
+    options: &CsvWriteOptions,
+) -> Result<(), Error> {
+    let mut wtr = SeqWriter::tsv(writer, options);
+    for record in records {
+        wtr.write(record)?;
+    }
+    wtr.finish()?;
+    Ok(())
+}
+
+fn writer_builder(options: &CsvWriteOptions) -> csv::WriterBuilder {
+    let mut builder = csv::WriterBuilder::new();
+    builder
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .quote_style(match options.quote_style {
+            CsvQuoteStyle::Always => csv::QuoteStyle::Always,
+            CsvQuoteStyle::Necessary => csv::QuoteStyle::Necessary,
+            CsvQuoteStyle::NonNumeric => csv::QuoteStyle::NonNumeric,
+            CsvQuoteStyle::Never => csv::QuoteStyle::Never,
+        })
+        .double_quote(options.double_quote)
+        .escape(options.escape)
+        .has_headers(options.has_headers)
+        .flexible(options.flexible)
+        .terminator(terminator(options.terminator));
+    builder
+}
+
+/// Incrementally writes records as CSV rows, emitting the header row once
+/// before the first record.
+pub struct SeqWriter<W: Write> {
+    writer: csv::Writer<TsvEscaper<W>>,
+    /// How records are laid out in columns when flattening.
+    flattened: Option<Flattened>,
+}
+
+enum Flattened {
+    /// Rows are written as they come, under the columns of the first record.
+    Streaming {
+        has_headers: bool,
+        /// Position of each column, once the first record is written.
+        columns: Option<HashMap<String, usize>>,
+        records: usize,
+    },
+    /// Rows are kept until the writer finishes, to be written under the union
+    /// of their columns.
+    Buffered {
+        has_headers: bool,
+        rows: Vec<Vec<(String, String)>>,
+    },
+}
+
+impl Flattened {
+    fn new(options: &CsvWriteOptions) -> Option<Self> {
+        let has_headers = options.has_headers;
+        options.flatten.then(|| match options.union_columns {
+            true => Self::Buffered {
+                has_headers,
+                rows: Vec::new(),
+            },
+            false => Self::Streaming {
+                has_headers,
+                columns: None,
+                records: 0,
+            },
+        })
+    }
+
+    fn write(
+        &mut self,
+        writer: &mut csv::Writer<impl Write>,
+        row: Vec<(String, String)>,
+    ) -> Result<(), Error> {
+        let (has_headers, columns, records) = match self {
+            Self::Buffered { rows, .. } => {
+                rows.push(row);
+                return Ok(());
+            }
+            Self::Streaming {
+                has_headers,
+                columns,
+                records,
+            } => (*has_headers, columns, records),
+        };
+        let record = *records;
+        *records += 1;
+        let Some(columns) = columns else {
+            if has_headers && !row.is_empty() {
+                writer.write_record(row.iter().map(|(column, _)| column))?;
+            }
+            writer.write_record(row.iter().map(|(_, cell)| cell))?;
+            *columns = Some(
+                row.into_iter()
+                    .enumerate()
+                    .map(|(i, (column, _))| (column, i))
+                    .collect(),
+            );
+            return Ok(());
+        };
+        let mut cells = vec![String::new(); columns.len()];
+        for (column, cell) in row {
+            match columns.get(&column) {
+                Some(&i) => cells[i] = cell,
+                // An empty cell holds nothing that would be lost.
+                None if cell.is_empty() => {}
+                None => {
+                    let message = format!(
+                        "column `{column}` is not among the columns of the first record; \
+                         set `union_columns` to write the columns of every record"
+                    );
+                    return Err(
+                        Error::Flatten(serde::de::Error::custom(message)).with_record(record)
+                    );
+                }
+            }
+        }
+        writer.write_record(&cells)?;
+        Ok(())
+    }
+
+    /// Writes the buffered rows, if any, under the union of their columns.
+    fn finish(self, writer: &mut csv::Writer<impl Write>) -> Result<(), Error> {
+        let Self::Buffered { has_headers, rows } = self else {
+            return Ok(());
+        };
+        let columns = flatten::columns(rows.iter().map(Vec::as_slice));
+        if has_headers && !columns.is_empty() {
+            writer.write_record(&columns)?;
+        }
+        let indices: HashMap<&str, usize> =
+            columns.iter().enumerate().map(|(i, c)| (*c, i)).collect();
+        let mut cells = vec![""; columns.len()];
+        for row in &rows {
+            cells.fill("");
+            for (column, cell) in row {
+                cells[indices[column.as_str()]] = cell;
+            }
+            writer.write_record(&cells)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> SeqWriter<W> {
+    pub fn new(writer: W, options: &CsvWriteOptions) -> Self {
+        let writer = writer_builder(options).from_writer(TsvEscaper::passthrough(writer));
+        Self {
+            writer,
+            flattened: Flattened::new(options),
+        }
+    }
+
+    /// Creates a writer of tab-separated values. The delimiter and quoting
+    /// settings of `options` are ignored.
+    pub fn tsv(writer: W, options: &CsvWriteOptions) -> Self {
+        // Every field is quoted so that `TsvEscaper` can tell the bytes of
+        // a field from delimiters and terminators.
+        let writer = writer_builder(options)
+            .delimiter(b'\t')
+            .quote(b'"')
+            .quote_style(csv::QuoteStyle::Always)
+            .double_quote(true)
+            .from_writer(TsvEscaper::new(writer));
+        Self {
+            writer,
+            flattened: Flattened::new(options),
+        }
+    }
+
+    pub fn write<T: Serialize>(&mut self, record: &T) -> Result<(), Error> {
+        match &mut self.flattened {
+            Some(flattened) => {
+                let value = crate::value::to_value(record)?;
+                flattened.write(&mut self.writer, flatten::flatten(&value))?;
+            }
+            None => self.writer.serialize(record)?,
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W, Error> {
+        if let Some(flattened) = self.flattened.take() {
+            flattened.finish(&mut self.writer)?;
+        }
+        let writer = self
+            .writer
+            .into_inner()
+            .map_err(|e| Error::Io(e.into_error()))?;
+        Ok(writer.writer.into_inner().map_err(|e| e.into_error())?)
+    }
+}
+
+/// Rewrites fully quoted CSV output into escaped TSV on the fly.
+///
+/// Quotes are dropped, and tabs, line breaks and backslashes between them are
+/// replaced by their escape sequences. When disabled, bytes pass through
+/// untouched.
+struct TsvEscaper<W: Write> {
+    writer: BufWriter<W>,
+    enabled: bool,
+    in_field: bool,
+    /// Whether the previous byte was a quote inside a field, which either
+    /// closes the field or starts a doubled quote.
+    pending_quote: bool,
+}
+
+impl<W: Write> TsvEscaper<W> {
+    fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            enabled: true,
+            in_field: false,
+            pending_quote: false,
+        }
+    }
+
+    fn passthrough(writer: W) -> Self {
+        Self {
+            enabled: false,
+            ..Self::new(writer)
+        }
+    }
+}
+
+impl<W: Write> Write for TsvEscaper<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !self.enabled {
+            return self.writer.write(buf);
+        }
+        for &byte in buf {
+            if self.pending_quote {
+                self.pending_quote = false;
+                if byte == b'"' {
+                    self.writer.write_all(b"\"")?;
+                    continue;
+                }
+                self.in_field = false;
+            }
+            if !self.in_field {
+                if byte == b'"' {
+                    self.in_field = true;
+                } else {
+                    self.writer.write_all(&[byte])?;
+                }
+                continue;
+            }
+            match byte {
+                b'"' => self.pending_quote = true,
+                b'\t' => self.writer.write_all(b"\\t")?,
+                b'\n' => self.writer.write_all(b"\\n")?,
+                b'\r' => self.writer.write_all(b"\\r")?,
+                b'\\' => self.writer.write_all(b"\\\\")?,
+                _ => self.writer.write_all(&[byte])?,
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use std::io::Cursor;
+    use std::io::{self, Cursor, Read};
 
     use serde::{Deserialize, Serialize};
 
-    use super::{read, write};
+    use super::{SeqWriter, read, read_iter, read_tsv, read_tsv_iter, write, write_tsv};
+    use crate::{
+        Error,
+        options::{CsvQuoteStyle, CsvReadOptions, CsvTerminator, CsvTrim, CsvWriteOptions},
+    };
 
     #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
     struct Record {
@@ -47,7 +758,7 @@ id,name
 1,foo
 2,bar"#;
 
-        let records: Vec<Record> = read(Cursor::new(text)).unwrap();
+        let records: Vec<Record> = read(Cursor::new(text), &Default::default()).unwrap();
         let expected = vec![
             Record {
                 id: 1,
@@ -74,7 +785,7 @@ id,name
             },
         ];
         let mut cursor = Cursor::new(vec![]);
-        write(&mut cursor, &records).unwrap();
+        write(&mut cursor, &records, &Default::default()).unwrap();
         let data = String::from_utf8(cursor.into_inner())
             .unwrap()
             .trim()
@@ -87,4 +798,299 @@ id,name
             .to_owned();
         assert_eq!(expected, data);
     }
+
+    #[test]
+    fn test_read_error_position() {
+        let text = "id,name\n1,foo\nx,bar\n";
+        let result: Result<Vec<Record>, Error> = read(Cursor::new(text), &Default::default());
+        let error = result.unwrap_err();
+        assert!(matches!(error.kind(), Error::Csv(_)));
+        let context = error.context().unwrap();
+        assert_eq!(context.record(), Some(1));
+        assert_eq!(context.field(), Some("id"));
+        assert_eq!(context.line(), Some(3));
+        assert_eq!(context.offset(), Some(14));
+    }
+
+    #[test]
+    fn test_read_iter_ends_after_io_error() {
+        /// Reader that fails on every read once its data is exhausted.
+        struct FailingReader(Cursor<&'static str>);
+
+        impl Read for FailingReader {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                match self.0.read(buf)? {
+                    0 => Err(io::Error::other("read failed")),
+                    n => Ok(n),
+                }
+            }
+        }
+
+        let reader = FailingReader(Cursor::new("id,name\n1,foo\n"));
+        let results: Vec<Result<Record, Error>> =
+            read_iter(reader, &Default::default()).take(10).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            results[1].as_ref().map_err(Error::kind),
+            Err(Error::Csv(e)) if e.is_io_error()
+        ));
+    }
+
+    #[test]
+    fn test_read_write_without_headers() {
+        let read_options = CsvReadOptions {
+            has_headers: false,
+            ..Default::default()
+        };
+        let records: Vec<Record> = read(Cursor::new("1,foo\n2,bar\n"), &read_options).unwrap();
+        assert_eq!(records[1].name, "bar");
+
+        let write_options = CsvWriteOptions {
+            has_headers: false,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        write(&mut buffer, &records, &write_options).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "1,foo\n2,bar\n");
+
+        let text = "1,foo\n2,bar,extra\n";
+        let error = read::<Record>(Cursor::new(text), &read_options).unwrap_err();
+        assert_eq!(error.context().unwrap().record(), Some(1));
+        let flexible = CsvReadOptions {
+            flexible: true,
+            ..read_options
+        };
+        let records: Vec<Record> = read(Cursor::new(text), &flexible).unwrap();
+        assert_eq!(records.len(), 2);
+    }
+
+    #[test]
+    fn test_dialect() {
+        let read_options = CsvReadOptions {
+            delimiter: b';',
+            comment: Some(b'#'),
+            trim: CsvTrim::All,
+            ..Default::default()
+        };
+        let text = "# exported 2024-01-01\nid ; name\n1 ; 'foo'\n2 ;\"b;ar\"\n";
+        let records: Vec<Record> = read(Cursor::new(text), &read_options).unwrap();
+        assert_eq!(records[0].name, "'foo'");
+        assert_eq!(records[1].name, "b;ar");
+
+        let write_options = CsvWriteOptions {
+            delimiter: b'|',
+            quote: b'\'',
+            quote_style: CsvQuoteStyle::NonNumeric,
+            terminator: CsvTerminator::Crlf,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        write(&mut buffer, &records, &write_options).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "'id'|'name'\r\n1|'''foo'''\r\n2|'b;ar'\r\n"
+        );
+    }
+
+    #[test]
+    fn test_escape_without_double_quote() {
+        let records = vec![Record {
+            id: 1,
+            name: "say \"hi\"".to_owned(),
+        }];
+        let write_options = CsvWriteOptions {
+            double_quote: false,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        write(&mut buffer, &records, &write_options).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        assert_eq!(text, "id,name\n1,\"say \\\"hi\\\"\"\n");
+
+        let read_options = CsvReadOptions {
+            escape: Some(b'\\'),
+            double_quote: false,
+            ..Default::default()
+        };
+        let read_back: Vec<Record> = read(Cursor::new(text), &read_options).unwrap();
+        assert_eq!(read_back, records);
+    }
+
+    #[test]
+    fn test_tsv_round_trip() {
+        let records = vec![
+            Record {
+                id: 1,
+                name: "tab\there, \"quoted\"".to_owned(),
+            },
+            Record {
+                id: 2,
+                name: "line\nbreak \\ slash".to_owned(),
+            },
+        ];
+        let mut buffer = Vec::new();
+        write_tsv(&mut buffer, &records, &Default::default()).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            text,
+            "id\tname\n1\ttab\\there, \"quoted\"\n2\tline\\nbreak \\\\ slash\n"
+        );
+
+        let read_back: Vec<Record> = read_tsv(Cursor::new(text), &Default::default()).unwrap();
+        assert_eq!(read_back, records);
+    }
+
+    #[test]
+    fn test_tsv_read_error() {
+        let text = "id\tname\n1\t\"foo\n\"x\"\tbar\n";
+        let results: Vec<Result<Record, Error>> =
+            read_tsv_iter(Cursor::new(text), &Default::default()).collect();
+        assert_eq!(results[0].as_ref().unwrap().name, "\"foo");
+        let context = results[1].as_ref().unwrap_err().context().unwrap();
+        assert_eq!(context.record(), Some(1));
+        assert_eq!(context.field(), Some("id"));
+        assert_eq!(context.raw(), Some("\"x\"\tbar"));
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Address {
+        city: String,
+        zip: Option<u32>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct User {
+        id: u32,
+        address: Address,
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn test_flatten_round_trip() {
+        let users = vec![
+            User {
+                id: 1,
+                address: Address {
+                    city: "Paris".to_owned(),
+                    zip: Some(75001),
+                },
+                tags: vec!["a".to_owned()],
+            },
+            User {
+                id: 2,
+                address: Address {
+                    city: "Lyon".to_owned(),
+                    zip: None,
+                },
+                tags: vec!["b".to_owned(), "c".to_owned()],
+            },
+        ];
+        let write_options = CsvWriteOptions {
+            flatten: true,
+            union_columns: true,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        write(&mut buffer, &users, &write_options).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            text,
+            "id,address.city,address.zip,tags.0,tags.1\n1,Paris,75001,a,\n2,Lyon,,b,c\n"
+        );
+
+        let read_options = CsvReadOptions {
+            flatten: true,
+            ..Default::default()
+        };
+        let read_back: Vec<User> = read(Cursor::new(&text), &read_options).unwrap();
+        assert_eq!(read_back, users);
+
+        let mut buffer = Vec::new();
+        write_tsv(&mut buffer, &users, &write_options).unwrap();
+        let read_back: Vec<User> = read_tsv(Cursor::new(buffer), &read_options).unwrap();
+        assert_eq!(read_back, users);
+    }
+
+    #[test]
+    fn test_flatten_read_error() {
+        let text = "id,address.city,address.zip,tags.0\n1,Paris,75001,a\n2,Lyon,x,b\n";
+        let options = CsvReadOptions {
+            flatten: true,
+            ..Default::default()
+        };
+        let error = read::<User>(Cursor::new(text), &options).unwrap_err();
+        assert!(matches!(error.kind(), Error::Flatten(_)));
+        let context = error.context().unwrap();
+        assert_eq!(context.record(), Some(1));
+        assert_eq!(context.field(), Some("address.zip"));
+        assert_eq!(context.line(), Some(3));
+        assert_eq!(context.raw(), Some("2,Lyon,x,b"));
+    }
+
+    #[test]
+    fn test_flatten_streams_first_record_columns() {
+        let user = |id, zip, tags: &[&str]| User {
+            id,
+            address: Address {
+                city: "Oslo".to_owned(),
+                zip,
+            },
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        };
+        let options = CsvWriteOptions {
+            flatten: true,
+            ..Default::default()
+        };
+        let mut writer = SeqWriter::new(Vec::new(), &options);
+        writer.write(&user(1, Some(1), &["a", "b"])).unwrap();
+        // The first row is written before the writer finishes.
+        writer.writer.flush().unwrap();
+        assert_eq!(
+            writer.writer.get_ref().writer.get_ref(),
+            b"id,address.city,address.zip,tags.0,tags.1\n1,Oslo,1,a,b\n"
+        );
+        writer.write(&user(2, None, &["c"])).unwrap();
+        let error = writer.write(&user(3, None, &["d", "e", "f"])).unwrap_err();
+        assert!(matches!(error.kind(), Error::Flatten(_)));
+        assert!(error.to_string().contains("tags.2"));
+        assert_eq!(error.context().unwrap().record(), Some(2));
+        let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(
+            text,
+            "id,address.city,address.zip,tags.0,tags.1\n1,Oslo,1,a,b\n2,Oslo,,c,\n"
+        );
+    }
+
+    #[test]
+    fn test_flatten_read_out_of_order_columns() {
+        let options = CsvReadOptions {
+            flatten: true,
+            ..Default::default()
+        };
+        let text = "id,tags.1,address.city,tags.0\n1,b,Oslo,a\n";
+        let users: Vec<User> = read(Cursor::new(text), &options).unwrap();
+        assert_eq!(users[0].tags, ["a", "b"]);
+
+        let text = "id,tags.0,address.city,tags.2\n1,a,Oslo,c\n";
+        let error = read::<User>(Cursor::new(text), &options).unwrap_err();
+        assert!(matches!(error.kind(), Error::Flatten(_)));
+        assert_eq!(error.context().unwrap().field(), Some("tags"));
+    }
+
+    #[test]
+    fn test_flatten_read_requires_headers() {
+        let options = CsvReadOptions {
+            flatten: true,
+            has_headers: false,
+            ..Default::default()
+        };
+        let results: Vec<Result<User, Error>> =
+            read_iter(Cursor::new("1,Oslo,,a\n"), &options).collect();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            results[0].as_ref().map_err(Error::kind),
+            Err(Error::Flatten(_))
+        ));
+    }
 }
@@ -0,0 +1,102 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Deserializer};
+use serde_path_to_error::Segment;
+
+use crate::Error;
+
+/// A deserialization error along with the path to the field that failed.
+pub struct PathError<E> {
+    error: E,
+    segments: Vec<Segment>,
+}
+
+/// Deserializes a value while tracking the path to the current field.
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, PathError<D::Error>>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let segments = e.path().iter().cloned().collect();
+        PathError {
+            error: e.into_inner(),
+            segments,
+        }
+    })
+}
+
+/// Deserializes a value with `seed` while tracking the path to the current field.
+#[cfg(feature = "toml")]
+pub fn deserialize_seed<'de, D, S>(
+    deserializer: D,
+    seed: S,
+) -> Result<S::Value, PathError<D::Error>>
+where
+    D: Deserializer<'de>,
+    S: serde::de::DeserializeSeed<'de>,
+{
+    let mut track = serde_path_to_error::Track::new();
+    seed.deserialize(serde_path_to_error::Deserializer::new(
+        deserializer,
+        &mut track,
+    ))
+    .map_err(|error| PathError {
+        error,
+        segments: track.path().iter().cloned().collect(),
+    })
+}
+
+impl<E> PathError<E> {
+    /// Wraps an error that is not tied to a field, such as trailing input.
+    pub fn new(error: E) -> Self {
+        Self {
+            error,
+            segments: Vec::new(),
+        }
+    }
+
+    /// Converts the inner error and attaches the field path.
+    pub fn attach(self, convert: impl FnOnce(E) -> Error) -> Error {
+        with_field(convert(self.error), &self.segments)
+    }
+
+    /// Drops the leading segment of the path, for values nested under a key
+    /// of the root.
+    #[cfg(feature = "toml")]
+    pub fn nested(mut self) -> Self {
+        if !self.segments.is_empty() {
+            self.segments.remove(0);
+        }
+        self
+    }
+
+    /// Converts the inner error of a value that is a sequence of records, so
+    /// the leading index of the path identifies the record. `first` is the
+    /// index of the sequence's first element among all records.
+    pub fn attach_record(self, first: usize, convert: impl FnOnce(E) -> Error) -> Error {
+        let error = convert(self.error);
+        match self.segments.split_first() {
+            Some((Segment::Seq { index }, rest)) => {
+                with_field(error.with_record(first + index), rest)
+            }
+            _ => with_field(error, &self.segments),
+        }
+    }
+}
+
+/// Attaches the field path spelled by `segments`. Paths through a segment
+/// the tracker could not name, as after a syntax error, are dropped.
+fn with_field(error: Error, segments: &[Segment]) -> Error {
+    if segments.is_empty() || segments.iter().any(|s| matches!(s, Segment::Unknown)) {
+        return error;
+    }
+    let mut field = String::new();
+    for segment in segments {
+        if !field.is_empty() && !matches!(segment, Segment::Seq { .. }) {
+            field.push('.');
+        }
+        let _ = write!(field, "{segment}");
+    }
+    error.with_field(field)
+}
@@ -0,0 +1,654 @@
+//! Flattening of nested records into dotted column names such as
+//! `address.city` or `tags.0`, and rebuilding them from such columns.
+
+use serde::de::{
+    self, IntoDeserializer, Unexpected, Visitor,
+    value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer},
+};
+
+use crate::value::{Map, Value};
+
+pub type DeError = de::value::Error;
+
+/// Separator between the keys of a flattened column name.
+const SEPARATOR: char = '.';
+
+/// Flattens a serialized record into `(column, cell)` pairs.
+///
+/// Nulls, empty sequences and empty maps become a single empty cell.
+pub fn flatten(value: &Value) -> Vec<(String, String)> {
+    let mut cells = Vec::new();
+    flatten_into(value, String::new(), &mut cells);
+    cells
+}
+
+fn flatten_into(value: &Value, column: String, cells: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                flatten_into(value, join(&column, key), cells);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (index, value) in items.iter().enumerate() {
+                flatten_into(value, join(&column, &index.to_string()), cells);
+            }
+        }
+        Value::Bytes(bytes) if !bytes.is_empty() => {
+            for (index, byte) in bytes.iter().enumerate() {
+                cells.push((join(&column, &index.to_string()), byte.to_string()));
+            }
+        }
+        Value::Null | Value::Object(_) | Value::Array(_) | Value::Bytes(_) => {
+            cells.push((column, String::new()));
+        }
+        Value::String(s) | Value::Datetime(s) => cells.push((column, s.clone())),
+        Value::Bool(b) => cells.push((column, b.to_string())),
+        Value::Integer(i) => cells.push((column, i.to_string())),
+        Value::Unsigned(u) => cells.push((column, u.to_string())),
+        // Written as JSON would, with non-finite floats as empty cells.
+        Value::Float(f) => cells.push((
+            column,
+            serde_json::Number::from_f64(*f).map_or_else(String::new, |n| n.to_string()),
+        )),
+    }
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}{SEPARATOR}{key}")
+    }
+}
+
+/// Rebuilds a nested record from a row read as an object keyed by its
+/// dotted column names, the untyped counterpart of [`Node`].
+///
+/// Objects whose keys run `0`, `1`, ... in order become arrays without their
+/// trailing empty cells, and an empty cell gives way to nested columns.
+///
+/// # Errors
+///
+/// Returns an error if a column holds a value and also has nested columns,
+/// or if a column appears twice with different values.
+pub fn unflatten_row(row: Map) -> Result<Map, DeError> {
+    let mut record = Map::new();
+    for (column, cell) in row {
+        insert(&mut record, &column, cell)?;
+    }
+    Ok(record
+        .into_iter()
+        .map(|(key, value)| (key, into_arrays(value)))
+        .collect())
+}
+
+fn insert(mut members: &mut Map, column: &str, cell: Value) -> Result<(), DeError> {
+    let conflict = || de::Error::custom(format!("column `{column}` conflicts with another"));
+    let (parents, key) = match column.rsplit_once(SEPARATOR) {
+        Some((parents, key)) => (Some(parents), key),
+        None => (None, column),
+    };
+    for parent in parents
+        .into_iter()
+        .flat_map(|parents| parents.split(SEPARATOR))
+    {
+        let node = members
+            .entry(parent.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if is_empty_cell(node) {
+            *node = Value::Object(Map::new());
+        }
+        let Value::Object(children) = node else {
+            return Err(conflict());
+        };
+        members = children;
+    }
+    match members.get_mut(key) {
+        None => {
+            members.insert(key.to_string(), cell);
+        }
+        Some(existing) if is_empty_cell(existing) => *existing = cell,
+        Some(_) if is_empty_cell(&cell) => {}
+        Some(_) => return Err(conflict()),
+    }
+    Ok(())
+}
+
+fn is_empty_cell(value: &Value) -> bool {
+    matches!(value, Value::String(s) if s.is_empty())
+}
+
+/// Turns objects whose keys are the indices `0` to `n - 1`, in any order,
+/// into arrays.
+fn into_arrays(value: Value) -> Value {
+    match value {
+        Value::Object(members) if are_indices(members.keys().map(String::as_str)) => {
+            let mut items: Vec<_> = members
+                .into_iter()
+                .filter_map(|(key, value)| Some((parse_index(&key)?, into_arrays(value))))
+                .collect();
+            items.sort_by_key(|(index, _)| *index);
+            let mut items: Vec<_> = items.into_iter().map(|(_, value)| value).collect();
+            while items.last().is_some_and(is_empty_cell) {
+                items.pop();
+            }
+            Value::Array(items)
+        }
+        Value::Object(members) => Value::Object(
+            members
+                .into_iter()
+                .map(|(key, value)| (key, into_arrays(value)))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+/// Whether `keys` are the sequence indices `0` to `n - 1`, in any order.
+fn are_indices<'k>(keys: impl ExactSizeIterator<Item = &'k str>) -> bool {
+    let mut seen = vec![false; keys.len()];
+    !seen.is_empty() && { keys }.all(|key| {
+        parse_index(key)
+            .and_then(|index| seen.get_mut(index))
+            .is_some_and(|seen| !std::mem::replace(seen, true))
+    })
+}
+
+/// Parses a key written as a sequence index, such as the `1` of `tags.1`.
+fn parse_index(key: &str) -> Option<usize> {
+    key.parse()
+        .ok()
+        .filter(|index: &usize| index.to_string() == key)
+}
+
+/// Orders the columns of several flattened records, keeping each new column
+/// next to the columns that share its parent, so that `tags.2` follows
+/// `tags.1` even when it first appears in a later record.
+pub fn columns<'a>(records: impl IntoIterator<Item = &'a [(String, String)]>) -> Vec<&'a str> {
+    let mut columns: Vec<&str> = Vec::new();
+    for record in records {
+        for (column, _) in record {
+            if columns.contains(&column.as_str()) {
+                continue;
+            }
+            let sibling = column.rsplit_once(SEPARATOR).and_then(|(parent, _)| {
+                columns.iter().rposition(|c| {
+                    c.strip_prefix(parent)
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with(SEPARATOR))
+                })
+            });
+            match sibling {
+                Some(position) => columns.insert(position + 1, column),
+                None => columns.push(column),
+            }
+        }
+    }
+    columns
+}
+
+/// A record rebuilt from flattened cells, deserialized like the nested
+/// record it was flattened from.
+///
+/// Cells are parsed according to the type being deserialized. Empty cells
+/// stand for missing values: an all-empty branch deserializes as `None`, an
+/// empty sequence or an empty map, trailing empty elements of sequences are
+/// dropped, and empty entries of maps are skipped.
+#[derive(Debug)]
+pub enum Node<'a> {
+    Leaf(&'a str),
+    Branch(Vec<(&'a str, Node<'a>)>),
+}
+
+impl<'a> Node<'a> {
+    /// Rebuilds a record from `(column, cell)` pairs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a column holds a value and also has nested columns,
+    /// or if a column appears twice with different values.
+    pub fn unflatten(cells: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<Self, DeError> {
+        let mut root = Node::Branch(Vec::new());
+        for (column, cell) in cells {
+            root.insert(column, cell)?;
+        }
+        Ok(root)
+    }
+
+    fn insert(&mut self, column: &'a str, cell: &'a str) -> Result<(), DeError> {
+        let conflict = || de::Error::custom(format!("column `{column}` conflicts with another"));
+        let mut node = self;
+        for key in column.split(SEPARATOR) {
+            // An empty cell gives way to nested columns.
+            if matches!(node, Node::Leaf("")) {
+                *node = Node::Branch(Vec::new());
+            }
+            let Node::Branch(children) = node else {
+                return Err(conflict());
+            };
+            let position = match children.iter().position(|(k, _)| *k == key) {
+                Some(position) => position,
+                None => {
+                    children.push((key, Node::Leaf("")));
+                    children.len() - 1
+                }
+            };
+            node = &mut children[position].1;
+        }
+        match node {
+            Node::Leaf(existing) if existing.is_empty() => *existing = cell,
+            _ if cell.is_empty() => {}
+            _ => return Err(conflict()),
+        }
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Node::Leaf(cell) => cell.is_empty(),
+            Node::Branch(children) => children.iter().all(|(_, node)| node.is_empty()),
+        }
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, DeError> for Node<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Types a cell whose type is not known.
+///
+/// A cell is a boolean or a number only if it is written exactly as
+/// [`flatten`] writes that value, so that a zip code such as `01234` or an
+/// amount such as `1.50` stays the string it was.
+pub fn cell_value(cell: &str) -> Value {
+    if let Ok(b) = cell.parse::<bool>() {
+        return Value::Bool(b);
+    }
+    match cell.parse::<i64>() {
+        Ok(i) if i.to_string() == cell => return Value::Integer(i),
+        Ok(_) => {}
+        Err(_) => match cell.parse::<u64>() {
+            Ok(u) if u.to_string() == cell => return Value::Unsigned(u),
+            _ => {}
+        },
+    }
+    match cell
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+    {
+        Some(f) if f.to_string() == cell => Value::Float(f.as_f64().unwrap_or_default()),
+        _ => Value::String(cell.to_string()),
+    }
+}
+
+/// Deserializes a cell whose type is not known, such as a field of an
+/// untagged enum.
+fn infer<'de, V: Visitor<'de>>(cell: &str, visitor: V) -> Result<V::Value, DeError> {
+    if cell.is_empty() {
+        return visitor.visit_unit();
+    }
+    match cell_value(cell) {
+        Value::Bool(b) => visitor.visit_bool(b),
+        Value::Integer(i) => visitor.visit_i64(i),
+        Value::Unsigned(u) => visitor.visit_u64(u),
+        Value::Float(f) => visitor.visit_f64(f),
+        _ => visitor.visit_str(cell),
+    }
+}
+
+macro_rules! parse_cell {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+                match self {
+                    Node::Leaf(cell) => match cell.parse() {
+                        Ok(value) => visitor.$visit(value),
+                        Err(_) => Err(de::Error::invalid_value(Unexpected::Str(cell), &visitor)),
+                    },
+                    branch => branch.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! visit_str {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+                match self {
+                    Node::Leaf(cell) => visitor.visit_str(cell),
+                    branch => branch.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Node<'a> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            Node::Leaf(cell) => infer(cell, visitor),
+            Node::Branch(children) if are_indices(children.iter().map(|(key, _)| *key)) => {
+                Node::Branch(children).deserialize_seq(visitor)
+            }
+            Node::Branch(children) => visitor.visit_map(MapDeserializer::new(children.into_iter())),
+        }
+    }
+
+    parse_cell! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    visit_str! {
+        deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_identifier
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        if self.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        if self.is_empty() {
+            visitor.visit_unit()
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            Node::Leaf("") => visitor.visit_seq(SeqDeserializer::new(std::iter::empty::<Self>())),
+            Node::Leaf(_) => self.deserialize_any(visitor),
+            Node::Branch(children) => {
+                // Elements are ordered by index, whatever the column order.
+                let mut items = children
+                    .into_iter()
+                    .map(|(key, node)| match parse_index(key) {
+                        Some(index) => Ok((index, node)),
+                        None => Err(de::Error::invalid_value(
+                            Unexpected::Str(key),
+                            &"a sequence index",
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, DeError>>()?;
+                items.sort_by_key(|(index, _)| *index);
+                while items.last().is_some_and(|(_, node)| node.is_empty()) {
+                    items.pop();
+                }
+                if let Some(missing) = items
+                    .iter()
+                    .enumerate()
+                    .position(|(i, (index, _))| *index != i)
+                {
+                    return Err(de::Error::custom(format!(
+                        "sequence element {missing} is missing"
+                    )));
+                }
+                let items = items.into_iter().map(|(_, node)| node);
+                visitor.visit_seq(SeqDeserializer::new(items))
+            }
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            Node::Leaf("") => {
+                visitor.visit_map(MapDeserializer::new(std::iter::empty::<(&str, Self)>()))
+            }
+            Node::Leaf(_) => self.deserialize_any(visitor),
+            Node::Branch(children) => {
+                let entries = children.into_iter().filter(|(_, node)| !node.is_empty());
+                visitor.visit_map(MapDeserializer::new(entries))
+            }
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        match self {
+            Node::Leaf("") => Node::Branch(Vec::new()).deserialize_any(visitor),
+            node => node.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        match self {
+            Node::Leaf(cell) => visitor.visit_enum(cell.into_deserializer()),
+            Node::Branch(children) => visitor.visit_enum(MapAccessDeserializer::new(
+                MapDeserializer::new(children.into_iter()),
+            )),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::{Node, cell_value, columns, flatten, unflatten_row};
+    use crate::value::{Map, Value, to_value};
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Address {
+        city: String,
+        zip: Option<u32>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct User {
+        id: u32,
+        address: Option<Address>,
+        tags: Vec<String>,
+        scores: BTreeMap<String, f64>,
+    }
+
+    fn round_trip(user: &User, columns: &[&str]) -> User {
+        let cells = flatten(&to_value(user).unwrap());
+        let cells: Vec<(&str, &str)> = columns
+            .iter()
+            .map(|column| {
+                let cell = cells.iter().find(|(c, _)| c == column);
+                (*column, cell.map_or("", |(_, cell)| cell.as_str()))
+            })
+            .collect();
+        User::deserialize(Node::unflatten(cells).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_flatten_round_trip() {
+        let user = User {
+            id: 1,
+            address: Some(Address {
+                city: "Paris".to_string(),
+                zip: None,
+            }),
+            tags: vec!["a".to_string(), "b".to_string()],
+            scores: BTreeMap::from([("math".to_string(), 1.5)]),
+        };
+        let cells = flatten(&to_value(&user).unwrap());
+        let names: Vec<&str> = cells.iter().map(|(c, _)| c.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "id",
+                "address.city",
+                "address.zip",
+                "tags.0",
+                "tags.1",
+                "scores.math"
+            ]
+        );
+        assert_eq!(round_trip(&user, &names), user);
+
+        // Columns of other records are empty for this one.
+        let sparse = User {
+            id: 2,
+            address: None,
+            tags: vec!["c".to_string()],
+            scores: BTreeMap::new(),
+        };
+        let names = [
+            "id",
+            "address",
+            "address.city",
+            "address.zip",
+            "tags.0",
+            "tags.1",
+            "scores",
+            "scores.math",
+        ];
+        assert_eq!(round_trip(&sparse, &names), sparse);
+    }
+
+    #[test]
+    fn test_columns() {
+        let first = vec![
+            ("id".to_string(), "1".to_string()),
+            ("tags.0".to_string(), "a".to_string()),
+            ("name".to_string(), "x".to_string()),
+        ];
+        let second = vec![
+            ("id".to_string(), "2".to_string()),
+            ("tags.0".to_string(), "b".to_string()),
+            ("tags.1".to_string(), "c".to_string()),
+            ("name".to_string(), "y".to_string()),
+        ];
+        assert_eq!(
+            columns([first.as_slice(), second.as_slice()]),
+            ["id", "tags.0", "tags.1", "name"]
+        );
+    }
+
+    #[test]
+    fn test_unflatten_sequence_order() {
+        let node = Node::unflatten([("tags.1", "b"), ("tags.0", "a"), ("tags.2", "")]).unwrap();
+        let tags: BTreeMap<String, Vec<String>> = BTreeMap::deserialize(node).unwrap();
+        assert_eq!(tags["tags"], ["a", "b"]);
+
+        let node = Node::unflatten([("tags.0", "a"), ("tags.2", "c")]).unwrap();
+        let error = BTreeMap::<String, Vec<String>>::deserialize(node).unwrap_err();
+        assert!(error.to_string().contains("element 1 is missing"));
+
+        let node = Node::unflatten([("tags.0", "a"), ("tags.x", "c")]).unwrap();
+        assert!(BTreeMap::<String, Vec<String>>::deserialize(node).is_err());
+    }
+
+    #[test]
+    fn test_cell_value() {
+        assert_eq!(cell_value("true"), Value::Bool(true));
+        assert_eq!(cell_value("-12"), Value::Integer(-12));
+        assert_eq!(
+            cell_value("18446744073709551615"),
+            Value::Unsigned(u64::MAX)
+        );
+        assert_eq!(cell_value("0.5"), Value::Float(0.5));
+        assert_eq!(cell_value("2.0"), Value::Float(2.0));
+        for cell in ["", "01234", "+1", "-0", "1.50", "1e3", "NaN", " 1", "True"] {
+            assert_eq!(cell_value(cell), Value::String(cell.to_string()), "{cell}");
+        }
+    }
+
+    #[test]
+    fn test_unflatten_conflict() {
+        let error = Node::unflatten([("a", "1"), ("a.b", "2")]).unwrap_err();
+        assert!(error.to_string().contains("a.b"));
+    }
+
+    #[test]
+    fn test_unflatten_row() {
+        let mut row = Map::new();
+        row.insert("id".to_string(), Value::Integer(1));
+        row.insert(
+            "address.city".to_string(),
+            Value::String("Oslo".to_string()),
+        );
+        row.insert("tags.1".to_string(), Value::String("b".to_string()));
+        row.insert("tags.0".to_string(), Value::String("a".to_string()));
+        row.insert("tags.2".to_string(), Value::String(String::new()));
+        row.insert("note".to_string(), Value::String(String::new()));
+        let record = Value::Object(unflatten_row(row).unwrap());
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            r#"{"id":1,"address":{"city":"Oslo"},"tags":["a","b"],"note":""}"#
+        );
+
+        let mut row = Map::new();
+        row.insert("a".to_string(), Value::Integer(1));
+        row.insert("a.b".to_string(), Value::Integer(2));
+        assert!(unflatten_row(row).unwrap_err().to_string().contains("a.b"));
+    }
+}
@@ -1,29 +1,413 @@
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::{
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    marker::PhantomData,
+};
 
 use serde::{Serialize, de::DeserializeOwned};
+use serde_json::ser::PrettyFormatter;
 
-use crate::Error;
+use super::field_path::{self, PathError};
+use crate::{Error, error::offset_of, options::JsonWriteOptions};
 
 pub fn read<T: DeserializeOwned>(reader: impl Read) -> Result<T, Error> {
-    let reader = BufReader::new(reader);
-    Ok(serde_json::from_reader(reader)?)
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    deserialize(&mut deserializer).map_err(|e| {
+        e.attach(|e| {
+            let (line, column) = (e.line() as u64, e.column() as u64);
+            let error = Error::from(e);
+            match line {
+                0 => error,
+                _ => error.with_position(Some(line), Some(column), None),
+            }
+        })
+    })
 }
 
-pub fn write<T: Serialize>(writer: impl Write, record: &T) -> Result<(), Error> {
+/// Deserializes one complete JSON value, tracking the failing field path.
+fn deserialize<'de, R: serde_json::de::Read<'de>, T: DeserializeOwned>(
+    deserializer: &mut serde_json::Deserializer<R>,
+) -> Result<T, PathError<serde_json::Error>> {
+    let value = field_path::deserialize(&mut *deserializer)?;
+    deserializer.end().map_err(PathError::new)?;
+    Ok(value)
+}
+
+/// Returns the text of a record for error reports.
+pub(crate) fn raw_text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).trim_end().to_string()
+}
+
+/// Decodes a value from `text`, which starts at `start`, attaching the field
+/// path and absolute position of any error.
+pub(crate) fn from_slice<T: DeserializeOwned>(text: &[u8], start: Position) -> Result<T, Error> {
+    deserialize(&mut serde_json::Deserializer::from_slice(text))
+        .map_err(|e| e.attach(|e| locate(e, text, start)))
+}
+
+/// Lazily decodes the elements of a top-level JSON array.
+pub fn read_iter<T: DeserializeOwned>(reader: impl Read) -> impl Iterator<Item = Result<T, Error>> {
+    ArrayIter::new(BufReader::new(reader))
+}
+
+/// Absolute position in a text input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Position {
+    pub line: u64,
+    pub column: u64,
+    pub offset: u64,
+}
+
+impl Position {
+    pub fn start() -> Self {
+        Self {
+            line: 1,
+            column: 1,
+            offset: 0,
+        }
+    }
+
+    /// Moves the position past `bytes`.
+    pub fn advance(&mut self, bytes: &[u8]) {
+        self.offset += bytes.len() as u64;
+        match bytes.iter().rposition(|b| *b == b'\n') {
+            Some(last) => {
+                self.line += bytes.iter().filter(|b| **b == b'\n').count() as u64;
+                self.column = (bytes.len() - last) as u64;
+            }
+            None => self.column += bytes.len() as u64,
+        }
+    }
+
+    pub fn attach(self, error: Error) -> Error {
+        error.with_position(Some(self.line), Some(self.column), Some(self.offset))
+    }
+}
+
+/// Converts an error raised while decoding `text`, which starts at `start`,
+/// and attaches the absolute position `serde_json` reported.
+pub(crate) fn locate(error: serde_json::Error, text: &[u8], start: Position) -> Error {
+    let (line, column) = (error.line(), error.column());
+    let mut position = start;
+    if line > 0 {
+        position.advance(&text[..offset_of(text, line, column)]);
+    }
+    position.attach(error.into())
+}
+
+fn syntax_error(msg: &str) -> Error {
+    Error::Json(serde::de::Error::custom(msg))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArrayState {
+    Start,
+    Element,
+    Separator,
+    Done,
+}
+
+/// Walks a top-level JSON array and decodes one element at a time.
+///
+/// Only the raw bytes of the current element are buffered: the scanner finds
+/// the element boundary by tracking nesting depth and string literals, and the
+/// buffered slice is then handed to `serde_json`.
+struct ArrayIter<R, T> {
+    reader: R,
+    buf: Vec<u8>,
+    state: ArrayState,
+    position: Position,
+    index: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: BufRead, T: DeserializeOwned> ArrayIter<R, T> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            state: ArrayState::Start,
+            position: Position::start(),
+            index: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), Error> {
+        loop {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                return Ok(());
+            }
+            let n = available
+                .iter()
+                .take_while(|b| b.is_ascii_whitespace())
+                .count();
+            let exhausted = n == available.len();
+            self.position.advance(&available[..n]);
+            self.reader.consume(n);
+            if !exhausted {
+                return Ok(());
+            }
+        }
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, Error> {
+        self.skip_whitespace()?;
+        let byte = self.reader.fill_buf()?.first().copied();
+        if let Some(b) = byte {
+            self.position.advance(&[b]);
+            self.reader.consume(1);
+        }
+        Ok(byte)
+    }
+
+    fn peek_byte(&mut self) -> Result<Option<u8>, Error> {
+        self.skip_whitespace()?;
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    /// Copies the raw bytes of the next value into `buf`, stopping right
+    /// before the `,` or `]` that terminates it.
+    fn scan_value(&mut self) -> Result<(), Error> {
+        self.buf.clear();
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        loop {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                return Ok(());
+            }
+            let mut end = None;
+            for (i, &b) in available.iter().enumerate() {
+                if in_string {
+                    if escaped {
+                        escaped = false;
+                    } else if b == b'\\' {
+                        escaped = true;
+                    } else if b == b'"' {
+                        in_string = false;
+                        if depth == 0 {
+                            end = Some(i + 1);
+                            break;
+                        }
+                    }
+                    continue;
+                }
+                match b {
+                    b'"' => in_string = true,
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' if depth == 0 => {
+                        end = Some(i);
+                        break;
+                    }
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            end = Some(i + 1);
+                            break;
+                        }
+                    }
+                    b',' if depth == 0 => {
+                        end = Some(i);
+                        break;
+                    }
+                    _ if depth == 0 && b.is_ascii_whitespace() => {
+                        end = Some(i);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            let n = end.unwrap_or(available.len());
+            self.buf.extend_from_slice(&available[..n]);
+            self.position.advance(&available[..n]);
+            self.reader.consume(n);
+            if end.is_some() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn syntax_error(&self, msg: &str) -> Error {
+        self.position.attach(syntax_error(msg))
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        self.state = ArrayState::Done;
+        match self.peek_byte()? {
+            None => Ok(()),
+            Some(_) => Err(self.syntax_error("trailing characters after JSON array")),
+        }
+    }
+
+    /// Moves to the next element. The outer error ends the array, while the
+    /// inner one only rejects the element it was decoded from.
+    fn advance(&mut self) -> Result<Option<Result<T, Error>>, Error> {
+        loop {
+            match self.state {
+                ArrayState::Done => return Ok(None),
+                ArrayState::Start => match self.peek_byte()? {
+                    Some(b'[') => {
+                        self.next_byte()?;
+                        if self.peek_byte()? == Some(b']') {
+                            self.next_byte()?;
+                            self.finish()?;
+                            return Ok(None);
+                        }
+                        self.state = ArrayState::Element;
+                    }
+                    Some(_) => return Err(self.syntax_error("expected a JSON array")),
+                    None => return Err(self.syntax_error("EOF while parsing a list")),
+                },
+                ArrayState::Separator => match self.peek_byte()? {
+                    Some(b',') => {
+                        self.next_byte()?;
+                        self.state = ArrayState::Element;
+                    }
+                    Some(b']') => {
+                        self.next_byte()?;
+                        self.finish()?;
+                        return Ok(None);
+                    }
+                    Some(_) => return Err(self.syntax_error("expected `,` or `]`")),
+                    None => return Err(self.syntax_error("EOF while parsing a list")),
+                },
+                ArrayState::Element => {
+                    self.skip_whitespace()?;
+                    let start = self.position;
+                    let index = self.index;
+                    self.index += 1;
+                    self.scan_value()?;
+                    self.state = ArrayState::Separator;
+                    return Ok(Some(
+                        from_slice(&self.buf, start)
+                            .map_err(|e| e.with_record(index).with_raw(raw_text(&self.buf))),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead, T: DeserializeOwned> Iterator for ArrayIter<R, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.advance() {
+            Ok(record) => record,
+            Err(e) => {
+                self.state = ArrayState::Done;
+                Some(Err(self.position.attach(e)))
+            }
+        }
+    }
+}
+
+pub fn write<T: Serialize>(
+    writer: impl Write,
+    record: &T,
+    options: &JsonWriteOptions,
+) -> Result<(), Error> {
     let mut writer = BufWriter::new(writer);
-    serde_json::to_writer(&mut writer, record)?;
+    let indent = options.pretty.then(|| options.indent());
+    encode(&mut writer, record, indent.as_deref())?;
     writer.flush()?;
     Ok(())
 }
 
+/// Serializes `record` compactly, or pretty-printed with `indent` per level.
+fn encode<T: Serialize>(writer: impl Write, record: &T, indent: Option<&str>) -> Result<(), Error> {
+    match indent {
+        Some(indent) => {
+            let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+            let mut serializer = serde_json::Serializer::with_formatter(writer, formatter);
+            record.serialize(&mut serializer)?;
+        }
+        None => serde_json::to_writer(writer, record)?,
+    }
+    Ok(())
+}
+
+/// Writes records as a JSON array without collecting them first.
+pub fn write_seq<'a, T: Serialize + 'a>(
+    writer: impl Write,
+    records: impl IntoIterator<Item = &'a T>,
+    options: &JsonWriteOptions,
+) -> Result<(), Error> {
+    let mut writer = SeqWriter::new(writer, options)?;
+    for record in records {
+        writer.write(record)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Incrementally writes records as the elements of a JSON array.
+///
+/// Pretty output matches `serde_json::to_string_pretty` of the whole array.
+pub struct SeqWriter<W: Write> {
+    writer: BufWriter<W>,
+    /// Indentation of one level, when pretty-printing.
+    indent: Option<String>,
+    empty: bool,
+}
+
+impl<W: Write> SeqWriter<W> {
+    pub fn new(writer: W, options: &JsonWriteOptions) -> Result<Self, Error> {
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(b"[")?;
+        Ok(Self {
+            writer,
+            indent: options.pretty.then(|| options.indent()),
+            empty: true,
+        })
+    }
+
+    pub fn write<T: Serialize>(&mut self, record: &T) -> Result<(), Error> {
+        if !self.empty {
+            self.writer.write_all(b",")?;
+        }
+        match &self.indent {
+            Some(indent) => {
+                // Line breaks only occur between tokens, since strings escape
+                // them, so every line of the element can be indented.
+                let mut element = Vec::new();
+                encode(&mut element, record, Some(indent))?;
+                write!(self.writer, "\n{indent}")?;
+                for (i, line) in element.split(|&b| b == b'\n').enumerate() {
+                    if i > 0 {
+                        write!(self.writer, "\n{indent}")?;
+                    }
+                    self.writer.write_all(line)?;
+                }
+            }
+            None => serde_json::to_writer(&mut self.writer, record)?,
+        }
+        self.empty = false;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W, Error> {
+        if self.indent.is_some() && !self.empty {
+            self.writer.write_all(b"\n")?;
+        }
+        self.writer.write_all(b"]")?;
+        Ok(self.writer.into_inner().map_err(|e| e.into_error())?)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::io::{self, Write};
 
-    use serde::Serialize;
+    use std::io::Cursor;
 
-    use super::write;
+    use serde::{Deserialize, Serialize};
+
+    use super::{SeqWriter, read_iter, write};
     use crate::Error;
+    use crate::options::JsonWriteOptions;
 
     /// Writer that succeeds on every write but fails on flush, so a
     /// correct implementation must surface the flush error instead of
@@ -43,7 +427,7 @@ mod test {
         }
     }
 
-    #[derive(Serialize)]
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
     struct Point {
         x: i32,
         y: i32,
@@ -52,10 +436,183 @@ mod test {
     #[test]
     fn test_write_flush_error_propagates() {
         let writer = FlushFailingWriter { inner: Vec::new() };
-        let result = write(writer, &Point { x: 1, y: 2 });
+        let result = write(writer, &Point { x: 1, y: 2 }, &Default::default());
         assert!(
             matches!(result, Err(Error::Io(_))),
             "expected flush error to propagate, got {result:?}"
         );
     }
+
+    #[test]
+    fn test_read_iter() {
+        let data = r#" [ {"x": 1, "y": [2]} , {"x": 3, "y": "a,]}"} ] "#;
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Record {
+            x: i32,
+            y: serde_json::Value,
+        }
+        let records: Vec<Record> = read_iter(Cursor::new(data))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            records,
+            vec![
+                Record {
+                    x: 1,
+                    y: serde_json::json!([2])
+                },
+                Record {
+                    x: 3,
+                    y: serde_json::json!("a,]}")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_iter_scalars_and_empty() {
+        let values: Vec<f64> = read_iter(Cursor::new("[1,-2 ,3e0]"))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(values, vec![1.0, -2.0, 3.0]);
+
+        let values: Vec<Point> = read_iter(Cursor::new(" [ ] "))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn test_read_iter_errors() {
+        for data in [r#"{"x": 1, "y": 2}"#, "[1, 2", "[1,]", "[1] x", "[1 2]"] {
+            let result: Result<Vec<i32>, Error> = read_iter(Cursor::new(data)).collect();
+            assert!(
+                matches!(result.as_ref().map_err(Error::kind), Err(Error::Json(_))),
+                "{data}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_iter_ends_after_syntax_error() {
+        for (data, records) in [("[1 2]", 2), ("[1, 2", 3), ("[1] x", 2), ("x", 1)] {
+            let mut iter = read_iter::<i32>(Cursor::new(data));
+            let results: Vec<_> = iter.by_ref().take(10).collect();
+            assert_eq!(results.len(), records, "{data}: {results:?}");
+            assert!(results.last().unwrap().is_err(), "{data}");
+            assert!(iter.next().is_none(), "{data}");
+        }
+    }
+
+    #[test]
+    fn test_read_iter_continues_after_invalid_element() {
+        let data = r#"[{"x": 1, "y": 2}, {"x": "a", "y": 2}, {"x": 3, "y": 4}]"#;
+        let results: Vec<Result<Point, Error>> = read_iter(Cursor::new(data)).collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap(), &Point { x: 3, y: 4 });
+    }
+
+    #[test]
+    fn test_read_iter_error_position() {
+        let data = "[\n  {\"x\": 1, \"y\": 2},\n  {\"x\": 3,\n   \"y\": true}\n]";
+        let results: Vec<Result<Point, Error>> = read_iter(Cursor::new(data)).collect();
+        let context = results[1].as_ref().unwrap_err().context().unwrap();
+        assert_eq!(context.record(), Some(1));
+        assert_eq!(context.field(), Some("y"));
+        assert_eq!(context.line(), Some(4));
+        assert_eq!(context.column(), Some(12));
+        assert_eq!(&data[context.offset().unwrap() as usize - 3..][..4], "true");
+
+        let result: Result<Vec<i32>, Error> = read_iter(Cursor::new("[1,\n 2 x]")).collect();
+        let context = result.unwrap_err().context().cloned().unwrap();
+        assert_eq!(
+            (context.line(), context.column(), context.offset()),
+            (Some(2), Some(4), Some(7))
+        );
+    }
+
+    #[test]
+    fn test_read_field_path() {
+        #[derive(Debug, Deserialize)]
+        struct Tls {
+            #[allow(dead_code)]
+            port: u16,
+        }
+        #[derive(Debug, Deserialize)]
+        struct Server {
+            #[allow(dead_code)]
+            tls: Tls,
+        }
+        #[derive(Debug, Deserialize)]
+        struct Config {
+            #[allow(dead_code)]
+            servers: Vec<Server>,
+        }
+
+        let data = r#"{"servers": [{"tls": {"port": 1}}, {"tls": {"port": "abc"}}]}"#;
+        let error = super::read::<Config>(Cursor::new(data)).unwrap_err();
+        assert_eq!(
+            error.context().unwrap().field(),
+            Some("servers[1].tls.port")
+        );
+        assert!(error.to_string().contains("field servers[1].tls.port"));
+
+        let error = super::read::<Config>(Cursor::new("{bad")).unwrap_err();
+        assert_eq!(error.context().unwrap().field(), None);
+        assert!(!error.to_string().contains("field"));
+    }
+
+    #[test]
+    fn test_seq_writer() {
+        let mut writer = SeqWriter::new(Vec::new(), &Default::default()).unwrap();
+        let data = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(data, "[]");
+
+        writer = SeqWriter::new(Vec::new(), &Default::default()).unwrap();
+        writer.write(&Point { x: 1, y: 2 }).unwrap();
+        writer.write(&Point { x: 3, y: 4 }).unwrap();
+        let data = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(data, r#"[{"x":1,"y":2},{"x":3,"y":4}]"#);
+    }
+
+    #[test]
+    fn test_pretty() {
+        let points = vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }];
+        let options = JsonWriteOptions {
+            pretty: true,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        write(&mut buffer, &points[0], &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            serde_json::to_string_pretty(&points[0]).unwrap()
+        );
+
+        let mut writer = SeqWriter::new(Vec::new(), &options).unwrap();
+        for point in &points {
+            writer.write(point).unwrap();
+        }
+        let data = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(data, serde_json::to_string_pretty(&points).unwrap());
+
+        let data = SeqWriter::new(Vec::new(), &options)
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(data, b"[]");
+
+        let options = JsonWriteOptions {
+            pretty: true,
+            indent_width: 1,
+            indent_char: '\t',
+        };
+        let mut buffer = Vec::new();
+        write(&mut buffer, &points[0], &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{\n\t\"x\": 1,\n\t\"y\": 2\n}"
+        );
+    }
 }
@@ -1,44 +1,125 @@
-use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::{
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    marker::PhantomData,
+};
 
 use serde::{Serialize, de::DeserializeOwned};
 
+use super::json::{Position, from_slice, raw_text};
 use crate::Error;
 
 pub fn read<T: DeserializeOwned>(reader: impl Read) -> Result<Vec<T>, Error> {
-    let reader = BufReader::new(reader);
-    let mut records: Vec<T> = Vec::new();
-    for line in reader.lines() {
-        let line = line?;
-        if line.trim().is_empty() {
-            continue;
+    read_iter(reader).collect()
+}
+
+pub fn read_iter<T: DeserializeOwned>(reader: impl Read) -> impl Iterator<Item = Result<T, Error>> {
+    read_located_iter(reader).map(|record| record.map(|(_, record)| record))
+}
+
+/// Reads records together with the one-based line each one is on.
+pub fn read_located_iter<T: DeserializeOwned>(
+    reader: impl Read,
+) -> impl Iterator<Item = Result<(u64, T), Error>> {
+    LineIter {
+        reader: BufReader::new(reader),
+        line: Vec::new(),
+        position: Position::start(),
+        index: 0,
+        done: false,
+        _marker: PhantomData,
+    }
+}
+
+/// Decodes one record per non-blank line, tracking the absolute position of
+/// each line so errors can point into the whole input.
+struct LineIter<R, T> {
+    reader: R,
+    line: Vec<u8>,
+    position: Position,
+    index: usize,
+    done: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: BufRead, T: DeserializeOwned> Iterator for LineIter<R, T> {
+    type Item = Result<(u64, T), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        loop {
+            self.line.clear();
+            let start = self.position;
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => {
+                    self.done = true;
+                    return None;
+                }
+                Ok(_) => self.position.advance(&self.line),
+                // An I/O error would recur on every call.
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(start.attach(e.into())));
+                }
+            }
+            if self.line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let index = self.index;
+            self.index += 1;
+            return Some(
+                from_slice(&self.line, start)
+                    .map(|record| (start.line, record))
+                    .map_err(|e| e.with_record(index).with_raw(raw_text(&self.line))),
+            );
         }
-        let record: T = serde_json::from_str(&line)?;
-        records.push(record);
     }
-    Ok(records)
 }
 
 pub fn write<'a, T: Serialize + 'a>(
     writer: impl Write,
     records: impl IntoIterator<Item = &'a T>,
 ) -> Result<(), Error> {
-    let mut writer = BufWriter::new(writer);
+    let mut writer = SeqWriter::new(writer);
     for record in records {
-        let line = serde_json::to_string(record)?;
-        writer.write_all(line.as_bytes())?;
-        writer.write_all(b"\n")?;
+        writer.write(record)?;
     }
-    writer.flush()?;
+    writer.finish()?;
     Ok(())
 }
 
+/// Incrementally writes records as newline-terminated JSON lines.
+pub struct SeqWriter<W: Write> {
+    writer: BufWriter<W>,
+}
+
+impl<W: Write> SeqWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+        }
+    }
+
+    pub fn write<T: Serialize>(&mut self, record: &T) -> Result<(), Error> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<W, Error> {
+        Ok(self.writer.into_inner().map_err(|e| e.into_error())?)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use std::io::Cursor;
+    use std::io::{self, Cursor, Read};
 
     use serde::{Deserialize, Serialize};
 
-    use super::{read, write};
+    use super::{read, read_iter, write};
+    use crate::Error;
 
     #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
     struct Record {
@@ -121,4 +202,44 @@ mod test {
 
         assert_eq!(expected, records);
     }
+
+    #[test]
+    fn test_read_error_position() {
+        let data =
+            "{\"id\":1,\"name\":\"foo\",\"items\":[]}\n\n{\"id\":2,\"name\":\"bar\",\"items\":[}\n";
+        let results: Vec<Result<Record, Error>> = read_iter(Cursor::new(data)).collect();
+        assert_eq!(results.len(), 2);
+        let error = results[1].as_ref().unwrap_err();
+        assert!(matches!(error.kind(), Error::Json(_)));
+        let context = error.context().unwrap();
+        assert_eq!(context.record(), Some(1));
+        assert_eq!(context.field(), Some("items[0]"));
+        assert_eq!(context.line(), Some(3));
+        assert_eq!(context.column(), Some(31));
+        assert_eq!(context.offset(), Some(64));
+    }
+
+    #[test]
+    fn test_read_iter_ends_after_io_error() {
+        /// Reader that fails on every read once its data is exhausted.
+        struct FailingReader(Cursor<&'static str>);
+
+        impl Read for FailingReader {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                match self.0.read(buf)? {
+                    0 => Err(io::Error::other("read failed")),
+                    n => Ok(n),
+                }
+            }
+        }
+
+        let reader = FailingReader(Cursor::new("{\"id\":1,\"name\":\"foo\",\"items\":[]}\n"));
+        let results: Vec<Result<Record, Error>> = read_iter(reader).take(10).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            results[1].as_ref().map_err(Error::kind),
+            Err(Error::Io(_))
+        ));
+    }
 }
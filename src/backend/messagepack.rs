@@ -2,27 +2,205 @@ use std::io::{BufReader, BufWriter, Read, Write};
 
 use serde::{Serialize, de::DeserializeOwned};
 
-use crate::Error;
+use super::field_path;
+use crate::{
+    Error,
+    options::{MessagePackReadOptions, MessagePackWriteOptions},
+};
 
-pub fn read<T: DeserializeOwned>(reader: impl Read) -> Result<T, Error> {
-    let reader = BufReader::new(reader);
-    Ok(rmp_serde::decode::from_read(reader)?)
+pub fn read<T: DeserializeOwned>(
+    reader: impl Read,
+    options: &MessagePackReadOptions,
+) -> Result<T, Error> {
+    let mut deserializer = rmp_serde::Deserializer::new(BufReader::new(reader));
+    deserializer.set_max_depth(options.max_depth);
+    field_path::deserialize(&mut deserializer).map_err(|e| e.attach(Error::from))
 }
 
-pub fn write<T: Serialize>(writer: impl Write, record: &T) -> Result<(), Error> {
+/// Decodes a MessagePack array of records and yields them one at a time.
+///
+/// The format is length-prefixed and has no record boundaries to resync on,
+/// so the whole array is decoded up front; a failure is yielded as a single
+/// error that identifies the offending record.
+pub fn read_iter<R: Read, T: DeserializeOwned>(
+    reader: R,
+    options: &MessagePackReadOptions,
+) -> impl Iterator<Item = Result<T, Error>> + use<R, T> {
+    let mut deserializer = rmp_serde::Deserializer::new(BufReader::new(reader));
+    deserializer.set_max_depth(options.max_depth);
+    let records: Result<Vec<T>, Error> =
+        field_path::deserialize(&mut deserializer).map_err(|e| e.attach_record(0, Error::from));
+    let (records, error) = match records {
+        Ok(records) => (records, None),
+        Err(e) => (Vec::new(), Some(Err(e))),
+    };
+    records.into_iter().map(Ok).chain(error)
+}
+
+pub fn write<T: Serialize>(
+    writer: impl Write,
+    record: &T,
+    options: &MessagePackWriteOptions,
+) -> Result<(), Error> {
     let mut writer = BufWriter::new(writer);
-    rmp_serde::encode::write(&mut writer, record)?;
+    encode(&mut writer, record, options.named_fields)?;
     writer.flush()?;
     Ok(())
 }
 
+fn encode<T: Serialize>(
+    writer: &mut impl Write,
+    record: &T,
+    named_fields: bool,
+) -> Result<(), Error> {
+    if named_fields {
+        rmp_serde::encode::write_named(writer, record)?;
+    } else {
+        rmp_serde::encode::write(writer, record)?;
+    }
+    Ok(())
+}
+
+/// Writes records as a MessagePack array, encoding them straight to the
+/// writer after the array header.
+///
+/// The header needs the number of records. When the iterator does not report
+/// an exact length through its size hint, references to the records are
+/// collected first to count them.
+pub fn write_seq<'a, T: Serialize + 'a>(
+    writer: impl Write,
+    records: impl IntoIterator<Item = &'a T>,
+    options: &MessagePackWriteOptions,
+) -> Result<(), Error> {
+    let records = records.into_iter();
+    match records.size_hint() {
+        (lower, Some(upper)) if lower == upper => write_all(writer, lower, records, options),
+        _ => {
+            let records: Vec<&T> = records.collect();
+            write_all(writer, records.len(), records, options)
+        }
+    }
+}
+
+fn write_all<'a, T: Serialize + 'a>(
+    writer: impl Write,
+    len: usize,
+    records: impl IntoIterator<Item = &'a T>,
+    options: &MessagePackWriteOptions,
+) -> Result<(), Error> {
+    let mut writer = SeqWriter::with_len(writer, len, options)?;
+    for record in records {
+        writer.write(record)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+fn length_error(msg: &str) -> Error {
+    Error::MessagePackEncode(<rmp_serde::encode::Error as serde::ser::Error>::custom(msg))
+}
+
+enum SeqLen {
+    /// The header is not written yet; records are encoded into `body`.
+    Buffered { body: Vec<u8>, len: u32 },
+    /// The header announcing `expected` records is already written.
+    Streaming { expected: u32, written: u32 },
+}
+
+/// Incrementally writes records as the elements of a MessagePack array.
+///
+/// MessagePack arrays are length-prefixed. When the number of records is
+/// known in advance ([`SeqWriter::with_len`]) the header is written first and
+/// records stream straight through. Otherwise ([`SeqWriter::new`]) the
+/// records, which are only borrowed, are kept in encoded form until
+/// [`SeqWriter::finish`] writes the header followed by them, since a plain
+/// writer cannot go back to fill in the header.
+pub struct SeqWriter<W: Write> {
+    writer: BufWriter<W>,
+    len: SeqLen,
+    named_fields: bool,
+}
+
+impl<W: Write> SeqWriter<W> {
+    pub fn new(writer: W, options: &MessagePackWriteOptions) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            len: SeqLen::Buffered {
+                body: Vec::new(),
+                len: 0,
+            },
+            named_fields: options.named_fields,
+        }
+    }
+
+    pub fn with_len(
+        writer: W,
+        len: usize,
+        options: &MessagePackWriteOptions,
+    ) -> Result<Self, Error> {
+        let expected = u32::try_from(len).map_err(|_| length_error("too many records"))?;
+        let mut writer = BufWriter::new(writer);
+        rmp::encode::write_array_len(&mut writer, expected)
+            .map_err(rmp_serde::encode::Error::from)?;
+        Ok(Self {
+            writer,
+            len: SeqLen::Streaming {
+                expected,
+                written: 0,
+            },
+            named_fields: options.named_fields,
+        })
+    }
+
+    pub fn write<T: Serialize>(&mut self, record: &T) -> Result<(), Error> {
+        match &mut self.len {
+            SeqLen::Buffered { body, len } => {
+                *len = len
+                    .checked_add(1)
+                    .ok_or_else(|| length_error("too many records"))?;
+                encode(body, record, self.named_fields)?;
+            }
+            SeqLen::Streaming { expected, written } => {
+                if written == expected {
+                    return Err(length_error("more records than the announced array length"));
+                }
+                *written += 1;
+                encode(&mut self.writer, record, self.named_fields)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W, Error> {
+        match self.len {
+            SeqLen::Buffered { body, len } => {
+                rmp::encode::write_array_len(&mut self.writer, len)
+                    .map_err(rmp_serde::encode::Error::from)?;
+                self.writer.write_all(&body)?;
+            }
+            SeqLen::Streaming { expected, written } => {
+                if written != expected {
+                    return Err(length_error(
+                        "fewer records than the announced array length",
+                    ));
+                }
+            }
+        }
+        Ok(self.writer.into_inner().map_err(|e| e.into_error())?)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::io::Cursor;
 
     use serde::{Deserialize, Serialize};
 
-    use super::{read, write};
+    use super::{SeqWriter, read, read_iter, write, write_seq};
+    use crate::{
+        Error,
+        options::{MessagePackReadOptions, MessagePackWriteOptions},
+    };
 
     #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
     struct User {
@@ -38,10 +216,10 @@ mod test {
         };
 
         let mut buffer = Vec::new();
-        write(&mut buffer, &user).unwrap();
+        write(&mut buffer, &user, &Default::default()).unwrap();
 
         let mut reader = Cursor::new(buffer);
-        let deserialized: User = read(&mut reader).unwrap();
+        let deserialized: User = read(&mut reader, &Default::default()).unwrap();
 
         assert_eq!(user, deserialized);
     }
@@ -60,11 +238,128 @@ mod test {
         ];
 
         let mut buffer = Vec::new();
-        write(&mut buffer, &users).unwrap();
+        write(&mut buffer, &users, &Default::default()).unwrap();
 
         let mut reader = Cursor::new(buffer);
-        let deserialized: Vec<User> = read(&mut reader).unwrap();
+        let deserialized: Vec<User> = read(&mut reader, &Default::default()).unwrap();
 
         assert_eq!(users, deserialized);
     }
+
+    #[test]
+    fn test_seq_writer() {
+        let users = vec![
+            User {
+                name: "Alice".to_string(),
+                age: 30,
+            },
+            User {
+                name: "Bob".to_string(),
+                age: 25,
+            },
+        ];
+
+        let mut writer = SeqWriter::new(Vec::new(), &Default::default());
+        for user in &users {
+            writer.write(user).unwrap();
+        }
+        let buffer = writer.finish().unwrap();
+
+        let deserialized: Vec<User> = read(Cursor::new(buffer), &Default::default()).unwrap();
+        assert_eq!(users, deserialized);
+    }
+
+    #[test]
+    fn test_write_seq_with_and_without_exact_len() {
+        let users = vec![
+            User {
+                name: "Alice".to_string(),
+                age: 30,
+            },
+            User {
+                name: "Bob".to_string(),
+                age: 25,
+            },
+        ];
+
+        let mut exact = Vec::new();
+        write_seq(&mut exact, &users, &Default::default()).unwrap();
+        let mut unknown = Vec::new();
+        write_seq(
+            &mut unknown,
+            users.iter().filter(|_| true),
+            &Default::default(),
+        )
+        .unwrap();
+        assert_eq!(exact, unknown);
+
+        let deserialized: Vec<User> = read(Cursor::new(exact), &Default::default()).unwrap();
+        assert_eq!(users, deserialized);
+    }
+
+    #[test]
+    fn test_seq_writer_with_len_mismatch() {
+        let user = User {
+            name: "Alice".to_string(),
+            age: 30,
+        };
+        let mut writer = SeqWriter::with_len(Vec::new(), 2, &Default::default()).unwrap();
+        writer.write(&user).unwrap();
+        assert!(matches!(writer.finish(), Err(Error::MessagePackEncode(_))));
+
+        let mut writer = SeqWriter::with_len(Vec::new(), 0, &Default::default()).unwrap();
+        assert!(matches!(
+            writer.write(&user),
+            Err(Error::MessagePackEncode(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_iter_field_path() {
+        let records = serde_json::json!([
+            {"name": "Alice", "age": 30},
+            {"name": "Bob", "age": "old"},
+        ]);
+        let mut buffer = Vec::new();
+        write(&mut buffer, &records, &Default::default()).unwrap();
+
+        let results: Vec<Result<User, Error>> =
+            read_iter(Cursor::new(buffer), &Default::default()).collect();
+        assert_eq!(results.len(), 1);
+        let context = results[0].as_ref().unwrap_err().context().unwrap();
+        assert_eq!(context.record(), Some(1));
+        assert_eq!(context.field(), Some("age"));
+    }
+
+    #[test]
+    fn test_named_fields() {
+        let user = User {
+            name: "Alice".to_string(),
+            age: 30,
+        };
+        let options = MessagePackWriteOptions { named_fields: true };
+        let mut buffer = Vec::new();
+        write(&mut buffer, &user, &options).unwrap();
+        let value: serde_json::Value = rmp_serde::from_slice(&buffer).unwrap();
+        assert_eq!(value, serde_json::json!({"name": "Alice", "age": 30}));
+
+        let mut writer = SeqWriter::new(Vec::new(), &options);
+        writer.write(&user).unwrap();
+        let buffer = writer.finish().unwrap();
+        let deserialized: Vec<User> = read(Cursor::new(buffer), &Default::default()).unwrap();
+        assert_eq!(deserialized, vec![user]);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let nested = serde_json::json!({"a": {"b": {"c": [1]}}});
+        let mut buffer = Vec::new();
+        write(&mut buffer, &nested, &Default::default()).unwrap();
+
+        let options = MessagePackReadOptions { max_depth: 2 };
+        let error = read::<serde_json::Value>(Cursor::new(&buffer), &options).unwrap_err();
+        assert!(matches!(error.kind(), Error::MessagePackDecode(_)));
+        let value: serde_json::Value = read(Cursor::new(&buffer), &Default::default()).unwrap();
+        assert_eq!(value, nested);
+    }
 }
@@ -1,31 +1,210 @@
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{BufReader, BufWriter, Read, Write},
+    marker::PhantomData,
+};
 
-use serde::{Serialize, de::DeserializeOwned};
+use serde::{
+    Serialize,
+    de::{self, DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, Visitor},
+};
 
-use crate::Error;
+use super::field_path;
+use crate::{
+    Error,
+    error::line_column_of,
+    options::{TomlReadOptions, TomlWriteOptions},
+};
 
 pub fn read<T: DeserializeOwned>(reader: impl Read) -> Result<T, Error> {
+    let content = read_content(reader)?;
+    let deserializer = toml::Deserializer::parse(&content).map_err(|e| locate(e, &content))?;
+    field_path::deserialize(deserializer).map_err(|e| e.attach(|e| locate(e, &content)))
+}
+
+fn read_content(reader: impl Read) -> Result<String, Error> {
     let mut reader = BufReader::new(reader);
     let mut content = String::new();
     reader.read_to_string(&mut content)?;
-    Ok(toml::from_str(&content)?)
+    Ok(content)
+}
+
+/// Reads the records of the array of tables under `options.key`, such as
+/// the `[[record]]` tables. Other keys of the root table are ignored. An
+/// empty document holds no records, but a table without the key is an error,
+/// as it most likely keeps its records under another key.
+pub fn read_seq<T: DeserializeOwned>(
+    reader: impl Read,
+    options: &TomlReadOptions,
+) -> Result<Vec<T>, Error> {
+    let content = read_content(reader)?;
+    let deserializer = toml::Deserializer::parse(&content).map_err(|e| locate(e, &content))?;
+    let seed = Records {
+        key: &options.key,
+        _marker: PhantomData,
+    };
+    field_path::deserialize_seed(deserializer, seed)
+        .map_err(|e| e.nested().attach_record(0, |e| locate(e, &content)))
+}
+
+/// Like [`read_seq`], yielding the records one at a time. The document is
+/// decoded as a whole, so a bad record is reported as the only error.
+pub fn read_iter<R: Read, T: DeserializeOwned>(
+    reader: R,
+    options: &TomlReadOptions,
+) -> impl Iterator<Item = Result<T, Error>> + use<R, T> {
+    let (records, error) = match read_seq(reader, options) {
+        Ok(records) => (records, None),
+        Err(e) => (Vec::new(), Some(Err(e))),
+    };
+    records.into_iter().map(Ok).chain(error)
+}
+
+/// Picks the array under `key` out of the root table.
+struct Records<'a, T> {
+    key: &'a str,
+    _marker: PhantomData<fn() -> T>,
 }
 
-pub fn write<T: Serialize>(writer: impl Write, record: &T) -> Result<(), Error> {
+impl<'de, T: DeserializeOwned> DeserializeSeed<'de> for Records<'_, T> {
+    type Value = Vec<T>;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Vec<T>, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for Records<'_, T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a table with an array of tables under `{}`", self.key)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Vec<T>, A::Error> {
+        let mut records = None;
+        let mut empty = true;
+        while let Some(key) = map.next_key::<String>()? {
+            empty = false;
+            if key == self.key {
+                records = Some(map.next_value()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        match records {
+            Some(records) => Ok(records),
+            None if empty => Ok(Vec::new()),
+            None => Err(de::Error::custom(format_args!(
+                "no array of tables under `{}`",
+                self.key
+            ))),
+        }
+    }
+}
+
+/// Attaches the position of the span `toml` reports within `content`.
+fn locate(error: toml::de::Error, content: &str) -> Error {
+    let offset = error.span().map(|span| span.start);
+    let error = Error::from(error);
+    match offset {
+        Some(offset) => {
+            let (line, column) = line_column_of(content.as_bytes(), offset);
+            error.with_position(Some(line), Some(column), Some(offset as u64))
+        }
+        None => error,
+    }
+}
+
+pub fn write<T: Serialize>(
+    writer: impl Write,
+    record: &T,
+    options: &TomlWriteOptions,
+) -> Result<(), Error> {
     let mut writer = BufWriter::new(writer);
-    let content = toml::to_string(record)?;
-    writer.write_all(content.as_bytes())?;
+    writer.write_all(encode(record, options.pretty)?.as_bytes())?;
     writer.flush()?;
     Ok(())
 }
 
+fn encode<T: Serialize + ?Sized>(record: &T, pretty: bool) -> Result<String, Error> {
+    Ok(if pretty {
+        toml::to_string_pretty(record)?
+    } else {
+        toml::to_string(record)?
+    })
+}
+
+/// Writes records as an array of tables under `options.key` without
+/// collecting them first.
+pub fn write_seq<'a, T: Serialize + 'a>(
+    writer: impl Write,
+    records: impl IntoIterator<Item = &'a T>,
+    options: &TomlWriteOptions,
+) -> Result<(), Error> {
+    let mut writer = SeqWriter::new(writer, options);
+    for record in records {
+        writer.write(record)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Incrementally writes records as the tables of an array of tables.
+///
+/// Each record is emitted as a document holding a one-table array under the
+/// key, and such documents concatenate into a single array of tables.
+pub struct SeqWriter<W: Write> {
+    writer: BufWriter<W>,
+    key: String,
+    pretty: bool,
+    empty: bool,
+}
+
+impl<W: Write> SeqWriter<W> {
+    pub fn new(writer: W, options: &TomlWriteOptions) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            key: options.key.clone(),
+            pretty: options.pretty,
+            empty: true,
+        }
+    }
+
+    pub fn write<T: Serialize>(&mut self, record: &T) -> Result<(), Error> {
+        let content = encode(&BTreeMap::from([(&self.key, [record])]), self.pretty)?;
+        // Anything but a table would be written as an inline array, which
+        // cannot be repeated.
+        if !content.starts_with("[[") {
+            return Err(Error::TomlSerialize(serde::ser::Error::custom(
+                "records must be tables to be written as an array of tables",
+            )));
+        }
+        if !self.empty {
+            self.writer.write_all(b"\n")?;
+        }
+        self.writer.write_all(content.as_bytes())?;
+        self.empty = false;
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<W, Error> {
+        Ok(self.writer.into_inner().map_err(|e| e.into_error())?)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::io::Cursor;
 
     use serde::{Deserialize, Serialize};
 
-    use super::{read, write};
+    use super::{read, read_seq, write, write_seq};
+    use crate::{
+        Error,
+        options::{TomlReadOptions, TomlWriteOptions},
+    };
 
     #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
     struct Config {
@@ -43,11 +222,103 @@ mod test {
         };
 
         let mut buffer = Vec::new();
-        write(&mut buffer, &config).unwrap();
+        write(&mut buffer, &config, &Default::default()).unwrap();
 
         let reader = Cursor::new(buffer);
         let deserialized: Config = read(reader).unwrap();
 
         assert_eq!(config, deserialized);
     }
+
+    #[test]
+    fn test_pretty() {
+        #[derive(Serialize)]
+        struct Build {
+            targets: Vec<String>,
+        }
+
+        let build = Build {
+            targets: vec!["linux".to_string(), "macos".to_string()],
+        };
+        let mut buffer = Vec::new();
+        write(&mut buffer, &build, &Default::default()).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "targets = [\"linux\", \"macos\"]\n"
+        );
+
+        let options = TomlWriteOptions {
+            pretty: true,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        write(&mut buffer, &build, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "targets = [\n    \"linux\",\n    \"macos\",\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_read_write_seq() {
+        let configs = vec![
+            Config {
+                name: "a".to_string(),
+                version: 1,
+                enabled: true,
+            },
+            Config {
+                name: "b".to_string(),
+                version: 2,
+                enabled: false,
+            },
+        ];
+        let mut buffer = Vec::new();
+        write_seq(&mut buffer, &configs, &Default::default()).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        assert_eq!(
+            text,
+            "[[record]]\nname = \"a\"\nversion = 1\nenabled = true\n\n\
+             [[record]]\nname = \"b\"\nversion = 2\nenabled = false\n"
+        );
+        let records: Vec<Config> = read_seq(Cursor::new(&text), &Default::default()).unwrap();
+        assert_eq!(records, configs);
+
+        let text = "title = \"apps\"\n\n[[app]]\nname = \"a\"\nversion = 1\nenabled = true\n";
+        let options = TomlReadOptions {
+            key: "app".to_string(),
+        };
+        let records: Vec<Config> = read_seq(Cursor::new(text), &options).unwrap();
+        assert_eq!(records, configs[..1]);
+        let error = read_seq::<Config>(Cursor::new(text), &Default::default()).unwrap_err();
+        assert!(matches!(error.kind(), Error::TomlDeserialize(_)));
+        assert!(error.to_string().contains("`record`"), "{error}");
+        let records: Vec<Config> = read_seq(Cursor::new(""), &Default::default()).unwrap();
+        assert!(records.is_empty());
+
+        let result = write_seq(Vec::new(), &[1, 2], &Default::default());
+        assert!(matches!(result, Err(Error::TomlSerialize(_))));
+    }
+
+    #[test]
+    fn test_read_seq_error_position() {
+        let text = "[[record]]\nname = \"a\"\nversion = 1\nenabled = true\n\n\
+                    [[record]]\nname = \"b\"\nversion = \"two\"\nenabled = false\n";
+        let error = read_seq::<Config>(Cursor::new(text), &Default::default()).unwrap_err();
+        let context = error.context().unwrap();
+        assert_eq!(context.record(), Some(1));
+        assert_eq!(context.field(), Some("version"));
+        assert_eq!(context.line(), Some(8));
+    }
+
+    #[test]
+    fn test_read_error_position() {
+        let text = "name = \"myapp\"\nversion = \"one\"\nenabled = true\n";
+        let error = read::<Config>(Cursor::new(text)).unwrap_err();
+        let context = error.context().unwrap();
+        assert_eq!(context.field(), Some("version"));
+        assert_eq!(context.line(), Some(2));
+        assert_eq!(context.column(), Some(11));
+        assert_eq!(context.offset(), Some(25));
+    }
 }
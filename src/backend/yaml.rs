@@ -1,29 +1,650 @@
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    mem,
+};
 
-use serde::{Serialize, de::DeserializeOwned};
+use serde::{
+    Deserialize, Deserializer, Serialize,
+    de::{DeserializeOwned, EnumAccess, IgnoredAny, MapAccess, SeqAccess, VariantAccess, Visitor},
+};
 
-use crate::Error;
+use super::field_path;
+use crate::{Error, options::YamlWriteOptions};
 
 pub fn read<T: DeserializeOwned>(reader: impl Read) -> Result<T, Error> {
     let reader = BufReader::new(reader);
-    Ok(serde_yaml::from_reader(reader)?)
+    field_path::deserialize(serde_yaml::Deserializer::from_reader(reader))
+        .map_err(|e| e.attach(locate))
 }
 
-pub fn write<T: Serialize>(writer: impl Write, record: &T) -> Result<(), Error> {
+/// Decodes every document of a YAML stream.
+pub fn read_documents<T: DeserializeOwned>(reader: impl Read) -> Result<Vec<T>, Error> {
+    let mut text = String::new();
+    BufReader::new(reader).read_to_string(&mut text)?;
+    serde_yaml::Deserializer::from_str(&text)
+        .enumerate()
+        .map(|(index, document)| {
+            field_path::deserialize(document).map_err(|e| e.attach(locate).with_record(index))
+        })
+        .collect()
+}
+
+/// Converts an error, attaching its position in the input.
+fn locate(error: serde_yaml::Error) -> Error {
+    locate_from(error, Start::ORIGIN)
+}
+
+/// Converts an error raised while decoding a piece of the input that starts
+/// at `start`, attaching its position in the whole input.
+fn locate_from(error: serde_yaml::Error, start: Start) -> Error {
+    match error.location() {
+        Some(location) => Error::from(error).with_position(
+            Some(start.line + location.line() as u64 - 1),
+            Some(location.column() as u64),
+            Some(start.offset + location.index() as u64),
+        ),
+        None => error.into(),
+    }
+}
+
+/// Decodes the records of a YAML stream and yields them one at a time.
+///
+/// A stream of several documents holds one record per document. So does a
+/// single document marked with `---` or `...`, unless it is a sequence; an
+/// unmarked single document must be a sequence of records. Empty documents
+/// are skipped.
+///
+/// The input is read a line at a time and each record is decoded as soon as
+/// its text is complete, so only one document, or one entry of a block
+/// sequence, is held in memory. The entries of a block sequence are told
+/// apart by the `-` that starts them in the first column. A flow sequence
+/// (`[...]`) is decoded as a whole, and so is the rest of a block sequence
+/// from the first entry that defines an anchor, since later entries may refer
+/// to it. An entry or document that does not decode is yielded as an error
+/// and the next ones are still read, unless a document is not even
+/// well-formed YAML, which ends the stream.
+pub fn read_iter<T: DeserializeOwned>(reader: impl Read) -> impl Iterator<Item = Result<T, Error>> {
+    StreamIter::new(BufReader::new(reader))
+}
+
+/// Decodes the records of a YAML stream, laid out as for [`read_iter`].
+pub fn read_seq<T: DeserializeOwned>(reader: impl Read) -> Result<Vec<T>, Error> {
+    read_iter(reader).collect()
+}
+
+/// Where a piece of the input starts: its one-based line and byte offset.
+#[derive(Clone, Copy)]
+struct Start {
+    line: u64,
+    offset: u64,
+}
+
+impl Start {
+    const ORIGIN: Start = Start { line: 1, offset: 0 };
+}
+
+/// How the records of a stream are laid out, known from its first document
+/// with content.
+#[derive(Clone, Copy, PartialEq)]
+enum Layout {
+    Unknown,
+    /// The first document is a sequence of records, so no other document may
+    /// hold content.
+    Sequence,
+    /// One record per document.
+    Documents,
+}
+
+/// Splits a YAML stream into documents, and a block sequence into its
+/// entries, at the lines that start them, and decodes each piece on its own.
+struct StreamIter<R, T> {
+    reader: R,
+    /// Text of the piece being read: a document, or the entries of a block
+    /// sequence since the last one decoded.
+    text: String,
+    start: Start,
+    /// Where the next line starts.
+    next: Start,
+    /// The current document has a `---` line.
+    opened: bool,
+    /// The current document holds more than comments, directives and markers.
+    content: bool,
+    /// The stream has a `---` or `...` line so far.
+    marked: bool,
+    /// The current document is a block sequence being read entry by entry.
+    entries: bool,
+    /// An entry of the current piece defines an anchor.
+    anchored: bool,
+    layout: Layout,
+    index: usize,
+    pending: VecDeque<Result<T, Error>>,
+    done: bool,
+}
+
+impl<R: BufRead, T: DeserializeOwned> StreamIter<R, T> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            text: String::new(),
+            start: Start::ORIGIN,
+            next: Start::ORIGIN,
+            opened: false,
+            content: false,
+            marked: false,
+            entries: false,
+            anchored: false,
+            layout: Layout::Unknown,
+            index: 0,
+            pending: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Reads one line, decoding the piece it completes.
+    fn step(&mut self) -> Result<(), Error> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            self.done = true;
+            return self.end_piece(true);
+        }
+        let at = self.next;
+        self.next.line += 1;
+        self.next.offset += line.len() as u64;
+        if is_marker(&line) {
+            self.marked = true;
+            if line.trim_end() == "..." {
+                self.push(&line, at);
+                return self.end_piece(true);
+            }
+            if self.opened || self.content {
+                self.end_piece(true)?;
+            }
+            self.opened = true;
+            // Content may follow the marker, as in `--- {x: 1}`.
+            self.content = line.trim_end() != "---";
+            self.push(&line, at);
+            return Ok(());
+        }
+        if self.entries {
+            if starts_entry(&line) && !self.anchored {
+                self.end_piece(false)?;
+            }
+        } else if !self.content && !is_blank(&line, self.opened) {
+            self.content = true;
+            self.entries = self.layout == Layout::Unknown && starts_entry(&line);
+        }
+        self.anchored |= self.entries && has_anchor(&line);
+        self.push(&line, at);
+        Ok(())
+    }
+
+    fn push(&mut self, line: &str, at: Start) {
+        if self.text.is_empty() {
+            self.start = at;
+        }
+        self.text.push_str(line);
+    }
+
+    /// Decodes the piece read so far, which ends a document if `document` is
+    /// set, or else an entry of a block sequence.
+    fn end_piece(&mut self, document: bool) -> Result<(), Error> {
+        let text = mem::take(&mut self.text);
+        let start = self.start;
+        let content = self.content;
+        if document {
+            self.opened = false;
+            self.content = false;
+        }
+        if self.entries {
+            self.entries = !document;
+            self.anchored = false;
+            self.layout = Layout::Sequence;
+            self.decode_entries(&text, start);
+            return Ok(());
+        }
+        if !content {
+            return Ok(());
+        }
+        let kind = Kind::deserialize(serde_yaml::Deserializer::from_str(&text))
+            .map_err(|e| locate_from(e, start).with_record(self.index))?;
+        if kind == Kind::Empty {
+            return Ok(());
+        }
+        match self.layout {
+            Layout::Sequence => {
+                return Err(Error::Yaml(serde::de::Error::custom(
+                    "a YAML stream whose first document is a sequence cannot hold more documents",
+                ))
+                .with_position(Some(start.line), None, Some(start.offset)));
+            }
+            // An unmarked document that ends the stream is its only one.
+            Layout::Unknown if kind == Kind::Sequence || (!self.marked && self.done) => {
+                self.layout = Layout::Sequence;
+                match field_path::deserialize::<_, Vec<T>>(serde_yaml::Deserializer::from_str(
+                    &text,
+                )) {
+                    Ok(records) => {
+                        self.index += records.len();
+                        self.pending.extend(records.into_iter().map(Ok));
+                    }
+                    Err(e) => self
+                        .pending
+                        .push_back(Err(e.attach_record(0, |e| locate_from(e, start)))),
+                }
+                return Ok(());
+            }
+            Layout::Unknown => self.layout = Layout::Documents,
+            Layout::Documents => {}
+        }
+        let index = self.index;
+        self.index += 1;
+        let record =
+            field_path::deserialize(serde_yaml::Deserializer::from_str(&text)).map_err(|e| {
+                e.attach(|e| locate_from(e, start))
+                    .with_record(index)
+                    .with_raw(text.clone())
+            });
+        self.pending.push_back(record);
+        Ok(())
+    }
+
+    /// Decodes entries of a block sequence, as a sequence of their own.
+    fn decode_entries(&mut self, text: &str, start: Start) {
+        let first = self.index;
+        self.index += text.lines().filter(|line| starts_entry(line)).count();
+        match field_path::deserialize::<_, Vec<T>>(serde_yaml::Deserializer::from_str(text)) {
+            Ok(records) => self.pending.extend(records.into_iter().map(Ok)),
+            Err(e) => self.pending.push_back(Err(e
+                .attach_record(first, |e| locate_from(e, start))
+                .with_raw(text.to_string()))),
+        }
+    }
+}
+
+impl<R: BufRead, T: DeserializeOwned> Iterator for StreamIter<R, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.pending.pop_front() {
+                return Some(record);
+            }
+            if self.done {
+                return None;
+            }
+            if let Err(e) = self.step() {
+                self.done = true;
+                self.pending.clear();
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// What a document holds, found without decoding it into a record.
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Empty,
+    Sequence,
+    Other,
+}
+
+impl<'de> Deserialize<'de> for Kind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(KindVisitor)
+    }
+}
+
+struct KindVisitor;
+
+impl<'de> Visitor<'de> for KindVisitor {
+    type Value = Kind;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a YAML document")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<Kind, E> {
+        Ok(Kind::Other)
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<Kind, E> {
+        Ok(Kind::Other)
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<Kind, E> {
+        Ok(Kind::Other)
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<Kind, E> {
+        Ok(Kind::Other)
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<Kind, E> {
+        Ok(Kind::Other)
+    }
+
+    fn visit_unit<E>(self) -> Result<Kind, E> {
+        Ok(Kind::Empty)
+    }
+
+    fn visit_none<E>(self) -> Result<Kind, E> {
+        Ok(Kind::Empty)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Kind, A::Error> {
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(Kind::Sequence)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Kind, A::Error> {
+        while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+        Ok(Kind::Other)
+    }
+
+    // Tagged values, such as `!Circle {r: 1}`.
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Kind, A::Error> {
+        let (IgnoredAny, variant) = data.variant()?;
+        variant.newtype_variant::<IgnoredAny>()?;
+        Ok(Kind::Other)
+    }
+}
+
+/// Returns true for lines that start or end a document. Such lines cannot
+/// occur within the content of a document, not even inside a scalar.
+fn is_marker(line: &str) -> bool {
+    let line = line.trim_end();
+    line == "---" || line == "..." || line.starts_with("--- ") || line.starts_with("---\t")
+}
+
+/// Returns true for lines that start an entry of a block sequence in the
+/// first column.
+fn starts_entry(line: &str) -> bool {
+    line.starts_with('-')
+        && matches!(
+            line.as_bytes().get(1),
+            None | Some(b' ' | b'\t' | b'\r' | b'\n')
+        )
+}
+
+/// Returns true for lines without content: blank lines, comments, and the
+/// directives before the `---` of a document.
+fn is_blank(line: &str, opened: bool) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#') || (!opened && line.starts_with('%'))
+}
+
+/// Returns true for lines that may define an anchor, such as `- &base`.
+/// Quoted text can look like one too, which only costs some buffering.
+fn has_anchor(line: &str) -> bool {
+    let bytes = line.as_bytes();
+    bytes.iter().enumerate().any(|(i, &b)| {
+        b == b'&'
+            && (i == 0 || matches!(bytes[i - 1], b' ' | b'\t' | b'[' | b'{' | b','))
+            && bytes
+                .get(i + 1)
+                .is_some_and(|next| !next.is_ascii_whitespace() && !b",[]{}".contains(next))
+    })
+}
+
+pub fn write<T: Serialize>(
+    writer: impl Write,
+    record: &T,
+    options: &YamlWriteOptions,
+) -> Result<(), Error> {
     let mut writer = BufWriter::new(writer);
-    serde_yaml::to_writer(&mut writer, record)?;
+    writer.write_all(encode(record, options)?.as_bytes())?;
     writer.flush()?;
     Ok(())
 }
 
+/// Encodes a record as a YAML document. Default settings leave the layout to
+/// `serde_yaml`; anything else goes through [`Emitter`].
+fn encode<T: Serialize + ?Sized>(record: &T, options: &YamlWriteOptions) -> Result<String, Error> {
+    if *options == YamlWriteOptions::default() {
+        return Ok(serde_yaml::to_string(record)?);
+    }
+    let value = serde_yaml::to_value(record)?;
+    let emitter = Emitter {
+        indent: options.indent.max(1),
+    };
+    let mut out = String::new();
+    if options.flow {
+        emitter.inline(&value, false, &mut out)?;
+        out.push('\n');
+    } else {
+        emitter.block(&value, At::Root, &mut out)?;
+    }
+    Ok(out)
+}
+
+/// Characters that end a plain scalar inside a flow collection.
+const FLOW_INDICATORS: [char; 7] = [',', '[', ']', '{', '}', ':', '#'];
+
+/// Where a block node starts.
+#[derive(Clone, Copy)]
+enum At {
+    /// At the start of the document.
+    Root,
+    /// After a `key:` or a tag; collections start on the next line at this
+    /// column.
+    Below(usize),
+    /// After the `-` of a sequence entry at this column.
+    Dash(usize),
+}
+
+/// Writes a [`serde_yaml::Value`] with a configurable indentation, laid out
+/// as `serde_yaml` does: sequences in mappings start at the column of their
+/// key, and the first entry of a collection in a sequence shares the line of
+/// its `-`.
+struct Emitter {
+    indent: usize,
+}
+
+impl Emitter {
+    fn block(&self, value: &serde_yaml::Value, at: At, out: &mut String) -> Result<(), Error> {
+        use serde_yaml::Value;
+
+        match value {
+            Value::Mapping(members) if !members.is_empty() => {
+                let (column, mut inline) = open(at, out);
+                for (key, value) in members {
+                    if !mem::take(&mut inline) {
+                        pad(column, out);
+                    }
+                    self.inline(key, false, out)?;
+                    out.push(':');
+                    let below = match value {
+                        Value::Sequence(items) if !items.is_empty() => column,
+                        _ => column + self.indent,
+                    };
+                    self.block(value, At::Below(below), out)?;
+                }
+            }
+            Value::Sequence(items) if !items.is_empty() => {
+                let (column, mut inline) = open(at, out);
+                for item in items {
+                    if !mem::take(&mut inline) {
+                        pad(column, out);
+                    }
+                    out.push('-');
+                    self.block(item, At::Dash(column), out)?;
+                }
+            }
+            Value::Tagged(tagged) => {
+                if !matches!(at, At::Root) {
+                    out.push(' ');
+                }
+                out.push_str(&tagged.tag.to_string());
+                let below = match at {
+                    At::Root => 0,
+                    At::Below(column) => column,
+                    At::Dash(column) => column + 2,
+                };
+                self.block(&tagged.value, At::Below(below), out)?;
+            }
+            value => {
+                if !matches!(at, At::Root) {
+                    out.push(' ');
+                }
+                self.inline(value, false, out)?;
+                out.push('\n');
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a value on one line, with collections in flow style.
+    fn inline(
+        &self,
+        value: &serde_yaml::Value,
+        in_flow: bool,
+        out: &mut String,
+    ) -> Result<(), Error> {
+        use serde_yaml::Value;
+
+        match value {
+            Value::Mapping(members) => {
+                out.push('{');
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    self.inline(key, true, out)?;
+                    out.push_str(": ");
+                    self.inline(value, true, out)?;
+                }
+                out.push('}');
+            }
+            Value::Sequence(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    self.inline(item, true, out)?;
+                }
+                out.push(']');
+            }
+            Value::Tagged(tagged) => {
+                out.push_str(&tagged.tag.to_string());
+                out.push(' ');
+                self.inline(&tagged.value, in_flow, out)?;
+            }
+            scalar => {
+                let text = serde_yaml::to_string(scalar)?;
+                let text = text.trim_end_matches('\n');
+                match scalar {
+                    // Block scalars span several lines, and plain scalars in
+                    // flow collections must not hold flow indicators; a
+                    // double-quoted JSON string is valid YAML either way.
+                    Value::String(s)
+                        if text.contains('\n')
+                            || (in_flow
+                                && !text.starts_with(['\'', '"'])
+                                && text.contains(FLOW_INDICATORS)) =>
+                    {
+                        out.push_str(&serde_json::to_string(s)?);
+                    }
+                    _ => out.push_str(text),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Starts a non-empty block collection, returning its column and whether its
+/// first entry continues the current line.
+fn open(at: At, out: &mut String) -> (usize, bool) {
+    match at {
+        At::Root => (0, false),
+        At::Below(column) => {
+            out.push('\n');
+            (column, false)
+        }
+        At::Dash(column) => {
+            out.push(' ');
+            (column + 2, true)
+        }
+    }
+}
+
+fn pad(column: usize, out: &mut String) {
+    out.extend(std::iter::repeat_n(' ', column));
+}
+
+/// Writes records as a YAML sequence, or as a stream of documents, without
+/// collecting them first.
+pub fn write_seq<'a, T: Serialize + 'a>(
+    writer: impl Write,
+    records: impl IntoIterator<Item = &'a T>,
+    options: &YamlWriteOptions,
+) -> Result<(), Error> {
+    let mut writer = SeqWriter::new(writer, options);
+    for record in records {
+        writer.write(record)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Incrementally writes records as the entries of a block sequence, or as
+/// documents each starting with `---`.
+///
+/// Each entry is emitted as a one-element sequence, and consecutive
+/// one-element block sequences concatenate into a single valid sequence. In
+/// flow style, each entry is a `-` followed by the record on one line.
+pub struct SeqWriter<W: Write> {
+    writer: BufWriter<W>,
+    options: YamlWriteOptions,
+    empty: bool,
+}
+
+impl<W: Write> SeqWriter<W> {
+    pub fn new(writer: W, options: &YamlWriteOptions) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            options: options.clone(),
+            empty: true,
+        }
+    }
+
+    pub fn write<T: Serialize>(&mut self, record: &T) -> Result<(), Error> {
+        let content = if self.options.documents {
+            format!("---\n{}", encode(record, &self.options)?)
+        } else if self.options.flow {
+            format!("- {}", encode(record, &self.options)?)
+        } else {
+            encode(&[record], &self.options)?
+        };
+        self.writer.write_all(content.as_bytes())?;
+        self.empty = false;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W, Error> {
+        // An empty stream of documents is an empty file.
+        if self.empty && !self.options.documents {
+            self.writer.write_all(b"[]\n")?;
+        }
+        Ok(self.writer.into_inner().map_err(|e| e.into_error())?)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use std::io::{self, Write};
+    use std::{
+        collections::BTreeMap,
+        io::{self, Cursor, Read, Write},
+    };
 
-    use serde::Serialize;
+    use serde::{Deserialize, Serialize};
 
-    use super::write;
-    use crate::Error;
+    use super::{SeqWriter, read_iter, read_seq, write};
+    use crate::{Error, options::YamlWriteOptions};
 
     /// Writer that succeeds on every write but fails on flush, so a
     /// correct implementation must surface the flush error instead of
@@ -43,7 +664,7 @@ mod test {
         }
     }
 
-    #[derive(Serialize)]
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
     struct Point {
         x: i32,
         y: i32,
@@ -52,10 +673,430 @@ mod test {
     #[test]
     fn test_write_flush_error_propagates() {
         let writer = FlushFailingWriter { inner: Vec::new() };
-        let result = write(writer, &Point { x: 1, y: 2 });
+        let result = write(writer, &Point { x: 1, y: 2 }, &Default::default());
         assert!(
             matches!(result, Err(Error::Io(_))),
             "expected flush error to propagate, got {result:?}"
         );
     }
+
+    #[test]
+    fn test_read_iter_block_sequence() {
+        let data = "# points\n---\n- x: 1\n  y: 2\n\n# second\n- x: 3\n  y: 4\n...\n";
+        let records: Vec<Point> = read_iter(Cursor::new(data))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(records, vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+    }
+
+    #[test]
+    fn test_read_seq_aliases() {
+        let data = "- &a {x: 1, y: 2}\n- *a\n";
+        let records: Vec<Point> = read_seq(Cursor::new(data)).unwrap();
+        assert_eq!(records, vec![Point { x: 1, y: 2 }, Point { x: 1, y: 2 }]);
+
+        let data = "- x: 1\n  y: 2\n---\n- x: 3\n  y: 4\n";
+        assert!(read_seq::<Point>(Cursor::new(data)).is_err());
+    }
+
+    #[test]
+    fn test_read_iter_empty_documents() {
+        for data in ["", "# nothing\n", "---\n", "---\n...\n---\n"] {
+            let records: Vec<Point> = read_seq(Cursor::new(data)).unwrap();
+            assert!(records.is_empty(), "{data}");
+        }
+    }
+
+    #[test]
+    fn test_read_iter_flow_sequence() {
+        let data = "[{x: 1, y: 2}, {x: 3, y: 4}]\n";
+        let records: Vec<Point> = read_iter(Cursor::new(data))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(records, vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+    }
+
+    #[test]
+    fn test_read_iter_continues_after_invalid_entry() {
+        let data = "- x: 1\n  y: 2\n- x: a\n  y: 2\n- x: 3\n  y: 4\n";
+        let results: Vec<Result<Point, Error>> = read_iter(Cursor::new(data)).collect();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[2].as_ref().unwrap(), &Point { x: 3, y: 4 });
+        let error = results[1].as_ref().unwrap_err();
+        assert!(matches!(error.kind(), Error::Yaml(_)));
+        let context = error.context().unwrap();
+        assert_eq!(context.record(), Some(1));
+        assert_eq!(context.field(), Some("x"));
+        assert_eq!(context.line(), Some(3));
+        assert_eq!(context.column(), Some(6));
+        assert_eq!(context.offset(), Some(19));
+        assert_eq!(context.raw(), Some("- x: a\n  y: 2\n"));
+    }
+
+    /// Yields its data, then fails instead of reaching the end.
+    struct Interrupted(Cursor<&'static str>);
+
+    impl Read for Interrupted {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.read(buf)? {
+                0 => Err(io::Error::other("connection lost")),
+                n => Ok(n),
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_iter_streams_entries() {
+        let data = "- x: 1\n  y: 2\n- x: 3\n  y: 4\n";
+        let mut records = read_iter::<Point>(Interrupted(Cursor::new(data)));
+        assert_eq!(records.next().unwrap().unwrap(), Point { x: 1, y: 2 });
+        let error = records.next().unwrap().unwrap_err();
+        assert!(matches!(error.kind(), Error::Io(_)));
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn test_read_iter_rejects_mapping_root() {
+        let result: Result<Vec<Point>, Error> = read_iter(Cursor::new("x: 1\ny: 2\n")).collect();
+        assert!(matches!(
+            result.as_ref().map_err(Error::kind),
+            Err(Error::Yaml(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_iter_documents() {
+        let data =
+            "# points\n---\nx: 1\ny: 2\n---\n# second\nx: 3\ny: 4\n...\n--- {x: 5, y: 6}\n---\n";
+        let records: Vec<Point> = read_iter(Cursor::new(data))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            records,
+            vec![
+                Point { x: 1, y: 2 },
+                Point { x: 3, y: 4 },
+                Point { x: 5, y: 6 }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_iter_single_document() {
+        for data in [
+            "---\nx: 1\ny: 2\n",
+            "--- {x: 1, y: 2}\n",
+            "x: 1\ny: 2\n...\n",
+        ] {
+            let records: Vec<Point> = read_iter(Cursor::new(data))
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(records, vec![Point { x: 1, y: 2 }], "{data}");
+            let records: Vec<Point> = read_seq(Cursor::new(data)).unwrap();
+            assert_eq!(records, vec![Point { x: 1, y: 2 }], "{data}");
+        }
+
+        let data = "---\n[{x: 1, y: 2}, {x: 3, y: 4}]\n";
+        let records: Vec<Point> = read_seq(Cursor::new(data)).unwrap();
+        assert_eq!(records, vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+    }
+
+    #[test]
+    fn test_read_iter_continues_after_invalid_document() {
+        let data = "x: 1\ny: 2\n---\nx: 3\ny: b\n---\nx: 5\ny: 6\n";
+        let results: Vec<Result<Point, Error>> = read_iter(Cursor::new(data)).collect();
+        assert_eq!(results.len(), 3);
+        let error = results[1].as_ref().unwrap_err();
+        let context = error.context().unwrap();
+        assert_eq!(context.record(), Some(1));
+        assert_eq!(context.field(), Some("y"));
+        assert_eq!(context.line(), Some(5));
+        assert_eq!(context.raw(), Some("---\nx: 3\ny: b\n"));
+        assert_eq!(results[2].as_ref().unwrap(), &Point { x: 5, y: 6 });
+    }
+
+    #[test]
+    fn test_read_iter_ends_after_syntax_error() {
+        let data = "x: 1\ny: 2\n---\nx: [3,\n---\nx: 5\ny: 6\n";
+        let results: Vec<Result<Point, Error>> = read_iter(Cursor::new(data)).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        let error = results[1].as_ref().unwrap_err();
+        assert!(matches!(error.kind(), Error::Yaml(_)));
+        assert_eq!(error.context().unwrap().record(), Some(1));
+    }
+
+    #[test]
+    fn test_read_iter_rejects_documents_after_sequence() {
+        let data = "- x: 1\n  y: 2\n---\n- x: 3\n  y: 4\n";
+        let results: Vec<Result<Point, Error>> = read_iter(Cursor::new(data)).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap(), &Point { x: 1, y: 2 });
+        let error = results[1].as_ref().unwrap_err();
+        assert!(matches!(error.kind(), Error::Yaml(_)));
+        assert_eq!(error.context().unwrap().line(), Some(3));
+    }
+
+    #[test]
+    fn test_documents_writer_round_trip() {
+        let points = vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }];
+        let options = YamlWriteOptions {
+            documents: true,
+            ..Default::default()
+        };
+        let mut writer = SeqWriter::new(Vec::new(), &options);
+        for point in &points {
+            writer.write(point).unwrap();
+        }
+        let data = writer.finish().unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&data),
+            "---\nx: 1\ny: 2\n---\nx: 3\ny: 4\n"
+        );
+        let records: Vec<Point> = read_iter(Cursor::new(data))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(points, records);
+    }
+
+    #[test]
+    fn test_seq_writer_round_trip() {
+        let points = vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }];
+        let mut writer = SeqWriter::new(Vec::new(), &Default::default());
+        for point in &points {
+            writer.write(point).unwrap();
+        }
+        let data = writer.finish().unwrap();
+        let records: Vec<Point> = serde_yaml::from_slice(&data).unwrap();
+        assert_eq!(points, records);
+
+        let data = SeqWriter::new(Vec::new(), &Default::default())
+            .finish()
+            .unwrap();
+        let records: Vec<Point> = serde_yaml::from_slice(&data).unwrap();
+        assert!(records.is_empty());
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    enum Shape {
+        Circle { r: f64 },
+        Square(u32),
+        Empty,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Drawing {
+        name: String,
+        notes: Vec<String>,
+        shapes: Vec<Shape>,
+        grid: Vec<Vec<u8>>,
+        origin: Point,
+        meta: BTreeMap<String, Option<String>>,
+    }
+
+    fn drawing() -> Drawing {
+        Drawing {
+            name: "a: b".to_string(),
+            notes: vec![
+                String::new(),
+                "123".to_string(),
+                "two\nlines".to_string(),
+                "x, [y]".to_string(),
+                "# not a comment".to_string(),
+                "null".to_string(),
+            ],
+            shapes: vec![Shape::Circle { r: 1.5 }, Shape::Square(2), Shape::Empty],
+            grid: vec![vec![1, 2], vec![]],
+            origin: Point { x: 0, y: -1 },
+            meta: BTreeMap::from([
+                ("k".to_string(), None),
+                ("{l}".to_string(), Some("v".to_string())),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_write_indent() {
+        let options = YamlWriteOptions {
+            indent: 4,
+            ..Default::default()
+        };
+        let mut data = Vec::new();
+        write(&mut data, &drawing(), &options).unwrap();
+        let yaml = String::from_utf8(data).unwrap();
+        assert!(yaml.contains("origin:\n    x: 0\n    y: -1\n"), "{yaml}");
+        assert!(yaml.contains("grid:\n- - 1\n  - 2\n- []\n"), "{yaml}");
+        let record: Drawing = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(record, drawing());
+    }
+
+    #[test]
+    fn test_write_flow() {
+        let options = YamlWriteOptions {
+            flow: true,
+            ..Default::default()
+        };
+        let mut data = Vec::new();
+        write(&mut data, &Point { x: 1, y: 2 }, &options).unwrap();
+        assert_eq!(String::from_utf8_lossy(&data), "{x: 1, y: 2}\n");
+
+        let mut data = Vec::new();
+        write(&mut data, &drawing(), &options).unwrap();
+        let yaml = String::from_utf8(data).unwrap();
+        assert_eq!(yaml.lines().count(), 1, "{yaml}");
+        let record: Drawing = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(record, drawing());
+
+        let mut writer = SeqWriter::new(Vec::new(), &options);
+        writer.write(&drawing()).unwrap();
+        writer.write(&drawing()).unwrap();
+        let data = writer.finish().unwrap();
+        let records: Vec<Drawing> = read_seq(data.as_slice()).unwrap();
+        assert_eq!(records, [drawing(), drawing()]);
+    }
+
+    #[test]
+    fn test_documents_writer_without_records() {
+        let options = YamlWriteOptions {
+            documents: true,
+            ..Default::default()
+        };
+        let data = SeqWriter::new(Vec::new(), &options).finish().unwrap();
+        assert!(data.is_empty());
+        let records: Vec<Point> = read_seq(data.as_slice()).unwrap();
+        assert!(records.is_empty());
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Tricky {
+        strings: Vec<String>,
+        keys: BTreeMap<String, String>,
+        empty_list: Vec<u8>,
+        empty_map: BTreeMap<String, u8>,
+        nested_empty: Vec<Vec<BTreeMap<String, u8>>>,
+        lists_in_list: Vec<Vec<String>>,
+        maps_in_list: Vec<BTreeMap<String, Vec<String>>>,
+        unit: (),
+        missing: Option<Box<Tricky>>,
+    }
+
+    fn tricky() -> Tricky {
+        let strings: Vec<String> = [
+            "",
+            " ",
+            "  leading",
+            "trailing  ",
+            "a: b",
+            "a:b",
+            "- x",
+            "-",
+            "? x",
+            "#",
+            "a #b",
+            "'",
+            "\"",
+            "\\",
+            "[",
+            "]",
+            "{",
+            "}",
+            ",",
+            "&anchor",
+            "*alias",
+            "!tag",
+            "%",
+            "@",
+            "`",
+            "|",
+            ">",
+            "~",
+            "null",
+            "Null",
+            "true",
+            "no",
+            "yes",
+            "0x1F",
+            "0o17",
+            "1e3",
+            ".inf",
+            "-.nan",
+            "1_000",
+            "12:30:00",
+            "2024-01-01",
+            "---",
+            "...",
+            "tab\there",
+            "é ü 日本",
+            "emoji 🎉",
+            "\u{7f}",
+            "\u{85}",
+            "\u{feff}bom",
+            // Multi-line strings.
+            "two\nlines",
+            "trailing newline\n",
+            "\nleading newline",
+            "blank\n\nline",
+            "  indented\n    more",
+            "crlf\r\nline",
+            "ends with spaces  \n  ",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let keys = strings
+            .iter()
+            .filter(|s| !s.is_empty())
+            .map(|s| (s.clone(), s.clone()))
+            .collect();
+        Tricky {
+            lists_in_list: vec![strings[..5].to_vec(), vec![], strings[50..].to_vec()],
+            maps_in_list: vec![
+                BTreeMap::from([("k".to_string(), strings[40..].to_vec())]),
+                BTreeMap::new(),
+                BTreeMap::from([("".to_string(), vec![])]),
+            ],
+            strings,
+            keys,
+            empty_list: vec![],
+            empty_map: BTreeMap::new(),
+            nested_empty: vec![vec![], vec![BTreeMap::new()]],
+            unit: (),
+            missing: None,
+        }
+    }
+
+    #[test]
+    fn test_write_options_round_trip() {
+        for (indent, flow) in [
+            (1, false),
+            (3, false),
+            (4, false),
+            (8, false),
+            (2, true),
+            (4, true),
+        ] {
+            let options = YamlWriteOptions {
+                indent,
+                flow,
+                ..Default::default()
+            };
+            let record = Tricky {
+                missing: Some(Box::new(tricky())),
+                ..tricky()
+            };
+            let mut data = Vec::new();
+            write(&mut data, &record, &options).unwrap();
+            let yaml = String::from_utf8(data).unwrap();
+            let read_back: Tricky = serde_yaml::from_str(&yaml)
+                .unwrap_or_else(|e| panic!("indent {indent}, flow {flow}: {e}\n{yaml}"));
+            assert_eq!(read_back, record, "indent {indent}, flow {flow}:\n{yaml}");
+
+            let mut writer = SeqWriter::new(Vec::new(), &options);
+            writer.write(&record).unwrap();
+            writer.write(&tricky()).unwrap();
+            let data = writer.finish().unwrap();
+            let records: Vec<Tricky> = read_seq(data.as_slice()).unwrap();
+            assert_eq!(records, [record, tricky()], "indent {indent}, flow {flow}");
+        }
+    }
 }
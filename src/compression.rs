@@ -0,0 +1,207 @@
+use std::io::{BufRead, Read, Write};
+
+use crate::{Error, types::Compression};
+
+/// Writer that compresses everything written to it with an optional codec.
+///
+/// Encoders must write a trailer once all data is written, so the stream is
+/// only complete after [`Encoder::finish`] has been called.
+pub(crate) enum Encoder<W: Write> {
+    Plain(W),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<W>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, W>),
+    #[cfg(feature = "bzip2")]
+    Bzip2(bzip2::write::BzEncoder<W>),
+    #[cfg(feature = "xz")]
+    Xz(liblzma::write::XzEncoder<W>),
+}
+
+impl<W: Write> Encoder<W> {
+    pub(crate) fn new(writer: W, compression: Option<Compression>) -> Result<Self, Error> {
+        Ok(match compression {
+            None => Encoder::Plain(writer),
+            #[cfg(feature = "gzip")]
+            Some(Compression::Gzip) => Encoder::Gzip(flate2::write::GzEncoder::new(
+                writer,
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "zstd")]
+            Some(Compression::Zstd) => Encoder::Zstd(zstd::stream::write::Encoder::new(
+                writer,
+                zstd::DEFAULT_COMPRESSION_LEVEL,
+            )?),
+            #[cfg(feature = "bzip2")]
+            Some(Compression::Bzip2) => Encoder::Bzip2(bzip2::write::BzEncoder::new(
+                writer,
+                bzip2::Compression::default(),
+            )),
+            #[cfg(feature = "xz")]
+            Some(Compression::Xz) => Encoder::Xz(liblzma::write::XzEncoder::new(writer, 6)),
+        })
+    }
+
+    /// Writes the trailer of the codec, flushes, and returns the inner writer.
+    // Without compression features the match has a single arm.
+    #[allow(clippy::infallible_destructuring_match)]
+    pub(crate) fn finish(self) -> Result<W, Error> {
+        let mut writer = match self {
+            Encoder::Plain(writer) => writer,
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(encoder) => encoder.finish()?,
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(encoder) => encoder.finish()?,
+            #[cfg(feature = "bzip2")]
+            Encoder::Bzip2(encoder) => encoder.finish()?,
+            #[cfg(feature = "xz")]
+            Encoder::Xz(encoder) => encoder.finish()?,
+        };
+        writer.flush()?;
+        Ok(writer)
+    }
+
+    fn inner(&mut self) -> &mut dyn Write {
+        match self {
+            Encoder::Plain(writer) => writer,
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(encoder) => encoder,
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(encoder) => encoder,
+            #[cfg(feature = "bzip2")]
+            Encoder::Bzip2(encoder) => encoder,
+            #[cfg(feature = "xz")]
+            Encoder::Xz(encoder) => encoder,
+        }
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.inner().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner().flush()
+    }
+}
+
+/// Wraps `reader` in the decoder matching `compression`.
+///
+/// Concatenated members (as produced by `cat a.gz b.gz`) are decoded as one stream.
+pub(crate) fn decoder<'a>(
+    reader: impl BufRead + 'a,
+    compression: Option<Compression>,
+) -> Result<Box<dyn Read + 'a>, Error> {
+    Ok(match compression {
+        None => Box::new(reader),
+        #[cfg(feature = "gzip")]
+        Some(Compression::Gzip) => Box::new(flate2::bufread::MultiGzDecoder::new(reader)),
+        #[cfg(feature = "zstd")]
+        Some(Compression::Zstd) => Box::new(zstd::stream::read::Decoder::with_buffer(reader)?),
+        #[cfg(feature = "bzip2")]
+        Some(Compression::Bzip2) => Box::new(bzip2::bufread::MultiBzDecoder::new(reader)),
+        #[cfg(feature = "xz")]
+        Some(Compression::Xz) => Box::new(liblzma::bufread::XzDecoder::new_multi_decoder(reader)),
+    })
+}
+
+/// Wraps an asynchronous `reader` in the decoder matching `compression`.
+///
+/// Concatenated members are decoded as one stream, as by [`decoder`].
+#[cfg(feature = "async")]
+pub(crate) fn async_decoder<'a>(
+    reader: impl tokio::io::AsyncBufRead + Unpin + Send + 'a,
+    compression: Option<Compression>,
+) -> Box<dyn tokio::io::AsyncRead + Unpin + Send + 'a> {
+    #[allow(unused_imports)]
+    use async_compression::tokio::bufread;
+
+    match compression {
+        None => Box::new(reader),
+        #[cfg(feature = "gzip")]
+        Some(Compression::Gzip) => {
+            let mut decoder = bufread::GzipDecoder::new(reader);
+            decoder.multiple_members(true);
+            Box::new(decoder)
+        }
+        #[cfg(feature = "zstd")]
+        Some(Compression::Zstd) => {
+            let mut decoder = bufread::ZstdDecoder::new(reader);
+            decoder.multiple_members(true);
+            Box::new(decoder)
+        }
+        #[cfg(feature = "bzip2")]
+        Some(Compression::Bzip2) => {
+            let mut decoder = bufread::BzDecoder::new(reader);
+            decoder.multiple_members(true);
+            Box::new(decoder)
+        }
+        #[cfg(feature = "xz")]
+        Some(Compression::Xz) => {
+            let mut decoder = bufread::XzDecoder::new(reader);
+            decoder.multiple_members(true);
+            Box::new(decoder)
+        }
+    }
+}
+
+/// Wraps an asynchronous `writer` in the encoder matching `compression`.
+///
+/// The encoder writes the trailer of the codec when it is shut down.
+#[cfg(feature = "async")]
+pub(crate) fn async_encoder<'a>(
+    writer: impl tokio::io::AsyncWrite + Unpin + Send + 'a,
+    compression: Option<Compression>,
+) -> Box<dyn tokio::io::AsyncWrite + Unpin + Send + 'a> {
+    #[allow(unused_imports)]
+    use async_compression::tokio::write;
+
+    match compression {
+        None => Box::new(writer),
+        #[cfg(feature = "gzip")]
+        Some(Compression::Gzip) => Box::new(write::GzipEncoder::new(writer)),
+        #[cfg(feature = "zstd")]
+        Some(Compression::Zstd) => Box::new(write::ZstdEncoder::new(writer)),
+        #[cfg(feature = "bzip2")]
+        Some(Compression::Bzip2) => Box::new(write::BzEncoder::new(writer)),
+        #[cfg(feature = "xz")]
+        Some(Compression::Xz) => Box::new(write::XzEncoder::new(writer)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{BufReader, Read, Write};
+
+    use super::{Encoder, decoder};
+    use crate::types::Compression;
+
+    #[test]
+    fn test_round_trip() {
+        #[allow(unused_mut)]
+        let mut codecs: Vec<Option<Compression>> = vec![None];
+        #[cfg(feature = "gzip")]
+        codecs.push(Some(Compression::Gzip));
+        #[cfg(feature = "zstd")]
+        codecs.push(Some(Compression::Zstd));
+        #[cfg(feature = "bzip2")]
+        codecs.push(Some(Compression::Bzip2));
+        #[cfg(feature = "xz")]
+        codecs.push(Some(Compression::Xz));
+
+        let data = b"{\"id\":1}\n{\"id\":2}\n".repeat(100);
+        for compression in codecs {
+            let mut encoder = Encoder::new(Vec::new(), compression).unwrap();
+            encoder.write_all(&data).unwrap();
+            let encoded = encoder.finish().unwrap();
+
+            let mut decoded = Vec::new();
+            decoder(BufReader::new(&encoded[..]), compression)
+                .unwrap()
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(data, decoded, "{compression:?}");
+        }
+    }
+}
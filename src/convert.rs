@@ -0,0 +1,370 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    path::Path,
+};
+
+#[cfg(feature = "csv")]
+use crate::write::check_flat;
+use crate::{
+    Error, RecordWriter, detect,
+    options::{ReadOptions, WriteOptions},
+    read::{open_reader, read_value_from_reader_with_options, read_value_records},
+    types::{DataFormat, resolve_format},
+    value::Value,
+    write::{create_writer, value_options, write_value_to_writer_with_options},
+};
+
+/// What is read from the input: a whole document, or records one at a time.
+enum Content<'a> {
+    Document(Value),
+    Records(Box<dyn Iterator<Item = Result<Value, Error>> + 'a>),
+}
+
+/// Reads the input as records when either side only holds records, and as a
+/// document otherwise.
+fn read_content<'a>(
+    reader: impl Read + 'a,
+    input_format: DataFormat,
+    output_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<Content<'a>, Error> {
+    if input_format.holds_only_records() {
+        return read_value_records(reader, input_format, options).map(Content::Records);
+    }
+    let mismatch = || Error::RecordMismatch {
+        input: input_format,
+        output: output_format,
+    };
+    if !output_format.holds_only_records() {
+        return read_value_from_reader_with_options(reader, input_format, options)
+            .map(Content::Document);
+    }
+    if input_format == DataFormat::Json {
+        // Only a top-level array holds records; it is streamed element by element.
+        let mut reader = BufReader::new(reader);
+        let first = reader.fill_buf()?.iter().find(|b| !b.is_ascii_whitespace());
+        if first != Some(&b'[') {
+            return Err(mismatch());
+        }
+        return read_value_records(reader, input_format, options).map(Content::Records);
+    }
+    let records = match read_value_from_reader_with_options(reader, input_format, options)? {
+        Value::Array(records) => records,
+        #[cfg(feature = "toml")]
+        Value::Object(mut table) if input_format == DataFormat::Toml => {
+            match table.shift_remove(&options.toml.key) {
+                Some(Value::Array(records)) => records,
+                _ => return Err(mismatch()),
+            }
+        }
+        _ => return Err(mismatch()),
+    };
+    Ok(Content::Records(Box::new(records.into_iter().map(Ok))))
+}
+
+fn write_content(
+    writer: impl Write,
+    content: Content<'_>,
+    output_format: DataFormat,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let records = match content {
+        Content::Document(value) => {
+            return write_value_to_writer_with_options(writer, &value, output_format, options);
+        }
+        Content::Records(records) => records,
+    };
+    #[cfg(feature = "toml")]
+    if output_format == DataFormat::Toml {
+        let records = Value::Array(records.collect::<Result<_, _>>()?);
+        return write_value_to_writer_with_options(writer, &records, output_format, options);
+    }
+    #[cfg(feature = "csv")]
+    let records = records
+        .enumerate()
+        .map(|(index, record)| -> Result<Value, Error> {
+            let record = record?;
+            if matches!(output_format, DataFormat::Csv | DataFormat::Tsv) {
+                check_flat(&record, index, options).map_err(|e| e.with_format(output_format))?;
+            }
+            Ok(record)
+        });
+    let mut sink = RecordWriter::with_options(writer, output_format, &value_options(options))?;
+    for record in records {
+        sink.write(&record?)?;
+    }
+    sink.finish()?;
+    Ok(())
+}
+
+/// Converts data from one format to another without a type to deserialize
+/// into.
+///
+/// When either format only holds multiple records (JSON Lines, CSV, TSV), the
+/// input is converted record by record; the records of JSON Lines, CSV, TSV
+/// and of a top-level JSON array are streamed from the reader, and written as
+/// they arrive unless the output is MessagePack, TOML, or CSV or TSV with
+/// `csv.union_columns`. A document
+/// in any other format must then hold multiple records: an array, a YAML
+/// stream of several documents, or TOML `[[record]]` tables. Otherwise, the
+/// whole document is converted as a [`Value`].
+///
+/// With `DataFormat::Auto` the input format, and any compression, is detected
+/// from the content; see [`crate::detect_format`].
+///
+/// # Errors
+///
+/// Returns an error if `output_format` is `Auto`, the input format cannot be
+/// detected, [`Error::RecordMismatch`] if the input holds a single record but
+/// the output format only holds multiple records, or an error if decoding or
+/// encoding fails, such as for a null in TOML.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::Cursor;
+///
+/// use serdeio::{convert_reader, DataFormat};
+///
+/// let json_data = r#"[{"name": "Alice", "age": 30}, {"name": "Bob", "age": 25}]"#;
+/// let mut buffer = Vec::new();
+/// convert_reader(Cursor::new(json_data), DataFormat::Auto, &mut buffer, DataFormat::JsonLines)
+///     .unwrap();
+/// assert_eq!(
+///     String::from_utf8(buffer).unwrap(),
+///     "{\"name\":\"Alice\",\"age\":30}\n{\"name\":\"Bob\",\"age\":25}\n"
+/// );
+/// ```
+pub fn convert_reader(
+    reader: impl Read,
+    input_format: DataFormat,
+    writer: impl Write,
+    output_format: DataFormat,
+) -> Result<(), Error> {
+    convert_reader_with_options(
+        reader,
+        input_format,
+        writer,
+        output_format,
+        &ReadOptions::default(),
+        &WriteOptions::default(),
+    )
+}
+
+/// Converts data from one format to another with backend settings for the
+/// input from `read_options` and for the output from `write_options`.
+///
+/// See [`convert_reader`] for how the content is converted. CSV and TSV output
+/// is only flattened with `csv.flatten` set in `write_options`, under the
+/// columns of the first record unless `csv.union_columns` is set; without it,
+/// a record with a nested member is an error. CSV and TSV input is only
+/// unflattened with `csv.flatten` set in `read_options`.
+///
+/// # Errors
+///
+/// Returns an error if `output_format` is `Auto`, the input format cannot be
+/// detected, [`Error::RecordMismatch`] if the input holds a single record but
+/// the output format only holds multiple records, or an error if decoding or
+/// encoding fails.
+pub fn convert_reader_with_options(
+    reader: impl Read,
+    input_format: DataFormat,
+    writer: impl Write,
+    output_format: DataFormat,
+    read_options: &ReadOptions,
+    write_options: &WriteOptions,
+) -> Result<(), Error> {
+    if output_format == DataFormat::Auto {
+        return Err(Error::AutoNotSupported);
+    }
+    let content = if input_format == DataFormat::Auto {
+        let (reader, input_format) = detect::sniff_reader(reader)?;
+        read_content(reader, input_format, output_format, read_options)?
+    } else {
+        read_content(reader, input_format, output_format, read_options)?
+    };
+    write_content(writer, content, output_format, write_options)
+}
+
+/// Converts a file to another file, with the formats inferred from the file
+/// extensions.
+///
+/// A trailing compression extension (`.gz`, `.zst`, `.bz2`, `.xz`) is handled
+/// on either side when the matching feature is enabled. When the input
+/// extension is missing or unknown, its format is detected from the content
+/// instead. See [`convert_reader`] for how the content is converted.
+///
+/// # Errors
+///
+/// Returns an error if the input cannot be opened or its format determined,
+/// the output cannot be created or its extension is unknown,
+/// [`Error::RecordMismatch`] if the input holds a single record but the output
+/// format only holds multiple records, or an error if decoding or encoding
+/// fails.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serdeio::convert_file;
+///
+/// convert_file("users.json", "users.csv.gz").unwrap();
+/// ```
+pub fn convert_file(input: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<(), Error> {
+    convert_file_with_options(
+        input,
+        DataFormat::Auto,
+        output,
+        DataFormat::Auto,
+        &ReadOptions::default(),
+        &WriteOptions::default(),
+    )
+}
+
+/// Converts a file to another file with backend settings for the input from
+/// `read_options` and for the output from `write_options`.
+///
+/// If `input_format` or `output_format` is set to a format other than `Auto`,
+/// that format is used as-is and the file extension is ignored. See
+/// [`convert_file`] for how the formats are otherwise resolved.
+///
+/// # Errors
+///
+/// Returns an error if the input cannot be opened or its format determined,
+/// the output cannot be created or its extension is unknown,
+/// [`Error::RecordMismatch`] if the input holds a single record but the output
+/// format only holds multiple records, or an error if decoding or encoding
+/// fails.
+pub fn convert_file_with_options(
+    input: impl AsRef<Path>,
+    input_format: DataFormat,
+    output: impl AsRef<Path>,
+    output_format: DataFormat,
+    read_options: &ReadOptions,
+    write_options: &WriteOptions,
+) -> Result<(), Error> {
+    let (input, output) = (input.as_ref(), output.as_ref());
+    let output_format = resolve_format(output, output_format).map_err(|e| e.with_path(output))?;
+    let content = open_reader(input, input_format)
+        .and_then(|(reader, input_format)| {
+            read_content(reader, input_format, output_format, read_options)
+        })
+        .map_err(|e| e.with_path(input))?;
+    let content = match content {
+        Content::Records(records) => Content::Records(Box::new(
+            records.map(move |record| record.map_err(|e| e.with_path(input))),
+        )),
+        document => document,
+    };
+    let write = || {
+        let mut writer = create_writer(output)?;
+        write_content(&mut writer, content, output_format, write_options)?;
+        writer.finish()?;
+        Ok(())
+    };
+    write().map_err(|e: Error| e.with_path(output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(input: &str, input_format: DataFormat, output_format: DataFormat) -> String {
+        let mut buffer = Vec::new();
+        convert_reader(input.as_bytes(), input_format, &mut buffer, output_format).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_convert_reader_records() {
+        let jsonl = convert(
+            r#"[{"id": 1, "name": "a"}, {"id": 2, "name": "b"}]"#,
+            DataFormat::Auto,
+            DataFormat::JsonLines,
+        );
+        assert_eq!(
+            jsonl,
+            "{\"id\":1,\"name\":\"a\"}\n{\"id\":2,\"name\":\"b\"}\n"
+        );
+
+        let json = convert(&jsonl, DataFormat::JsonLines, DataFormat::Json);
+        assert_eq!(json, r#"[{"id":1,"name":"a"},{"id":2,"name":"b"}]"#);
+    }
+
+    #[test]
+    fn test_convert_reader_document() {
+        let json = convert(
+            r#"{"b": [1, 2.5], "a": null}"#,
+            DataFormat::Json,
+            DataFormat::Json,
+        );
+        assert_eq!(json, r#"{"b":[1,2.5],"a":null}"#);
+    }
+
+    #[test]
+    fn test_convert_reader_record_mismatch() {
+        let result = convert_reader(
+            r#"{"id": 1}"#.as_bytes(),
+            DataFormat::Json,
+            Vec::new(),
+            DataFormat::JsonLines,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::RecordMismatch {
+                input: DataFormat::Json,
+                output: DataFormat::JsonLines,
+            })
+        ));
+
+        let result = convert_reader(
+            "{}".as_bytes(),
+            DataFormat::Json,
+            Vec::new(),
+            DataFormat::Auto,
+        );
+        assert!(matches!(result, Err(Error::AutoNotSupported)));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_convert_reader_toml_records() {
+        let toml = convert(
+            "{\"id\": 1}\n{\"id\": 2}\n",
+            DataFormat::JsonLines,
+            DataFormat::Toml,
+        );
+        assert_eq!(toml, "[[record]]\nid = 1\n\n[[record]]\nid = 2\n");
+
+        let jsonl = convert(&toml, DataFormat::Toml, DataFormat::JsonLines);
+        assert_eq!(jsonl, "{\"id\":1}\n{\"id\":2}\n");
+
+        let result = convert_reader(
+            "id = 1\n".as_bytes(),
+            DataFormat::Toml,
+            Vec::new(),
+            DataFormat::JsonLines,
+        );
+        assert!(matches!(result, Err(Error::RecordMismatch { .. })));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_convert_reader_yaml_documents() {
+        let json = convert("id: 1\n---\nid: 2\n", DataFormat::Yaml, DataFormat::Json);
+        assert_eq!(json, r#"[{"id":1},{"id":2}]"#);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_convert_file_csv_to_json() {
+        let stem = format!("serdeio_convert_csv_to_json_test_{}", std::process::id());
+        let input = std::env::temp_dir().join(format!("{stem}.csv"));
+        let output = std::env::temp_dir().join(format!("{stem}.json"));
+        std::fs::write(&input, "id,name,score\n1,a,0.5\n").unwrap();
+        convert_file(&input, &output).unwrap();
+        let json = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&input).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        assert_eq!(json, r#"[{"id":1,"name":"a","score":0.5}]"#);
+    }
+}
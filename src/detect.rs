@@ -0,0 +1,431 @@
+use std::{
+    io::{BufReader, Cursor, Read},
+    path::Path,
+};
+
+use crate::{
+    Error, compression,
+    types::{Compression, DataFormat, DataFormatError, resolve_format},
+};
+
+/// Number of leading bytes inspected when detecting the format of a reader.
+pub(crate) const SAMPLE_SIZE: u64 = 8 * 1024;
+
+/// Detects the compression codec of a stream from its magic bytes.
+///
+/// Only codecs whose feature is enabled are detected.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "gzip")] {
+/// use serdeio::{detect_compression, Compression};
+///
+/// assert_eq!(detect_compression(&[0x1f, 0x8b, 0x08, 0x00]), Some(Compression::Gzip));
+/// assert_eq!(detect_compression(b"{\"id\": 1}"), None);
+/// # }
+/// ```
+#[allow(unused_variables)]
+pub fn detect_compression(sample: &[u8]) -> Option<Compression> {
+    #[cfg(feature = "gzip")]
+    if sample.starts_with(&[0x1f, 0x8b]) {
+        return Some(Compression::Gzip);
+    }
+    #[cfg(feature = "zstd")]
+    if sample.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        return Some(Compression::Zstd);
+    }
+    #[cfg(feature = "bzip2")]
+    if sample.starts_with(b"BZh") {
+        return Some(Compression::Bzip2);
+    }
+    #[cfg(feature = "xz")]
+    if sample.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+        return Some(Compression::Xz);
+    }
+    None
+}
+
+/// Detects the data format of a stream from its first bytes.
+///
+/// The detector looks at the leading bytes of the content:
+///
+/// - MessagePack: a leading map or array marker in otherwise non-UTF-8 data
+/// - JSON Lines: a complete JSON value on the first line followed by more lines
+/// - JSON: a leading `{` or `[`
+/// - TOML: a leading `key = value` or `[table]` line
+/// - YAML: a leading `---` document marker, `key: value` or `- item` line
+/// - TSV: leading lines with the same number (at least two) of tab separated fields
+/// - CSV: leading lines with the same number (at least two) of comma separated fields
+///
+/// Blank lines and `#` comment lines before the first significant line are
+/// skipped. Only formats whose feature is enabled are detected. Decompress the
+/// content first if [`detect_compression`] reports a codec.
+///
+/// # Examples
+///
+/// ```rust
+/// use serdeio::{detect_format, DataFormat};
+///
+/// assert_eq!(detect_format(b"[{\"id\": 1}]"), Some(DataFormat::Json));
+/// assert_eq!(detect_format(b"{\"id\": 1}\n{\"id\": 2}\n"), Some(DataFormat::JsonLines));
+/// ```
+pub fn detect_format(sample: &[u8]) -> Option<DataFormat> {
+    #[cfg(feature = "messagepack")]
+    if is_messagepack(sample) {
+        return Some(DataFormat::MessagePack);
+    }
+
+    let text = match std::str::from_utf8(sample) {
+        Ok(text) => text,
+        // The sample may end in the middle of a multi-byte character.
+        Err(e) if e.error_len().is_none() => {
+            std::str::from_utf8(&sample[..e.valid_up_to()]).ok()?
+        }
+        Err(_) => return None,
+    };
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim_start().is_empty())
+        .collect();
+    let start = lines.iter().position(|line| !line.starts_with('#'))?;
+    let first = lines[start];
+    let first_trimmed = first.trim_start();
+    let starts_json = |line: &str| line.starts_with('{') || line.starts_with('[');
+
+    if starts_json(first_trimmed) {
+        let first_is_value = serde_json::from_str::<serde::de::IgnoredAny>(first).is_ok();
+        let next_is_value = lines
+            .get(start + 1)
+            .is_some_and(|line| starts_json(line.trim_start()));
+        if first_is_value && next_is_value {
+            return Some(DataFormat::JsonLines);
+        }
+        #[cfg(feature = "toml")]
+        if !first_is_value && is_toml_table_header(first_trimmed) {
+            return Some(DataFormat::Toml);
+        }
+        return Some(DataFormat::Json);
+    }
+
+    #[cfg(feature = "yaml")]
+    if first.starts_with("---") || first.starts_with("%YAML") {
+        return Some(DataFormat::Yaml);
+    }
+
+    #[cfg(feature = "toml")]
+    if is_toml_key_value(first) {
+        return Some(DataFormat::Toml);
+    }
+
+    #[cfg(feature = "yaml")]
+    if is_yaml_entry(first) {
+        return Some(DataFormat::Yaml);
+    }
+
+    #[cfg(feature = "csv")]
+    if is_delimited(text, '\t') {
+        return Some(DataFormat::Tsv);
+    }
+
+    #[cfg(feature = "csv")]
+    if is_delimited(text, ',') {
+        return Some(DataFormat::Csv);
+    }
+
+    None
+}
+
+#[cfg(feature = "messagepack")]
+fn is_messagepack(sample: &[u8]) -> bool {
+    match sample.first() {
+        // fixmap and fixarray markers are never valid UTF-8 lead bytes.
+        Some(0x80..=0x9f) => true,
+        // array 16/32 and map 16/32 markers double as UTF-8 lead bytes.
+        Some(0xdc..=0xdf) => std::str::from_utf8(sample).is_err_and(|e| e.error_len().is_some()),
+        _ => false,
+    }
+}
+
+#[cfg(feature = "toml")]
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '"' | '\'' | ' '))
+}
+
+#[cfg(feature = "toml")]
+fn is_toml_table_header(line: &str) -> bool {
+    let line = line.split('#').next().unwrap_or(line).trim();
+    let name = line
+        .strip_prefix("[[")
+        .and_then(|rest| rest.strip_suffix("]]"))
+        .or_else(|| {
+            line.strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+        });
+    name.is_some_and(|name| is_bare_key(name.trim()))
+}
+
+#[cfg(feature = "toml")]
+fn is_toml_key_value(line: &str) -> bool {
+    line.split_once('=')
+        .is_some_and(|(key, _)| is_bare_key(key.trim()) && !key.trim().contains(' '))
+}
+
+#[cfg(feature = "yaml")]
+fn is_yaml_entry(line: &str) -> bool {
+    if line == "-" || line.starts_with("- ") {
+        return true;
+    }
+    let key = match line.split_once(": ") {
+        Some((key, _)) => key,
+        None => match line.strip_suffix(':') {
+            Some(key) => key,
+            None => return false,
+        },
+    };
+    let key = key.trim();
+    if let Some(quoted) = key.strip_prefix('"') {
+        return quoted.ends_with('"');
+    }
+    !key.is_empty() && !key.contains(',') && !key.starts_with(['{', '[', '#', '&', '*'])
+}
+
+#[cfg(feature = "csv")]
+fn count_csv_fields(line: &str, delimiter: char) -> usize {
+    let mut in_quotes = false;
+    let mut fields = 1;
+    for c in line.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields += 1,
+            _ => {}
+        }
+    }
+    fields
+}
+
+#[cfg(feature = "csv")]
+fn is_delimited(text: &str, delimiter: char) -> bool {
+    let mut lines = text
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .take(5);
+    let Some(header) = lines.next() else {
+        return false;
+    };
+    let fields = count_csv_fields(header, delimiter);
+    // The last sampled line may be truncated, so it only has to not exceed
+    // the header's field count.
+    let rest: Vec<&str> = lines.collect();
+    fields >= 2
+        && rest.iter().enumerate().all(|(i, line)| {
+            let n = count_csv_fields(line, delimiter);
+            n == fields || (i + 1 == rest.len() && n < fields)
+        })
+}
+
+/// Reads up to [`SAMPLE_SIZE`] leading bytes of `reader`.
+fn read_sample(reader: &mut impl Read) -> Result<Vec<u8>, Error> {
+    let mut sample = Vec::new();
+    reader.take(SAMPLE_SIZE).read_to_end(&mut sample)?;
+    Ok(sample)
+}
+
+/// Reads a sample of `reader`, decompressing it when magic bytes of a
+/// compression codec are found, and detects its data format.
+///
+/// Returns a reader over the full (decompressed) content together with the
+/// detected format.
+pub(crate) fn sniff_reader<'a>(
+    mut reader: impl Read + 'a,
+) -> Result<(Box<dyn Read + 'a>, DataFormat), Error> {
+    let sample = read_sample(&mut reader)?;
+    let compression = detect_compression(&sample);
+    let reader = BufReader::new(Cursor::new(sample).chain(reader));
+    let mut reader = compression::decoder(reader, compression)?;
+
+    let sample = read_sample(&mut reader)?;
+    let format = detect_format(&sample).ok_or(DataFormatError::Undetected)?;
+    Ok((Box::new(Cursor::new(sample).chain(reader)), format))
+}
+
+/// Resolves the data format of the file at `path` whose content is read
+/// from `reader`.
+///
+/// The format is taken from `data_format` or the file extension as usual.
+/// When `data_format` is `Auto` and the extension is missing, not UTF-8, or
+/// unknown, the content is sniffed instead.
+pub(crate) fn resolve_or_sniff<'a>(
+    path: &Path,
+    data_format: DataFormat,
+    reader: impl Read + 'a,
+) -> Result<(Box<dyn Read + 'a>, DataFormat), Error> {
+    let extension_error = match resolve_format(path, data_format) {
+        Ok(data_format) => return Ok((Box::new(reader), data_format)),
+        Err(Error::DataFormat(e)) => e,
+        Err(e) => return Err(e),
+    };
+    sniff_reader(reader).map_err(|e| unresolved(path, extension_error, e))
+}
+
+/// Turns a failure to detect the format of the file at `path` into an error
+/// that also tells why its extension did not resolve it.
+pub(crate) fn unresolved(path: &Path, extension_error: DataFormatError, error: Error) -> Error {
+    match error {
+        Error::DataFormat(DataFormatError::Undetected) => DataFormatError::Unresolved {
+            path: path.to_path_buf(),
+            extension: Box::new(extension_error),
+        }
+        .into(),
+        e => e,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{Cursor, Read},
+        path::Path,
+    };
+
+    use super::{detect_format, resolve_or_sniff, sniff_reader};
+    use crate::{DataFormat, DataFormatError, Error};
+
+    #[test]
+    fn test_detect_json() {
+        assert_eq!(detect_format(b"[1, 2, 3]"), Some(DataFormat::Json));
+        assert_eq!(
+            detect_format(b"\xef\xbb\xbf\n  {\n  \"id\": 1\n}\n"),
+            Some(DataFormat::Json)
+        );
+        assert_eq!(
+            detect_format(b"[\n  {\"id\": 1},\n  {\"id\": 2}\n]"),
+            Some(DataFormat::Json)
+        );
+        assert_eq!(
+            detect_format(b"{\"id\": 1}\n\n{\"id\": 2}\n"),
+            Some(DataFormat::JsonLines)
+        );
+        assert_eq!(detect_format(b"[1]\n[2]\n"), Some(DataFormat::JsonLines));
+        assert_eq!(detect_format(b""), None);
+        assert_eq!(detect_format(b"hello world"), None);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_detect_toml() {
+        assert_eq!(
+            detect_format(b"# config\nname = \"app\"\n"),
+            Some(DataFormat::Toml)
+        );
+        assert_eq!(
+            detect_format(b"[server]\nport = 80\n"),
+            Some(DataFormat::Toml)
+        );
+        assert_eq!(
+            detect_format(b"[[servers]]\nport = 80\n"),
+            Some(DataFormat::Toml)
+        );
+        assert_eq!(detect_format(b"[\"a\"]\n"), Some(DataFormat::Json));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_detect_yaml() {
+        assert_eq!(detect_format(b"---\nname: app\n"), Some(DataFormat::Yaml));
+        assert_eq!(
+            detect_format(b"# config\nname: app\nport: 80\n"),
+            Some(DataFormat::Yaml)
+        );
+        assert_eq!(detect_format(b"- a\n- b\n"), Some(DataFormat::Yaml));
+        assert_eq!(detect_format(b"servers:\n  - a\n"), Some(DataFormat::Yaml));
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_detect_csv() {
+        assert_eq!(
+            detect_format(b"id,name\n1,\"foo, bar\"\n2,baz\n"),
+            Some(DataFormat::Csv)
+        );
+        assert_eq!(detect_format(b"id,name\n1,foo,extra\n2,baz\n"), None);
+        assert_eq!(
+            detect_format(b"id\tname\n1\tfoo, bar\n2\tbaz\n"),
+            Some(DataFormat::Tsv)
+        );
+    }
+
+    #[cfg(feature = "messagepack")]
+    #[test]
+    fn test_detect_messagepack() {
+        let data = rmp_serde::to_vec(&vec![1, 2, 3]).unwrap();
+        assert_eq!(detect_format(&data), Some(DataFormat::MessagePack));
+        let data = rmp_serde::to_vec_named(&std::collections::BTreeMap::from([("a", 1)])).unwrap();
+        assert_eq!(detect_format(&data), Some(DataFormat::MessagePack));
+    }
+
+    #[test]
+    fn test_sniff_reader_preserves_content() {
+        let data = format!("{}\n", "{\"id\": 1}\n".repeat(2000));
+        let (mut reader, format) = sniff_reader(Cursor::new(data.clone())).unwrap();
+        assert_eq!(format, DataFormat::JsonLines);
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        assert_eq!(data, content);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_sniff_reader_gzip() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"[1, 2, 3]").unwrap();
+        let data = encoder.finish().unwrap();
+        let (mut reader, format) = sniff_reader(Cursor::new(data)).unwrap();
+        assert_eq!(format, DataFormat::Json);
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "[1, 2, 3]");
+    }
+
+    #[test]
+    fn test_resolve_or_sniff() {
+        let path = Path::new("data");
+        let (_, format) = resolve_or_sniff(path, DataFormat::Auto, Cursor::new("[1]")).unwrap();
+        assert_eq!(format, DataFormat::Json);
+
+        let path = Path::new("data.unknown");
+        let (_, format) = resolve_or_sniff(path, DataFormat::Auto, Cursor::new("[1]")).unwrap();
+        assert_eq!(format, DataFormat::Json);
+
+        let path = Path::new("data.jsonl");
+        let (_, format) = resolve_or_sniff(path, DataFormat::Auto, Cursor::new("[1]")).unwrap();
+        assert_eq!(format, DataFormat::JsonLines);
+
+        let result = resolve_or_sniff(path, DataFormat::Json, Cursor::new("")).map(|(_, f)| f);
+        assert_eq!(result.unwrap(), DataFormat::Json);
+    }
+
+    #[test]
+    fn test_resolve_or_sniff_reports_strategies() {
+        let path = Path::new("data");
+        let Err(err) = resolve_or_sniff(path, DataFormat::Auto, Cursor::new("plain text")) else {
+            panic!("expected an error");
+        };
+        assert!(matches!(
+            &err,
+            Error::DataFormat(DataFormatError::Unresolved { extension, .. })
+                if matches!(**extension, DataFormatError::NoExtension(_))
+        ));
+        let message = err.to_string();
+        assert!(message.contains("file extension"), "{message}");
+        assert!(message.contains("content detection"), "{message}");
+    }
+}
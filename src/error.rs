@@ -1,5 +1,27 @@
+use std::{fmt, path::PathBuf};
+
 use thiserror::Error;
 
+use crate::types::DataFormat;
+
+/// Error returned by every serdeio function.
+///
+/// Errors that know where they occurred, such as decoding errors and any
+/// error from a file function, are wrapped in [`Error::Context`]. Match on
+/// [`Error::kind`] rather than on the error itself to reach the underlying
+/// variant whether or not it carries context.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serdeio::{read_record_from_file, DataFormat, Error};
+///
+/// match read_record_from_file::<serde_json::Value>("config.json", DataFormat::Auto) {
+///     Ok(config) => println!("{config}"),
+///     Err(e) if matches!(e.kind(), Error::Io(_)) => println!("cannot read config: {e}"),
+///     Err(e) => println!("invalid config: {e}"),
+/// }
+/// ```
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("Auto data format is not supported for this operation")]
@@ -8,6 +30,8 @@ pub enum Error {
     DataFormat(#[from] crate::types::DataFormatError),
     #[error("Unsupported file format: {0}")]
     UnsupportedFormat(crate::types::DataFormat),
+    #[error("Unsupported compression for this operation: {0}")]
+    UnsupportedCompression(crate::types::Compression),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     #[error("JSON error: {0}")]
@@ -15,6 +39,9 @@ pub enum Error {
     #[cfg(feature = "csv")]
     #[error("CSV error: {0}")]
     Csv(#[from] csv::Error),
+    #[cfg(feature = "csv")]
+    #[error("CSV flattening error: {0}")]
+    Flatten(#[from] serde::de::value::Error),
     #[cfg(feature = "yaml")]
     #[error("YAML error: {0}")]
     Yaml(#[from] serde_yaml::Error),
@@ -30,4 +57,306 @@ pub enum Error {
     #[cfg(feature = "toml")]
     #[error("TOML serialization error: {0}")]
     TomlSerialize(#[from] toml::ser::Error),
+    #[error(
+        "Cannot convert {input} to {output}: the input holds a single record, but {output} only holds multiple records"
+    )]
+    RecordMismatch {
+        input: DataFormat,
+        output: DataFormat,
+    },
+    #[error("JSON Pointer `{0}` does not refer to a location in the document")]
+    PointerNotFound(String),
+    #[cfg(feature = "schema")]
+    #[error("Invalid JSON Schema: {0}")]
+    InvalidSchema(String),
+    #[cfg(feature = "schema")]
+    #[error("Schema violation: {0}")]
+    SchemaViolation(String),
+    #[cfg(feature = "schema")]
+    #[error("{} JSON Schema violation(s){}", .0.len(), first_violation(.0))]
+    SchemaViolations(Vec<Error>),
+    #[error("Too many rejected records ({rejected} of {total}), last: {source}")]
+    TooManyRejected {
+        rejected: usize,
+        total: usize,
+        source: Box<Error>,
+    },
+    /// Another error together with where it occurred. Decoding errors and
+    /// file errors come wrapped in it, so match on [`Error::kind`] rather than
+    /// on the variant itself.
+    #[error("{context}: {source}")]
+    Context {
+        context: Box<ErrorContext>,
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Returns where the error occurred, if known.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::Context { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Returns the underlying error without its positional context.
+    pub fn kind(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.kind(),
+            e => e,
+        }
+    }
+
+    /// Records additional context, keeping whatever is already known.
+    fn with_context(self, update: impl FnOnce(&mut ErrorContext)) -> Self {
+        match self {
+            Error::Context {
+                mut context,
+                source,
+            } => {
+                update(&mut context);
+                Error::Context { context, source }
+            }
+            source => {
+                let mut context = Box::default();
+                update(&mut context);
+                Error::Context {
+                    context,
+                    source: Box::new(source),
+                }
+            }
+        }
+    }
+
+    pub(crate) fn with_path(self, path: impl Into<PathBuf>) -> Self {
+        self.with_context(|c| {
+            c.path.get_or_insert_with(|| path.into());
+        })
+    }
+
+    pub(crate) fn with_format(self, format: DataFormat) -> Self {
+        self.with_context(|c| {
+            c.format.get_or_insert(format);
+        })
+    }
+
+    pub(crate) fn with_record(self, index: usize) -> Self {
+        self.with_context(|c| {
+            c.record.get_or_insert(index);
+        })
+    }
+
+    pub(crate) fn with_field(self, field: String) -> Self {
+        self.with_context(|c| {
+            c.field.get_or_insert(field);
+        })
+    }
+
+    pub(crate) fn with_raw(self, raw: String) -> Self {
+        self.with_context(|c| {
+            c.raw.get_or_insert(raw);
+        })
+    }
+
+    /// Records a position in the input; any field may be unknown.
+    pub(crate) fn with_position(
+        self,
+        line: Option<u64>,
+        column: Option<u64>,
+        offset: Option<u64>,
+    ) -> Self {
+        self.with_context(|c| {
+            c.line = c.line.or(line);
+            c.column = c.column.or(column);
+            c.offset = c.offset.or(offset);
+        })
+    }
+}
+
+/// Where in the input an [`Error`] occurred.
+///
+/// Every field is optional because not every backend can report every
+/// position: MessagePack has no lines, CSV has no meaningful column, and the
+/// reader APIs have no path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    path: Option<PathBuf>,
+    format: Option<DataFormat>,
+    record: Option<usize>,
+    field: Option<String>,
+    line: Option<u64>,
+    column: Option<u64>,
+    offset: Option<u64>,
+    raw: Option<String>,
+}
+
+impl ErrorContext {
+    /// Path of the file being read or written.
+    pub fn path(&self) -> Option<&std::path::Path> {
+        self.path.as_deref()
+    }
+
+    /// Data format of the input.
+    pub fn format(&self) -> Option<DataFormat> {
+        self.format
+    }
+
+    /// Zero-based index of the record that failed.
+    pub fn record(&self) -> Option<usize> {
+        self.record
+    }
+
+    /// Path to the field that failed within the record, such as
+    /// `servers[3].tls.port`. For CSV this is the column header.
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    /// One-based line number within the whole input.
+    pub fn line(&self) -> Option<u64> {
+        self.line
+    }
+
+    /// One-based column within the line.
+    pub fn column(&self) -> Option<u64> {
+        self.column
+    }
+
+    /// Zero-based byte offset within the whole (decompressed) input.
+    pub fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+
+    /// Raw text of the record that failed, for formats that delimit each
+    /// record on its own (JSON arrays, JSON Lines, CSV, YAML document streams).
+    pub fn raw(&self) -> Option<&str> {
+        self.raw.as_deref()
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(path) = &self.path {
+            parts.push(format!("file {}", path.display()));
+        }
+        if let Some(format) = self.format {
+            parts.push(format!("format {format}"));
+        }
+        if let Some(record) = self.record {
+            parts.push(format!("record {record}"));
+        }
+        if let Some(field) = &self.field {
+            parts.push(format!("field {field}"));
+        }
+        if let Some(line) = self.line {
+            parts.push(format!("line {line}"));
+        }
+        if let Some(column) = self.column {
+            parts.push(format!("column {column}"));
+        }
+        if let Some(offset) = self.offset {
+            parts.push(format!("byte offset {offset}"));
+        }
+        f.write_str(&parts.join(", "))
+    }
+}
+
+/// Converts a one-based line and column within `text` to a byte offset.
+pub(crate) fn offset_of(text: &[u8], line: usize, column: usize) -> usize {
+    let line_start = text
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| **b == b'\n')
+        .nth(line.saturating_sub(2))
+        .map_or(0, |(i, _)| if line > 1 { i + 1 } else { 0 });
+    (line_start + column.saturating_sub(1)).min(text.len())
+}
+
+/// Converts a byte offset within `text` to a one-based line and column.
+#[cfg(feature = "toml")]
+pub(crate) fn line_column_of(text: &[u8], offset: usize) -> (u64, u64) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.iter().filter(|b| **b == b'\n').count() + 1;
+    let line_start = before
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+    (line as u64, (before.len() - line_start + 1) as u64)
+}
+
+/// Spells the first of a list of schema violations for [`Error::SchemaViolations`],
+/// which may be empty.
+#[cfg(feature = "schema")]
+fn first_violation(violations: &[Error]) -> String {
+    violations
+        .first()
+        .map(|error| format!(", first: {error}"))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_display_and_kind() {
+        let error = Error::Io(std::io::Error::other("boom"))
+            .with_position(Some(3), Some(5), Some(20))
+            .with_record(2)
+            .with_field("servers[3].port".to_string())
+            .with_format(DataFormat::JsonLines)
+            .with_path("data.jsonl")
+            .with_record(7);
+        assert_eq!(
+            error.to_string(),
+            "file data.jsonl, format jsonl, record 2, field servers[3].port, line 3, column 5, byte offset 20: IO error: boom"
+        );
+        assert!(matches!(error.kind(), Error::Io(_)));
+        let context = error.context().unwrap();
+        assert_eq!(context.record(), Some(2));
+        assert_eq!(context.path(), Some(std::path::Path::new("data.jsonl")));
+    }
+
+    #[test]
+    fn test_file_errors_keep_their_kind() {
+        let error = crate::read_record_from_file::<serde_json::Value>(
+            "serdeio_missing_test.json",
+            DataFormat::Auto,
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::Context { .. }));
+        assert!(matches!(error.kind(), Error::Io(_)));
+    }
+
+    #[test]
+    fn test_offset_conversions() {
+        let text = b"ab\ncde\nf";
+        assert_eq!(offset_of(text, 1, 2), 1);
+        assert_eq!(offset_of(text, 2, 3), 5);
+        assert_eq!(offset_of(text, 3, 1), 7);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_line_column_of() {
+        let text = b"ab\ncde\nf";
+        assert_eq!(line_column_of(text, 5), (2, 3));
+        assert_eq!(line_column_of(text, 0), (1, 1));
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_schema_violations_display() {
+        assert_eq!(
+            Error::SchemaViolations(Vec::new()).to_string(),
+            "0 JSON Schema violation(s)"
+        );
+        let error = Error::SchemaViolations(vec![Error::SchemaViolation("too big".to_string())]);
+        assert_eq!(
+            error.to_string(),
+            "1 JSON Schema violation(s), first: Schema violation: too big"
+        );
+    }
 }
@@ -0,0 +1,782 @@
+use std::{collections::HashSet, fmt::Write, io::Read, path::Path};
+
+use indexmap::IndexMap;
+
+use crate::{
+    Error,
+    options::ReadOptions,
+    read::{open_reader, read_value_from_reader_with_options, read_value_records},
+    types::DataFormat,
+    value::{Map, Value},
+};
+
+/// The shape of the records of some input, inferred from every record in it.
+///
+/// Get one with [`infer_schema_from_reader`] or [`infer_schema_from_file`],
+/// then render it with [`InferredSchema::to_json_schema`] or
+/// [`InferredSchema::to_rust`].
+#[derive(Debug, Clone)]
+pub struct InferredSchema {
+    shape: Shape,
+}
+
+impl InferredSchema {
+    /// Number of records the schema was inferred from.
+    pub fn records(&self) -> usize {
+        self.shape.seen
+    }
+
+    /// Renders the schema as a JSON Schema (draft 2020-12) for one record.
+    ///
+    /// Each place in the records gets the types seen there, `"null"` included,
+    /// with `"integer"` only if no float was seen. Strings get a `format` of
+    /// `date-time`, `date` or `time` when every one of them looks like it,
+    /// times and date-times only with a UTC offset.
+    /// Object members present in every object are `required`. Arrays get
+    /// `minItems` when none was empty, and `maxItems` as well when they all
+    /// had the same length.
+    pub fn to_json_schema(&self) -> Value {
+        let mut schema = Map::new();
+        schema.insert(
+            "$schema".to_string(),
+            Value::String("https://json-schema.org/draft/2020-12/schema".to_string()),
+        );
+        schema.extend(self.shape.json_schema());
+        Value::Object(schema)
+    }
+
+    /// Renders a Rust struct definition named `name` for one record, with
+    /// nested structs for nested objects, ready to read the records with
+    /// [`crate::read_records_from_file`].
+    ///
+    /// Fields that were null or missing in some records are `Option`s, and
+    /// members whose name is not a Rust identifier are renamed with
+    /// `#[serde(rename = "...")]`. Places that held values of different types
+    /// are typed as [`crate::Value`].
+    pub fn to_rust(&self, name: &str) -> String {
+        let mut names = HashSet::new();
+        let mut items = Vec::new();
+        let name = unique(pascal_case(name), &mut names);
+        match &self.shape {
+            shape if shape.kinds() == 1 && !shape.fields.is_empty() => {
+                shape.rust_struct(&name, &mut items, &mut names);
+            }
+            shape => {
+                let ty = shape.rust_type(&name, &mut items, &mut names);
+                items.insert(0, format!("pub type {name} = {ty};\n"));
+            }
+        }
+        let mut code = "use serde::{Deserialize, Serialize};\n".to_string();
+        for item in items {
+            code.push('\n');
+            code.push_str(&item);
+        }
+        code
+    }
+}
+
+/// What has been seen at one place in the records.
+#[derive(Debug, Clone, Default)]
+struct Shape {
+    /// Number of values seen, nulls included.
+    seen: usize,
+    nulls: usize,
+    bools: usize,
+    integers: usize,
+    /// Smallest and largest integer seen.
+    range: Option<(i128, i128)>,
+    floats: usize,
+    strings: usize,
+    /// Format of every string seen so far: `None` before the first string,
+    /// `Some(None)` once two strings disagree.
+    format: Option<Option<&'static str>>,
+    bytes: usize,
+    arrays: usize,
+    /// Shortest and longest array seen.
+    lengths: Option<(usize, usize)>,
+    items: Option<Box<Shape>>,
+    objects: usize,
+    fields: IndexMap<String, Shape>,
+}
+
+impl Shape {
+    /// Takes `value` into account. `empty_is_null` reads empty strings as
+    /// nulls, as for CSV cells.
+    fn observe(&mut self, value: &Value, empty_is_null: bool) {
+        self.seen += 1;
+        match value {
+            Value::Null => self.nulls += 1,
+            Value::String(s) if s.is_empty() && empty_is_null => self.nulls += 1,
+            Value::Bool(_) => self.bools += 1,
+            Value::Integer(n) => self.observe_integer(i128::from(*n)),
+            Value::Unsigned(n) => self.observe_integer(i128::from(*n)),
+            Value::Float(_) => self.floats += 1,
+            Value::String(s) | Value::Datetime(s) => {
+                self.strings += 1;
+                let format = string_format(s);
+                self.format = match self.format {
+                    Some(seen) if seen != format => Some(None),
+                    _ => Some(format),
+                };
+            }
+            Value::Bytes(_) => self.bytes += 1,
+            Value::Array(elements) => {
+                self.arrays += 1;
+                let len = elements.len();
+                self.lengths = Some(match self.lengths {
+                    Some((min, max)) => (min.min(len), max.max(len)),
+                    None => (len, len),
+                });
+                let items = self.items.get_or_insert_default();
+                for element in elements {
+                    items.observe(element, empty_is_null);
+                }
+            }
+            Value::Object(members) => {
+                self.objects += 1;
+                for (key, member) in members {
+                    self.fields
+                        .entry(key.clone())
+                        .or_default()
+                        .observe(member, empty_is_null);
+                }
+            }
+        }
+    }
+
+    /// Types as strings the CSV cells of a column that also holds cells that
+    /// are not numbers or booleans, such as a zip code column where only some
+    /// codes have leading zeros.
+    fn cells_as_strings(&mut self) {
+        let scalars = self.bools + self.integers + self.floats;
+        if self.strings > 0 && scalars > 0 {
+            self.strings += scalars;
+            self.bools = 0;
+            self.integers = 0;
+            self.floats = 0;
+            self.range = None;
+            self.format = Some(None);
+        }
+        if let Some(items) = &mut self.items {
+            items.cells_as_strings();
+        }
+        for field in self.fields.values_mut() {
+            field.cells_as_strings();
+        }
+    }
+
+    fn observe_integer(&mut self, n: i128) {
+        self.integers += 1;
+        self.range = Some(match self.range {
+            Some((min, max)) => (min.min(n), max.max(n)),
+            None => (n, n),
+        });
+    }
+
+    /// Number of different non-null types seen, counting integers and floats
+    /// as one.
+    fn kinds(&self) -> usize {
+        [
+            self.bools,
+            self.integers + self.floats,
+            self.strings,
+            self.bytes,
+            self.arrays,
+            self.objects,
+        ]
+        .iter()
+        .filter(|count| **count > 0)
+        .count()
+    }
+
+    fn json_schema(&self) -> Map {
+        let mut schema = Map::new();
+        let mut types = Vec::new();
+        if self.objects > 0 {
+            types.push("object");
+        }
+        if self.arrays > 0 || self.bytes > 0 {
+            types.push("array");
+        }
+        if self.strings > 0 {
+            types.push("string");
+        }
+        if self.floats > 0 {
+            types.push("number");
+        } else if self.integers > 0 {
+            types.push("integer");
+        }
+        if self.bools > 0 {
+            types.push("boolean");
+        }
+        if self.nulls > 0 {
+            types.push("null");
+        }
+        let types: Vec<_> = types
+            .into_iter()
+            .map(|t| Value::String(t.to_string()))
+            .collect();
+        match <[Value; 1]>::try_from(types) {
+            Ok([single]) => {
+                schema.insert("type".to_string(), single);
+            }
+            Err(types) if !types.is_empty() => {
+                schema.insert("type".to_string(), Value::Array(types));
+            }
+            Err(_) => {}
+        }
+        if let Some(Some(format)) = self.format {
+            schema.insert("format".to_string(), Value::String(format.to_string()));
+        }
+        if self.objects > 0 {
+            let properties = self
+                .fields
+                .iter()
+                .map(|(key, field)| (key.clone(), Value::Object(field.json_schema())))
+                .collect();
+            schema.insert("properties".to_string(), Value::Object(properties));
+            let required: Vec<_> = self
+                .fields
+                .iter()
+                .filter(|(_, field)| field.seen == self.objects)
+                .map(|(key, _)| Value::String(key.clone()))
+                .collect();
+            if !required.is_empty() {
+                schema.insert("required".to_string(), Value::Array(required));
+            }
+        }
+        if self.arrays > 0 {
+            if let Some(items) = self.items.as_deref().filter(|items| items.seen > 0) {
+                schema.insert("items".to_string(), Value::Object(items.json_schema()));
+            }
+            if let Some((min, max)) = self.lengths {
+                if min > 0 {
+                    schema.insert("minItems".to_string(), Value::Integer(min as i64));
+                }
+                if min == max {
+                    schema.insert("maxItems".to_string(), Value::Integer(max as i64));
+                }
+            }
+        } else if self.bytes > 0 {
+            schema.insert(
+                "items".to_string(),
+                Value::Object(Map::from_iter([
+                    ("type".to_string(), Value::String("integer".to_string())),
+                    ("minimum".to_string(), Value::Integer(0)),
+                    ("maximum".to_string(), Value::Integer(255)),
+                ])),
+            );
+        }
+        schema
+    }
+
+    /// Returns the Rust type for this place, adding the definitions of any
+    /// nested structs to `items`. `name` is the name for a struct in place of
+    /// an object.
+    fn rust_type(
+        &self,
+        name: &str,
+        items: &mut Vec<String>,
+        names: &mut HashSet<String>,
+    ) -> String {
+        let ty = match self.kinds() {
+            0 if self.seen == 0 => return "serdeio::Value".to_string(),
+            0 => return "Option<serdeio::Value>".to_string(),
+            1 => self.rust_base_type(name, items, names),
+            _ => return "serdeio::Value".to_string(),
+        };
+        if self.nulls > 0 {
+            format!("Option<{ty}>")
+        } else {
+            ty
+        }
+    }
+
+    fn rust_base_type(
+        &self,
+        name: &str,
+        items: &mut Vec<String>,
+        names: &mut HashSet<String>,
+    ) -> String {
+        if self.bools > 0 {
+            "bool".to_string()
+        } else if self.floats > 0 {
+            "f64".to_string()
+        } else if self.integers > 0 {
+            match self.range {
+                Some((min, max)) if max > i128::from(i64::MAX) && min >= 0 => "u64".to_string(),
+                Some((_, max)) if max > i128::from(i64::MAX) => "i128".to_string(),
+                _ => "i64".to_string(),
+            }
+        } else if self.strings > 0 {
+            "String".to_string()
+        } else if self.arrays > 0 {
+            let item = match self.items.as_deref() {
+                Some(items_shape) => items_shape.rust_type(name, items, names),
+                None => "serdeio::Value".to_string(),
+            };
+            format!("Vec<{item}>")
+        } else if self.objects > 0 && !self.fields.is_empty() {
+            let name = unique(name.to_string(), names);
+            self.rust_struct(&name, items, names);
+            name
+        } else if self.objects > 0 {
+            "serdeio::Map".to_string()
+        } else {
+            "serdeio::Value".to_string()
+        }
+    }
+
+    /// Adds the definition of a struct named `name` for this object to
+    /// `items`, followed by those of the structs nested in it.
+    fn rust_struct(&self, name: &str, items: &mut Vec<String>, names: &mut HashSet<String>) {
+        let index = items.len();
+        items.push(String::new());
+        let mut code = format!(
+            "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct {name} {{\n"
+        );
+        let mut fields = HashSet::new();
+        for (key, field) in &self.fields {
+            let ident = unique(raw_identifier(&snake_case(key)), &mut fields);
+            let nested = format!("{name}{}", pascal_case(key));
+            let mut ty = field.rust_type(&nested, items, names);
+            if field.seen < self.objects && !ty.starts_with("Option<") {
+                ty = format!("Option<{ty}>");
+            }
+            if ident.strip_prefix("r#").unwrap_or(&ident) != key {
+                let _ = writeln!(code, "    #[serde(rename = {key:?})]");
+            }
+            let _ = writeln!(code, "    pub {ident}: {ty},");
+        }
+        code.push_str("}\n");
+        items[index] = code;
+    }
+}
+
+/// Returns the JSON Schema format a string looks like, if any.
+fn string_format(s: &str) -> Option<&'static str> {
+    let b = s.as_bytes();
+    let digits = |range: std::ops::Range<usize>| {
+        b.get(range)
+            .is_some_and(|d| d.iter().all(u8::is_ascii_digit))
+    };
+    let is_date = |at: usize| {
+        digits(at..at + 4)
+            && b.get(at + 4) == Some(&b'-')
+            && digits(at + 5..at + 7)
+            && b.get(at + 7) == Some(&b'-')
+            && digits(at + 8..at + 10)
+    };
+    // Length of a time such as `07:32:00.999` at `at`, if there is one.
+    let time = |at: usize| {
+        let whole = digits(at..at + 2)
+            && b.get(at + 2) == Some(&b':')
+            && digits(at + 3..at + 5)
+            && b.get(at + 5) == Some(&b':')
+            && digits(at + 6..at + 8);
+        if !whole {
+            return None;
+        }
+        let mut end = at + 8;
+        if b.get(end) == Some(&b'.') && b.get(end + 1).is_some_and(u8::is_ascii_digit) {
+            end += 1;
+            while b.get(end).is_some_and(u8::is_ascii_digit) {
+                end += 1;
+            }
+        }
+        Some(end)
+    };
+    let offset = |at: usize| match b.get(at) {
+        Some(b'Z' | b'z') => b.len() == at + 1,
+        Some(b'+' | b'-') => {
+            b.len() == at + 6
+                && digits(at + 1..at + 3)
+                && b[at + 3] == b':'
+                && digits(at + 4..at + 6)
+        }
+        _ => false,
+    };
+    if b.len() == 10 && is_date(0) {
+        Some("date")
+    } else if is_date(0) && matches!(b.get(10), Some(b'T' | b't' | b' ')) {
+        time(11).filter(|end| offset(*end)).map(|_| "date-time")
+    } else {
+        // A time without an offset is not a JSON Schema `time`.
+        time(0).filter(|end| offset(*end)).map(|_| "time")
+    }
+}
+
+/// Splits a member name into words at non-alphanumeric characters and at
+/// lower-to-upper case changes.
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+            previous_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lower {
+            words.push(std::mem::take(&mut word));
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        word.extend(c.to_lowercase());
+    }
+    words.extend((!word.is_empty()).then_some(word));
+    words
+}
+
+fn snake_case(name: &str) -> String {
+    let ident = words(name).join("_");
+    match ident.chars().next() {
+        None => "field".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("field_{ident}"),
+        Some(_) => ident,
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    let ident: String = words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect();
+    match ident.chars().next() {
+        None => "Record".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("Record{ident}"),
+        Some(_) => ident,
+    }
+}
+
+/// Appends a number to `name` if it is already taken, and takes it. A
+/// numbered raw identifier is no longer a keyword, so it loses its `r#`.
+fn unique(name: String, taken: &mut HashSet<String>) -> String {
+    let mut candidate = name.clone();
+    let mut n = 2;
+    while !taken.insert(candidate.clone()) {
+        candidate = format!("{}{n}", name.trim_start_matches("r#"));
+        n += 1;
+    }
+    candidate
+}
+
+/// Escapes `ident` if it is a Rust keyword.
+fn raw_identifier(ident: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do",
+        "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in",
+        "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+        "return", "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe",
+        "unsized", "use", "virtual", "where", "while", "yield",
+    ];
+    match ident {
+        // These cannot be raw identifiers.
+        "crate" | "self" | "super" => format!("{ident}_"),
+        _ if KEYWORDS.contains(&ident) => format!("r#{ident}"),
+        _ => ident.to_string(),
+    }
+}
+
+fn infer(
+    reader: impl Read,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<InferredSchema, Error> {
+    let mut shape = Shape::default();
+    #[cfg(feature = "csv")]
+    let empty_is_null = matches!(data_format, DataFormat::Csv | DataFormat::Tsv);
+    #[cfg(not(feature = "csv"))]
+    let empty_is_null = false;
+    if data_format.holds_only_records() {
+        for record in read_value_records(reader, data_format, options)? {
+            shape.observe(&record?, empty_is_null);
+        }
+        if empty_is_null {
+            shape.cells_as_strings();
+        }
+        return Ok(InferredSchema { shape });
+    }
+    let document = read_value_from_reader_with_options(reader, data_format, options)?;
+    let records = match document {
+        Value::Array(records) => records,
+        #[cfg(feature = "toml")]
+        // Records are stored as the only member, an array of tables.
+        Value::Object(mut table) if data_format == DataFormat::Toml && table.len() == 1 => {
+            match table.shift_remove(&options.toml.key) {
+                Some(Value::Array(records)) => records,
+                Some(other) => {
+                    table.insert(options.toml.key.clone(), other);
+                    vec![Value::Object(table)]
+                }
+                None => vec![Value::Object(table)],
+            }
+        }
+        record => vec![record],
+    };
+    for record in &records {
+        shape.observe(record, empty_is_null);
+    }
+    Ok(InferredSchema { shape })
+}
+
+/// Infers the schema of the records in a reader.
+///
+/// Every record is taken into account: each element of a JSON array, each
+/// line of JSON Lines, each CSV or TSV row, each document of a YAML stream
+/// (or element of a YAML sequence), each TOML table under the `[[record]]`
+/// key, or else the whole document as one record. CSV and TSV cells are
+/// untyped, so they are read as numbers or booleans when they look like
+/// them, and empty cells count as nulls.
+///
+/// # Errors
+///
+/// Returns an error if the data format cannot be detected, or if decoding
+/// fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::Cursor;
+///
+/// use serdeio::{infer_schema_from_reader, DataFormat};
+///
+/// let jsonl = "{\"id\": 1, \"email\": null}\n{\"id\": 2, \"email\": \"b@example.com\"}\n";
+/// let schema = infer_schema_from_reader(Cursor::new(jsonl), DataFormat::JsonLines).unwrap();
+/// assert_eq!(schema.records(), 2);
+/// assert!(schema.to_rust("User").contains("pub email: Option<String>,"));
+/// ```
+pub fn infer_schema_from_reader(
+    reader: impl Read,
+    data_format: DataFormat,
+) -> Result<InferredSchema, Error> {
+    infer_schema_from_reader_with_options(reader, data_format, &ReadOptions::default())
+}
+
+/// Infers the schema of the records in a reader with backend settings from
+/// `options`.
+///
+/// See [`infer_schema_from_reader`] for which records are taken into account.
+///
+/// # Errors
+///
+/// Returns an error if the data format cannot be detected, or if decoding
+/// fails.
+pub fn infer_schema_from_reader_with_options(
+    reader: impl Read,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<InferredSchema, Error> {
+    if data_format == DataFormat::Auto {
+        let (reader, data_format) = crate::detect::sniff_reader(reader)?;
+        return infer(reader, data_format, options);
+    }
+    infer(reader, data_format, options)
+}
+
+/// Infers the schema of the records in a file.
+///
+/// The format is resolved as in [`crate::read_records_from_file`]. See
+/// [`infer_schema_from_reader`] for which records are taken into account.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, its format cannot be
+/// determined, or decoding fails.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serdeio::{infer_schema_from_file, DataFormat};
+///
+/// let schema = infer_schema_from_file("users.csv", DataFormat::Auto).unwrap();
+/// println!("{}", schema.to_rust("User"));
+/// ```
+pub fn infer_schema_from_file(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+) -> Result<InferredSchema, Error> {
+    infer_schema_from_file_with_options(path, data_format, &ReadOptions::default())
+}
+
+/// Infers the schema of the records in a file with backend settings from
+/// `options`.
+///
+/// See [`infer_schema_from_file`] for how the format is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, its format cannot be
+/// determined, or decoding fails.
+pub fn infer_schema_from_file_with_options(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<InferredSchema, Error> {
+    let path = path.as_ref();
+    open_reader(path, data_format)
+        .and_then(|(reader, data_format)| infer(reader, data_format, options))
+        .map_err(|e| e.with_path(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn infer_json(records: &str) -> InferredSchema {
+        infer_schema_from_reader(records.as_bytes(), DataFormat::Json).unwrap()
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let schema = infer_json(
+            r#"[
+                {"id": 1, "score": 2, "born": "1990-01-31", "tags": ["a"], "address": {"city": "x"}},
+                {"id": 2, "score": 2.5, "born": null, "tags": [], "extra": true}
+            ]"#,
+        );
+        assert_eq!(schema.records(), 2);
+        assert_eq!(
+            serde_json::Value::from(schema.to_json_schema()),
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {
+                    "id": {"type": "integer"},
+                    "score": {"type": "number"},
+                    "born": {"type": ["string", "null"], "format": "date"},
+                    "tags": {"type": "array", "items": {"type": "string"}},
+                    "address": {
+                        "type": "object",
+                        "properties": {"city": {"type": "string"}},
+                        "required": ["city"],
+                    },
+                    "extra": {"type": "boolean"},
+                },
+                "required": ["id", "score", "born", "tags"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_to_rust() {
+        let schema = infer_json(
+            r#"[
+                {"id": 1, "firstName": "a", "type": "x", "geo": {"lat": 1.5}, "items": [{"sku": "a"}]},
+                {"id": 2, "firstName": "b", "type": 1, "geo": {"lat": 2.0}, "items": []}
+            ]"#,
+        );
+        assert_eq!(
+            schema.to_rust("user"),
+            "use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    pub id: i64,
+    #[serde(rename = \"firstName\")]
+    pub first_name: String,
+    pub r#type: serdeio::Value,
+    pub geo: UserGeo,
+    pub items: Vec<UserItems>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserGeo {
+    pub lat: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserItems {
+    pub sku: String,
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_to_rust_reads_back_sample() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Sample {
+            id: i128,
+            v: Option<Value>,
+        }
+
+        let sample = r#"[{"id": -1, "v": 1}, {"id": 18446744073709551615, "v": "a"}, {"id": 2}]"#;
+        let rust = infer_json(sample).to_rust("sample");
+        assert!(rust.contains("    pub id: i128,\n"), "{rust}");
+        assert!(
+            rust.contains("    pub v: Option<serdeio::Value>,\n"),
+            "{rust}"
+        );
+        let records: Vec<Sample> =
+            crate::read_records_from_reader(sample.as_bytes(), DataFormat::Json).unwrap();
+        assert_eq!(records[2], Sample { id: 2, v: None });
+    }
+
+    #[test]
+    fn test_to_rust_escaped_fields() {
+        let schema = infer_json(r#"[{"self": 1, "self_": 2, "type": 3, "Type": 4}]"#);
+        assert_eq!(
+            schema.to_rust("record"),
+            "use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    #[serde(rename = \"self\")]
+    pub self_: i64,
+    #[serde(rename = \"self_\")]
+    pub self_2: i64,
+    pub r#type: i64,
+    #[serde(rename = \"Type\")]
+    pub type2: i64,
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_string_format() {
+        assert_eq!(string_format("2024-02-29"), Some("date"));
+        assert_eq!(string_format("2024-02-29T07:32:00Z"), Some("date-time"));
+        assert_eq!(
+            string_format("2024-02-29 07:32:00.5+09:00"),
+            Some("date-time")
+        );
+        assert_eq!(string_format("2024-02-29T07:32:00"), None);
+        assert_eq!(string_format("07:32:00Z"), Some("time"));
+        assert_eq!(string_format("07:32:00.5+09:00"), Some("time"));
+        assert_eq!(string_format("07:32:00"), None);
+        assert_eq!(string_format("2024-2-29"), None);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_csv_leading_zeros() {
+        let csv = "id,zip,price\n1,01234,1.50\n2,98765,2.25\n";
+        let schema = infer_schema_from_reader(csv.as_bytes(), DataFormat::Csv).unwrap();
+        let rust = schema.to_rust("Order");
+        assert!(rust.contains("    pub id: i64,\n"), "{rust}");
+        assert!(rust.contains("    pub zip: String,\n"), "{rust}");
+        assert!(rust.contains("    pub price: String,\n"), "{rust}");
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_csv_empty_cells() {
+        let csv = "id,email\n1,\n2,b@example.com\n";
+        let schema = infer_schema_from_reader(csv.as_bytes(), DataFormat::Csv).unwrap();
+        assert!(
+            schema
+                .to_rust("User")
+                .contains("    pub email: Option<String>,\n")
+        );
+    }
+}
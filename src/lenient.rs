@@ -0,0 +1,253 @@
+use crate::Error;
+
+/// Thresholds for the lenient multi-record read APIs.
+///
+/// With the default options every bad record is rejected and the read never
+/// fails because of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LenientOptions {
+    /// Fail once more than this many records have been rejected.
+    pub max_errors: Option<usize>,
+    /// Fail if, after reading everything, the rejected records make up more
+    /// than this fraction (between 0.0 and 1.0) of all records.
+    pub max_error_ratio: Option<f64>,
+}
+
+/// A record that could not be decoded by a lenient read.
+#[derive(Debug)]
+pub struct RejectedRecord {
+    /// Zero-based index of the record among all records, good or bad.
+    pub index: usize,
+    /// One-based line where the record starts, if the format has lines.
+    pub line: Option<u64>,
+    /// Raw text of the record.
+    pub raw: String,
+    /// Why the record was rejected.
+    pub error: Error,
+}
+
+/// Outcome of a lenient read: the decoded records and the rejected ones.
+#[derive(Debug)]
+pub struct LenientRecords<T> {
+    pub records: Vec<T>,
+    pub rejected: Vec<RejectedRecord>,
+}
+
+impl<T> LenientRecords<T> {
+    fn total(&self) -> usize {
+        self.records.len() + self.rejected.len()
+    }
+
+    fn too_many_rejected(mut self) -> Error {
+        let total = self.total();
+        let rejected = self.rejected.len();
+        let last = self.rejected.pop().expect("at least one rejected record");
+        Error::TooManyRejected {
+            rejected,
+            total,
+            source: Box::new(last.error),
+        }
+    }
+}
+
+/// Drains `records`, setting aside the ones that fail to decode.
+///
+/// Only errors that carry the raw text of their record are rejected: the
+/// backend attaches it when it has delimited the record on its own and can
+/// carry on with the next one. Any other error, such as a syntax error in
+/// the enclosing document or an I/O error, still fails the read.
+pub(crate) fn collect<T>(
+    records: impl Iterator<Item = Result<T, Error>>,
+    options: &LenientOptions,
+) -> Result<LenientRecords<T>, Error> {
+    let mut result = LenientRecords {
+        records: Vec::new(),
+        rejected: Vec::new(),
+    };
+    for record in records {
+        let error = match record {
+            Ok(record) => {
+                result.records.push(record);
+                continue;
+            }
+            Err(error) => error,
+        };
+        let rejected = error
+            .context()
+            .and_then(|c| Some((c.record()?, c.line(), c.raw()?.to_string())));
+        let Some((index, line, raw)) = rejected else {
+            return Err(error);
+        };
+        result.rejected.push(RejectedRecord {
+            index,
+            line,
+            raw,
+            error,
+        });
+        if options
+            .max_errors
+            .is_some_and(|max| result.rejected.len() > max)
+        {
+            return Err(result.too_many_rejected());
+        }
+    }
+    if let Some(ratio) = options.max_error_ratio
+        && !result.rejected.is_empty()
+        && result.rejected.len() as f64 > ratio * result.total() as f64
+    {
+        return Err(result.too_many_rejected());
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{DataFormat, read_records_lenient_from_reader};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Record {
+        name: String,
+        value: i32,
+    }
+
+    const JSONL: &str = "{\"name\": \"a\", \"value\": 1}\nnot json\n{\"name\": \"b\", \"value\": \"x\"}\n{\"name\": \"c\", \"value\": 3}\n";
+
+    #[test]
+    fn test_lenient_jsonl() {
+        let result: LenientRecords<Record> = read_records_lenient_from_reader(
+            Cursor::new(JSONL),
+            DataFormat::JsonLines,
+            &LenientOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result.records.len(), 2);
+        assert_eq!(result.records[1].name, "c");
+        let rejected: Vec<_> = result
+            .rejected
+            .iter()
+            .map(|r| (r.index, r.line, r.raw.as_str()))
+            .collect();
+        assert_eq!(
+            rejected,
+            vec![
+                (1, Some(2), "not json"),
+                (2, Some(3), "{\"name\": \"b\", \"value\": \"x\"}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lenient_thresholds() {
+        let options = LenientOptions {
+            max_errors: Some(1),
+            ..Default::default()
+        };
+        let result: Result<LenientRecords<Record>, Error> =
+            read_records_lenient_from_reader(Cursor::new(JSONL), DataFormat::JsonLines, &options);
+        assert!(matches!(
+            result,
+            Err(Error::TooManyRejected {
+                rejected: 2,
+                total: 3,
+                ..
+            })
+        ));
+
+        let options = LenientOptions {
+            max_error_ratio: Some(0.5),
+            ..Default::default()
+        };
+        let result: LenientRecords<Record> =
+            read_records_lenient_from_reader(Cursor::new(JSONL), DataFormat::JsonLines, &options)
+                .unwrap();
+        assert_eq!(result.rejected.len(), 2);
+
+        let options = LenientOptions {
+            max_error_ratio: Some(0.25),
+            ..Default::default()
+        };
+        let result: Result<LenientRecords<Record>, Error> =
+            read_records_lenient_from_reader(Cursor::new(JSONL), DataFormat::JsonLines, &options);
+        assert!(matches!(
+            result,
+            Err(Error::TooManyRejected {
+                rejected: 2,
+                total: 4,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_lenient_json_array() {
+        let data =
+            r#"[{"name": "a", "value": 1}, {"name": "b", "value": }, {"name": "c", "value": 3}]"#;
+        let result: LenientRecords<Record> = read_records_lenient_from_reader(
+            Cursor::new(data),
+            DataFormat::Json,
+            &LenientOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result.records.len(), 2);
+        assert_eq!(result.rejected[0].index, 1);
+        assert_eq!(result.rejected[0].raw, r#"{"name": "b", "value": }"#);
+
+        // A broken array cannot be resynchronized, so the read still fails.
+        let result: Result<LenientRecords<Record>, Error> = read_records_lenient_from_reader(
+            Cursor::new(r#"[{"name": "a", "value": 1} {"name": "b", "value": 2}]"#),
+            DataFormat::Json,
+            &LenientOptions::default(),
+        );
+        assert!(matches!(
+            result.as_ref().map_err(Error::kind),
+            Err(Error::Json(_))
+        ));
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_lenient_csv() {
+        let data = "name,value\na,1\nb,x\nc\nd,4\n";
+        let result: LenientRecords<Record> = read_records_lenient_from_reader(
+            Cursor::new(data),
+            DataFormat::Csv,
+            &LenientOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result.records.len(), 2);
+        assert_eq!(result.records[1].name, "d");
+        let rejected: Vec<_> = result
+            .rejected
+            .iter()
+            .map(|r| (r.index, r.line, r.raw.as_str()))
+            .collect();
+        assert_eq!(rejected, vec![(1, Some(3), "b,x"), (2, Some(4), "c")]);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_lenient_csv_with_options() {
+        let mut options = crate::ReadOptions::default();
+        options.csv.delimiter = b';';
+        let result: LenientRecords<Record> = crate::read_records_lenient_from_reader_with_options(
+            Cursor::new("name;value\na;1\nb;x\n"),
+            DataFormat::Csv,
+            &LenientOptions::default(),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            result.records,
+            vec![Record {
+                name: "a".to_string(),
+                value: 1,
+            }]
+        );
+        assert_eq!(result.rejected[0].raw, "b;x");
+    }
+}
@@ -2,18 +2,89 @@
 #![allow(clippy::module_name_repetitions)]
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "async")]
+pub(crate) mod async_io;
 pub(crate) mod backend;
+pub(crate) mod compression;
+pub(crate) mod convert;
+pub(crate) mod detect;
 pub(crate) mod error;
+pub(crate) mod infer;
+pub(crate) mod lenient;
+pub(crate) mod options;
+pub(crate) mod pointer;
 pub(crate) mod read;
+#[cfg(feature = "schema")]
+pub(crate) mod schema;
 pub(crate) mod types;
+pub(crate) mod value;
 pub(crate) mod write;
 
-pub use error::Error;
+#[cfg(feature = "async")]
+pub use async_io::{
+    RecordStream, read_record_from_file_async, read_record_from_file_with_options_async,
+    read_record_from_reader_async, read_record_from_reader_with_options_async,
+    read_records_from_file_async, read_records_from_file_with_options_async,
+    read_records_from_reader_async, read_records_from_reader_with_options_async,
+    read_records_stream_from_file, read_records_stream_from_file_with_options,
+    read_records_stream_from_reader, read_records_stream_from_reader_with_options,
+    write_record_to_file_async, write_record_to_file_with_options_async,
+    write_record_to_writer_async, write_record_to_writer_with_options_async,
+    write_records_to_file_async, write_records_to_file_with_options_async,
+    write_records_to_writer_async, write_records_to_writer_with_options_async,
+};
+pub use convert::{
+    convert_file, convert_file_with_options, convert_reader, convert_reader_with_options,
+};
+pub use detect::{detect_compression, detect_format};
+pub use error::{Error, ErrorContext};
+pub use infer::{
+    InferredSchema, infer_schema_from_file, infer_schema_from_file_with_options,
+    infer_schema_from_reader, infer_schema_from_reader_with_options,
+};
+pub use lenient::{LenientOptions, LenientRecords, RejectedRecord};
+#[cfg(feature = "yaml")]
+pub use options::YamlWriteOptions;
+#[cfg(feature = "csv")]
+pub use options::{CsvQuoteStyle, CsvReadOptions, CsvTerminator, CsvTrim, CsvWriteOptions};
+pub use options::{JsonWriteOptions, ReadOptions, WriteOptions};
+#[cfg(feature = "messagepack")]
+pub use options::{MessagePackReadOptions, MessagePackWriteOptions};
+#[cfg(feature = "toml")]
+pub use options::{TomlReadOptions, TomlWriteOptions};
 pub use read::{
-    read_record_from_file, read_record_from_reader, read_records_from_file,
-    read_records_from_reader,
+    RecordIter, read_record_at_pointer_from_file, read_record_at_pointer_from_file_with_options,
+    read_record_at_pointer_from_reader, read_record_at_pointer_from_reader_with_options,
+    read_record_from_file, read_record_from_file_with_options, read_record_from_reader,
+    read_record_from_reader_with_options, read_records_at_pointer_from_file,
+    read_records_at_pointer_from_file_with_options, read_records_at_pointer_from_reader,
+    read_records_at_pointer_from_reader_with_options, read_records_from_file,
+    read_records_from_file_with_options, read_records_from_reader,
+    read_records_from_reader_with_options, read_records_iter_from_file,
+    read_records_iter_from_file_with_options, read_records_iter_from_reader,
+    read_records_iter_from_reader_with_options, read_records_lenient_from_file,
+    read_records_lenient_from_file_with_options, read_records_lenient_from_reader,
+    read_records_lenient_from_reader_with_options, read_value_from_file,
+    read_value_from_file_with_options, read_value_from_reader, read_value_from_reader_with_options,
+};
+#[cfg(feature = "schema")]
+pub use schema::{
+    Schema, read_record_from_file_with_schema, read_record_from_reader_with_schema,
+    read_records_from_file_with_options_and_schema, read_records_from_file_with_schema,
+    read_records_from_reader_with_options_and_schema, read_records_from_reader_with_schema,
+    validate_record_from_file, validate_record_from_file_with_options, validate_record_from_reader,
+    validate_record_from_reader_with_options, validate_records_from_file,
+    validate_records_from_file_with_options, validate_records_from_reader,
+    validate_records_from_reader_with_options,
 };
-pub use types::DataFormat;
+pub use types::{Compression, DataFormat, DataFormatError};
+pub use value::{Map, Value};
 pub use write::{
-    write_record_to_file, write_record_to_writer, write_records_to_file, write_records_to_writer,
+    RecordWriter, write_record_to_file, write_record_to_file_with_options, write_record_to_writer,
+    write_record_to_writer_with_options, write_records_at_pointer_to_file,
+    write_records_at_pointer_to_file_with_options, write_records_at_pointer_to_writer,
+    write_records_at_pointer_to_writer_with_options, write_records_to_file,
+    write_records_to_file_with_options, write_records_to_writer,
+    write_records_to_writer_with_options, write_value_to_file, write_value_to_file_with_options,
+    write_value_to_writer, write_value_to_writer_with_options,
 };
@@ -0,0 +1,613 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serdeio::{
+    Compression, CsvQuoteStyle, CsvTerminator, CsvTrim, DataFormat, DataFormatError,
+    JsonWriteOptions, ReadOptions, Schema, WriteOptions, convert_file_with_options,
+    convert_reader_with_options, infer_schema_from_file_with_options,
+    infer_schema_from_reader_with_options, validate_record_from_file_with_options,
+    validate_record_from_reader_with_options, validate_records_from_file_with_options,
+    validate_records_from_reader_with_options, write_value_to_writer_with_options,
+};
+
+/// Converts data between JSON, JSON Lines, CSV, TSV, YAML, MessagePack and TOML.
+#[derive(Parser)]
+#[command(name = "serdeio", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Convert a file from one data format to another.
+    ///
+    /// Formats are inferred from the file extensions, and from the content of
+    /// the input when its extension is unknown or it is read from stdin.
+    Convert(ConvertArgs),
+    /// Check a file for syntax errors and, optionally, against a JSON Schema.
+    ///
+    /// Every problem is reported on its own line, with the record index, line
+    /// and field path where known. The exit status is non-zero if any problem
+    /// is found.
+    Validate(ValidateArgs),
+    /// Infer the schema of the records in a file.
+    ///
+    /// Prints a JSON Schema for one record, or with `--rust` a Rust struct
+    /// definition to read the records into.
+    Infer(InferArgs),
+}
+
+#[derive(Args)]
+struct ConvertArgs {
+    /// Input file, or `-` for stdin.
+    input: PathBuf,
+    /// Output file, or `-` for stdout.
+    output: PathBuf,
+    /// Input format, such as `json`, `jsonl`, `csv`, `tsv`, `yaml`, `msgpack`
+    /// or `toml`.
+    #[arg(long, value_parser = parse_format)]
+    from: Option<DataFormat>,
+    /// Output format; required when writing to stdout.
+    #[arg(long, value_parser = parse_format)]
+    to: Option<DataFormat>,
+    #[command(flatten)]
+    read: ReadArgs,
+    #[command(flatten)]
+    write: WriteArgs,
+}
+
+#[derive(Args)]
+struct ValidateArgs {
+    /// Input file, or `-` for stdin.
+    input: PathBuf,
+    /// Input format, such as `json`, `jsonl`, `csv`, `tsv`, `yaml`, `msgpack`
+    /// or `toml`.
+    #[arg(long, value_parser = parse_format)]
+    from: Option<DataFormat>,
+    /// JSON Schema to check the records against, in any supported format.
+    #[arg(long, value_name = "FILE")]
+    schema: Option<PathBuf>,
+    /// Check each element of a JSON array, YAML stream or TOML array of
+    /// tables as a record, instead of the whole document. JSON Lines, CSV and
+    /// TSV are always checked record by record.
+    #[arg(long)]
+    records: bool,
+    #[command(flatten)]
+    read: ReadArgs,
+}
+
+#[derive(Args)]
+struct InferArgs {
+    /// Input file, or `-` for stdin.
+    input: PathBuf,
+    /// Input format, such as `json`, `jsonl`, `csv`, `tsv`, `yaml`, `msgpack`
+    /// or `toml`.
+    #[arg(long, value_parser = parse_format)]
+    from: Option<DataFormat>,
+    /// Print a Rust struct definition with this name instead of a JSON Schema.
+    #[arg(long, value_name = "NAME")]
+    rust: Option<String>,
+    #[command(flatten)]
+    read: ReadArgs,
+}
+
+#[derive(Args)]
+#[command(next_help_heading = "Input options")]
+struct ReadArgs {
+    /// CSV/TSV field delimiter, such as `;` or `tab`.
+    #[arg(long, value_name = "CHAR", value_parser = parse_byte)]
+    in_delimiter: Option<u8>,
+    /// CSV quote character.
+    #[arg(long, value_name = "CHAR", value_parser = parse_byte)]
+    in_quote: Option<u8>,
+    /// Treat CSV quote characters as ordinary data.
+    #[arg(long)]
+    in_no_quoting: bool,
+    /// CSV escape character for quotes inside quoted fields, such as `\`.
+    #[arg(long, value_name = "CHAR", value_parser = parse_byte)]
+    in_escape: Option<u8>,
+    /// Do not read two consecutive CSV quotes as one quote.
+    #[arg(long)]
+    in_no_double_quote: bool,
+    /// The CSV/TSV input has no header row.
+    #[arg(long)]
+    in_no_headers: bool,
+    /// Skip CSV/TSV lines starting with this character, such as `#`.
+    #[arg(long, value_name = "CHAR", value_parser = parse_byte)]
+    in_comment: Option<u8>,
+    /// Accept CSV/TSV rows with varying numbers of fields.
+    #[arg(long)]
+    in_flexible: bool,
+    /// Strip whitespace around CSV/TSV headers, fields, or both.
+    #[arg(long, value_name = "WHAT")]
+    in_trim: Option<Trim>,
+    /// CSV/TSV record terminator: `crlf` (any line break), `lf`, `cr` or a
+    /// single character.
+    #[arg(long, value_name = "TERMINATOR", value_parser = parse_terminator)]
+    in_terminator: Option<CsvTerminator>,
+    /// Rebuild nested records from dotted CSV/TSV column names such as
+    /// `address.city`. Without it, dotted names are kept as flat keys.
+    #[arg(long)]
+    in_unflatten: bool,
+    /// Key of the TOML array of tables holding the records.
+    #[arg(long, value_name = "KEY")]
+    in_toml_key: Option<String>,
+}
+
+#[derive(Args)]
+#[command(next_help_heading = "Output options")]
+struct WriteArgs {
+    /// Pretty-print JSON, and TOML arrays.
+    #[arg(long)]
+    pretty: bool,
+    /// Indent characters per level of pretty JSON.
+    #[arg(long, value_name = "N")]
+    indent_width: Option<usize>,
+    /// Indent character of pretty JSON, such as `tab`.
+    #[arg(long, value_name = "CHAR", value_parser = parse_char)]
+    indent_char: Option<char>,
+    /// CSV/TSV field delimiter, such as `;` or `tab`.
+    #[arg(long, value_name = "CHAR", value_parser = parse_byte)]
+    out_delimiter: Option<u8>,
+    /// CSV quote character.
+    #[arg(long, value_name = "CHAR", value_parser = parse_byte)]
+    out_quote: Option<u8>,
+    /// Which CSV fields get quoted.
+    #[arg(long, value_name = "STYLE")]
+    out_quote_style: Option<QuoteStyle>,
+    /// Escape CSV quotes with `--out-escape` instead of doubling them.
+    #[arg(long)]
+    out_no_double_quote: bool,
+    /// CSV escape character used with `--out-no-double-quote`.
+    #[arg(long, value_name = "CHAR", value_parser = parse_byte)]
+    out_escape: Option<u8>,
+    /// Do not write a CSV/TSV header row.
+    #[arg(long)]
+    out_no_headers: bool,
+    /// Accept records with varying numbers of fields in CSV/TSV output.
+    #[arg(long)]
+    out_flexible: bool,
+    /// Write CSV/TSV under the columns of every record rather than those of
+    /// the first one, holding the records in memory until the end.
+    #[arg(long)]
+    out_union_columns: bool,
+    /// CSV/TSV record terminator: `crlf`, `lf`, `cr` or a single character.
+    #[arg(long, value_name = "TERMINATOR", value_parser = parse_terminator)]
+    out_terminator: Option<CsvTerminator>,
+    /// Write nested records in dotted CSV/TSV columns such as `address.city`.
+    /// Without it, a record with a nested value is an error.
+    #[arg(long)]
+    out_flatten: bool,
+    /// Write YAML records as a stream of documents instead of a sequence.
+    #[arg(long)]
+    yaml_documents: bool,
+    /// Spaces per level of YAML mappings.
+    #[arg(long, value_name = "N")]
+    yaml_indent: Option<usize>,
+    /// Write each YAML record on one line in flow style.
+    #[arg(long)]
+    yaml_flow: bool,
+    /// Key of the TOML array of tables to put the records under.
+    #[arg(long, value_name = "KEY")]
+    out_toml_key: Option<String>,
+    /// Encode MessagePack structs as maps keyed by field name instead of
+    /// arrays of field values.
+    #[arg(long)]
+    msgpack_named_fields: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Trim {
+    Headers,
+    Fields,
+    All,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum QuoteStyle {
+    Always,
+    Necessary,
+    NonNumeric,
+    Never,
+}
+
+fn parse_format(value: &str) -> Result<DataFormat, DataFormatError> {
+    DataFormat::try_from(value)
+}
+
+fn parse_char(value: &str) -> Result<char, String> {
+    match value {
+        "tab" | "\\t" => Ok('\t'),
+        "space" => Ok(' '),
+        _ => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err("expected a single character".to_string()),
+            }
+        }
+    }
+}
+
+fn parse_byte(value: &str) -> Result<u8, String> {
+    let c = parse_char(value)?;
+    u8::try_from(c)
+        .ok()
+        .filter(u8::is_ascii)
+        .ok_or_else(|| "expected an ASCII character".to_string())
+}
+
+fn parse_terminator(value: &str) -> Result<CsvTerminator, String> {
+    match value {
+        "crlf" => Ok(CsvTerminator::Crlf),
+        "lf" => Ok(CsvTerminator::Any(b'\n')),
+        "cr" => Ok(CsvTerminator::Any(b'\r')),
+        _ => parse_byte(value).map(CsvTerminator::Any),
+    }
+}
+
+impl ReadArgs {
+    fn options(&self) -> ReadOptions {
+        let mut options = ReadOptions::default();
+        let csv = &mut options.csv;
+        csv.delimiter = self.in_delimiter.unwrap_or(csv.delimiter);
+        csv.quote = self.in_quote.unwrap_or(csv.quote);
+        csv.quoting &= !self.in_no_quoting;
+        csv.escape = self.in_escape.or(csv.escape);
+        csv.double_quote &= !self.in_no_double_quote;
+        csv.has_headers &= !self.in_no_headers;
+        csv.comment = self.in_comment.or(csv.comment);
+        csv.flexible |= self.in_flexible;
+        csv.trim = match self.in_trim {
+            Some(Trim::Headers) => CsvTrim::Headers,
+            Some(Trim::Fields) => CsvTrim::Fields,
+            Some(Trim::All) => CsvTrim::All,
+            None => csv.trim,
+        };
+        csv.terminator = self.in_terminator.unwrap_or(csv.terminator);
+        csv.flatten |= self.in_unflatten;
+        if let Some(key) = &self.in_toml_key {
+            options.toml.key.clone_from(key);
+        }
+        options
+    }
+}
+
+impl WriteArgs {
+    fn options(&self) -> WriteOptions {
+        let mut options = WriteOptions::default();
+        let json = &mut options.json;
+        json.pretty |= self.pretty;
+        json.indent_width = self.indent_width.unwrap_or(json.indent_width);
+        json.indent_char = self.indent_char.unwrap_or(json.indent_char);
+        let csv = &mut options.csv;
+        csv.delimiter = self.out_delimiter.unwrap_or(csv.delimiter);
+        csv.quote = self.out_quote.unwrap_or(csv.quote);
+        csv.quote_style = match self.out_quote_style {
+            Some(QuoteStyle::Always) => CsvQuoteStyle::Always,
+            Some(QuoteStyle::Necessary) => CsvQuoteStyle::Necessary,
+            Some(QuoteStyle::NonNumeric) => CsvQuoteStyle::NonNumeric,
+            Some(QuoteStyle::Never) => CsvQuoteStyle::Never,
+            None => csv.quote_style,
+        };
+        csv.double_quote &= !self.out_no_double_quote;
+        csv.escape = self.out_escape.unwrap_or(csv.escape);
+        csv.has_headers &= !self.out_no_headers;
+        csv.flexible |= self.out_flexible;
+        csv.union_columns |= self.out_union_columns;
+        csv.terminator = self.out_terminator.unwrap_or(csv.terminator);
+        csv.flatten |= self.out_flatten;
+        let yaml = &mut options.yaml;
+        yaml.documents |= self.yaml_documents;
+        yaml.indent = self.yaml_indent.unwrap_or(yaml.indent);
+        yaml.flow |= self.yaml_flow;
+        options.toml.pretty |= self.pretty;
+        if let Some(key) = &self.out_toml_key {
+            options.toml.key.clone_from(key);
+        }
+        options.messagepack.named_fields |= self.msgpack_named_fields;
+        options
+    }
+}
+
+fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+/// Runs `convert`, writing to `stdout` when the output is `-`.
+fn convert(args: &ConvertArgs, stdout: impl Write) -> Result<(), Box<dyn std::error::Error>> {
+    let read_options = args.read.options();
+    let write_options = args.write.options();
+    let from = args.from.unwrap_or(DataFormat::Auto);
+    if !is_stdio(&args.input) && !is_stdio(&args.output) {
+        let to = args.to.unwrap_or(DataFormat::Auto);
+        convert_file_with_options(
+            &args.input,
+            from,
+            &args.output,
+            to,
+            &read_options,
+            &write_options,
+        )?;
+        return Ok(());
+    }
+
+    let to = match args.to {
+        Some(to) => to,
+        None if is_stdio(&args.output) => {
+            return Err("--to is required when writing to stdout".into());
+        }
+        None => DataFormat::try_from(args.output.as_path())?,
+    };
+    if !is_stdio(&args.output) && Compression::from_path(&args.output).is_some() {
+        return Err("compressed output requires the input to be a file".into());
+    }
+    // A compressed input file is left to content detection, which also
+    // detects its compression.
+    let from = match args.from {
+        None if !is_stdio(&args.input) && Compression::from_path(&args.input).is_none() => {
+            DataFormat::try_from(args.input.as_path()).unwrap_or(DataFormat::Auto)
+        }
+        _ => from,
+    };
+    let reader: Box<dyn Read> = if is_stdio(&args.input) {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(BufReader::new(File::open(&args.input)?))
+    };
+    let writer: Box<dyn Write + '_> = if is_stdio(&args.output) {
+        Box::new(stdout)
+    } else {
+        Box::new(File::create(&args.output)?)
+    };
+    let mut writer = BufWriter::new(writer);
+    convert_reader_with_options(reader, from, &mut writer, to, &read_options, &write_options)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn validate(args: &ValidateArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = args.schema.as_ref().map(Schema::from_file).transpose()?;
+    let schema = schema.as_ref();
+    let options = args.read.options();
+    let from = args.from.unwrap_or(DataFormat::Auto);
+    let problems = match (is_stdio(&args.input), args.records) {
+        (true, true) => {
+            validate_records_from_reader_with_options(io::stdin().lock(), from, schema, &options)?
+        }
+        (true, false) => {
+            validate_record_from_reader_with_options(io::stdin().lock(), from, schema, &options)?
+        }
+        (false, true) => {
+            validate_records_from_file_with_options(&args.input, from, schema, &options)?
+        }
+        (false, false) => {
+            validate_record_from_file_with_options(&args.input, from, schema, &options)?
+        }
+    };
+    if problems.is_empty() {
+        return Ok(());
+    }
+    let mut stdout = io::stdout().lock();
+    for problem in &problems {
+        writeln!(stdout, "{problem}")?;
+    }
+    Err(format!("found {} problem(s)", problems.len()).into())
+}
+
+fn infer(args: &InferArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let options = args.read.options();
+    let from = args.from.unwrap_or(DataFormat::Auto);
+    let schema = if is_stdio(&args.input) {
+        infer_schema_from_reader_with_options(io::stdin().lock(), from, &options)?
+    } else {
+        infer_schema_from_file_with_options(&args.input, from, &options)?
+    };
+    let mut stdout = io::stdout().lock();
+    match &args.rust {
+        Some(name) => write!(stdout, "{}", schema.to_rust(name))?,
+        None => {
+            let options = WriteOptions {
+                json: JsonWriteOptions {
+                    pretty: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let schema = schema.to_json_schema();
+            write_value_to_writer_with_options(&mut stdout, &schema, DataFormat::Json, &options)?;
+            writeln!(stdout)?;
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match &cli.command {
+        Command::Convert(args) => convert(args, io::stdout().lock()),
+        Command::Validate(args) => validate(args),
+        Command::Infer(args) => infer(args),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("serdeio: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_convert_options() {
+        let cli = Cli::try_parse_from([
+            "serdeio",
+            "convert",
+            "-",
+            "out.tsv",
+            "--from",
+            "jsonl",
+            "--in-delimiter",
+            ";",
+            "--in-trim",
+            "all",
+            "--in-toml-key",
+            "server",
+            "--pretty",
+            "--indent-char",
+            "tab",
+            "--out-quote-style",
+            "non-numeric",
+            "--out-terminator",
+            "crlf",
+            "--yaml-indent",
+            "4",
+            "--out-flatten",
+            "--msgpack-named-fields",
+        ])
+        .unwrap();
+        let Command::Convert(args) = cli.command else {
+            panic!("expected the convert subcommand");
+        };
+        assert_eq!(args.from, Some(DataFormat::JsonLines));
+        assert_eq!(args.to, None);
+
+        let read = args.read.options();
+        assert_eq!(read.csv.delimiter, b';');
+        assert_eq!(read.csv.trim, CsvTrim::All);
+        assert_eq!(read.toml.key, "server");
+        let write = args.write.options();
+        assert!(write.json.pretty && write.toml.pretty);
+        assert_eq!(write.json.indent_char, '\t');
+        assert_eq!(write.csv.quote_style, CsvQuoteStyle::NonNumeric);
+        assert_eq!(write.csv.terminator, CsvTerminator::Crlf);
+        assert_eq!(write.yaml.indent, 4);
+        assert!(write.csv.flatten && write.messagepack.named_fields);
+
+        let result = Cli::try_parse_from(["serdeio", "convert", "a", "b", "--to", "xml"]);
+        assert!(result.is_err());
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("serdeio_cli_{}_{name}", std::process::id()))
+    }
+
+    fn run_convert(args: &[&str]) {
+        let cli = Cli::try_parse_from(["serdeio", "convert"].iter().chain(args)).unwrap();
+        let Command::Convert(args) = cli.command else {
+            panic!("expected the convert subcommand");
+        };
+        convert(&args, io::sink()).unwrap();
+    }
+
+    #[test]
+    fn test_convert_in_unflatten() {
+        let input = temp_path("unflatten.csv");
+        let output = temp_path("unflatten.json");
+        std::fs::write(&input, "id,address.city\n1,Oslo\n").unwrap();
+        let (input_arg, output_arg) = (input.to_str().unwrap(), output.to_str().unwrap());
+
+        run_convert(&[input_arg, output_arg]);
+        let flat = std::fs::read_to_string(&output).unwrap();
+        run_convert(&[input_arg, output_arg, "--in-unflatten"]);
+        let nested = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&input).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        assert_eq!(flat, r#"[{"id":1,"address.city":"Oslo"}]"#);
+        assert_eq!(nested, r#"[{"id":1,"address":{"city":"Oslo"}}]"#);
+    }
+
+    #[test]
+    fn test_convert_out_flatten() {
+        let input = temp_path("flatten.json");
+        let output = temp_path("flatten.csv");
+        std::fs::write(&input, r#"[{"id":1,"address":{"city":"Oslo"}}]"#).unwrap();
+        let (input_arg, output_arg) = (input.to_str().unwrap(), output.to_str().unwrap());
+
+        let cli = Cli::try_parse_from(["serdeio", "convert", input_arg, output_arg]).unwrap();
+        let Command::Convert(args) = cli.command else {
+            panic!("expected the convert subcommand");
+        };
+        let nested = convert(&args, io::sink());
+        run_convert(&[input_arg, output_arg, "--out-flatten"]);
+        let flat = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&input).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        assert!(nested.is_err());
+        assert_eq!(flat, "id,address.city\n1,Oslo\n");
+    }
+
+    #[test]
+    fn test_convert_file_to_stdout_resolves_extension() {
+        // A single line of JSON Lines is detected as a JSON document, which
+        // holds no records.
+        let input = temp_path("stdout.jsonl");
+        std::fs::write(&input, "{\"id\": 1}\n").unwrap();
+        let cli = Cli::try_parse_from([
+            "serdeio",
+            "convert",
+            input.to_str().unwrap(),
+            "-",
+            "--to",
+            "csv",
+        ])
+        .unwrap();
+        let Command::Convert(args) = cli.command else {
+            panic!("expected the convert subcommand");
+        };
+        let mut stdout = Vec::new();
+        let result = convert(&args, &mut stdout);
+        std::fs::remove_file(&input).unwrap();
+        result.unwrap();
+        assert_eq!(String::from_utf8(stdout).unwrap(), "id\n1\n");
+    }
+
+    #[test]
+    fn test_validate_args() {
+        let cli = Cli::try_parse_from([
+            "serdeio",
+            "validate",
+            "users.csv",
+            "--schema",
+            "user.schema.yaml",
+            "--records",
+            "--in-delimiter",
+            "tab",
+        ])
+        .unwrap();
+        let Command::Validate(args) = cli.command else {
+            panic!("expected the validate subcommand");
+        };
+        assert_eq!(args.schema, Some(PathBuf::from("user.schema.yaml")));
+        assert!(args.records);
+        assert_eq!(args.read.options().csv.delimiter, b'\t');
+    }
+
+    #[test]
+    fn test_infer_args() {
+        let cli = Cli::try_parse_from(["serdeio", "infer", "-", "--from", "csv", "--rust", "User"])
+            .unwrap();
+        let Command::Infer(args) = cli.command else {
+            panic!("expected the infer subcommand");
+        };
+        assert_eq!(args.from, Some(DataFormat::Csv));
+        assert_eq!(args.rust.as_deref(), Some("User"));
+    }
+}
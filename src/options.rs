@@ -0,0 +1,331 @@
+/// Backend settings for the `*_with_options` read functions.
+///
+/// Each supported format has its own section, present when the format's
+/// feature is enabled. Formats without a section have no read settings yet.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "csv")] {
+/// use serdeio::ReadOptions;
+///
+/// let mut options = ReadOptions::default();
+/// options.csv.has_headers = false;
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReadOptions {
+    #[cfg(feature = "csv")]
+    pub csv: CsvReadOptions,
+    #[cfg(feature = "messagepack")]
+    pub messagepack: MessagePackReadOptions,
+    #[cfg(feature = "toml")]
+    pub toml: TomlReadOptions,
+}
+
+/// Backend settings for the `*_with_options` write functions and
+/// [`crate::RecordWriter::with_options`].
+///
+/// Each supported format has its own section, present when the format's
+/// feature is enabled. Formats without a section have no write settings yet.
+///
+/// # Examples
+///
+/// ```rust
+/// use serdeio::WriteOptions;
+///
+/// let mut options = WriteOptions::default();
+/// options.json.pretty = true;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WriteOptions {
+    pub json: JsonWriteOptions,
+    #[cfg(feature = "csv")]
+    pub csv: CsvWriteOptions,
+    #[cfg(feature = "yaml")]
+    pub yaml: YamlWriteOptions,
+    #[cfg(feature = "messagepack")]
+    pub messagepack: MessagePackWriteOptions,
+    #[cfg(feature = "toml")]
+    pub toml: TomlWriteOptions,
+}
+
+/// JSON write settings.
+///
+/// Output is compact by default. JSON Lines output is always compact, with
+/// one record per line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonWriteOptions {
+    /// Write each field and element on its own line, indented by
+    /// `indent_width` copies of `indent_char` per level.
+    pub pretty: bool,
+    /// Number of indent characters per level of pretty output.
+    pub indent_width: usize,
+    /// Indent character of pretty output, such as `' '` or `'\t'`.
+    pub indent_char: char,
+}
+
+impl Default for JsonWriteOptions {
+    fn default() -> Self {
+        Self {
+            pretty: false,
+            indent_width: 2,
+            indent_char: ' ',
+        }
+    }
+}
+
+impl JsonWriteOptions {
+    /// Returns the indentation of one level of pretty output.
+    pub(crate) fn indent(&self) -> String {
+        std::iter::repeat_n(self.indent_char, self.indent_width).collect()
+    }
+}
+
+/// CSV read settings.
+///
+/// The defaults read RFC 4180 files: comma-separated, `"`-quoted, with a
+/// header row. TSV reads use the same settings except for the delimiter and
+/// the quoting ones.
+#[cfg(feature = "csv")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvReadOptions {
+    /// Field delimiter, such as `b';'` for many European exports or `b'|'`.
+    pub delimiter: u8,
+    /// Quote character.
+    pub quote: u8,
+    /// Interpret quotes. When disabled, quote characters are ordinary data.
+    pub quoting: bool,
+    /// Escape character for quotes inside quoted fields, such as `b'\\'`.
+    /// When unset, quotes are escaped by doubling them.
+    pub escape: Option<u8>,
+    /// Treat two consecutive quotes inside a quoted field as one quote.
+    pub double_quote: bool,
+    /// Treat the first row as column headers naming the fields of each record.
+    /// Without headers, fields are matched to struct fields by position.
+    pub has_headers: bool,
+    /// Skip lines starting with this character, such as `b'#'`.
+    pub comment: Option<u8>,
+    /// Accept rows whose number of fields differs from the first row.
+    pub flexible: bool,
+    /// Strip leading and trailing whitespace.
+    pub trim: CsvTrim,
+    /// Record terminator.
+    pub terminator: CsvTerminator,
+    /// Rebuild nested records from dotted column names such as
+    /// `address.city` or `tags.0`, as written with
+    /// [`CsvWriteOptions::flatten`]. Requires a header row; reading without
+    /// one is an error.
+    pub flatten: bool,
+}
+
+#[cfg(feature = "csv")]
+impl Default for CsvReadOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+            quoting: true,
+            escape: None,
+            double_quote: true,
+            has_headers: true,
+            comment: None,
+            flexible: false,
+            trim: CsvTrim::None,
+            terminator: CsvTerminator::Crlf,
+            flatten: false,
+        }
+    }
+}
+
+/// CSV write settings.
+///
+/// The defaults write RFC 4180 files with a header row, except that records
+/// end with `\n`; use [`CsvTerminator::Crlf`] for `\r\n`. TSV writes use the
+/// same settings except for the delimiter and the quoting ones.
+#[cfg(feature = "csv")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvWriteOptions {
+    /// Field delimiter.
+    pub delimiter: u8,
+    /// Quote character.
+    pub quote: u8,
+    /// Which fields get quoted.
+    pub quote_style: CsvQuoteStyle,
+    /// Escape quotes inside quoted fields by doubling them. When disabled,
+    /// they are prefixed with `escape` instead.
+    pub double_quote: bool,
+    /// Escape character used when `double_quote` is disabled.
+    pub escape: u8,
+    /// Write a header row with the field names before the first record.
+    pub has_headers: bool,
+    /// Accept records whose number of fields differs from the first one.
+    pub flexible: bool,
+    /// Record terminator.
+    pub terminator: CsvTerminator,
+    /// Flatten nested structs, sequences and maps into one column per leaf
+    /// value, named by its dotted path such as `address.city` or `tags.0`.
+    /// Rows are written as they come, under the columns of the first record;
+    /// a later record with a non-empty cell in any other column, such as a
+    /// longer `Vec`, is an error unless `union_columns` is set.
+    pub flatten: bool,
+    /// With `flatten`, buffer every record until the writer finishes and
+    /// write them under the union of their columns, so that records may have
+    /// columns the first one lacks.
+    pub union_columns: bool,
+}
+
+#[cfg(feature = "csv")]
+impl Default for CsvWriteOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+            quote_style: CsvQuoteStyle::Necessary,
+            double_quote: true,
+            escape: b'\\',
+            has_headers: true,
+            flexible: false,
+            terminator: CsvTerminator::Any(b'\n'),
+            flatten: false,
+            union_columns: false,
+        }
+    }
+}
+
+/// Which parts of a CSV file have surrounding whitespace stripped.
+#[cfg(feature = "csv")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CsvTrim {
+    #[default]
+    None,
+    Headers,
+    Fields,
+    All,
+}
+
+/// Which fields are quoted when writing CSV.
+#[cfg(feature = "csv")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CsvQuoteStyle {
+    /// Every field.
+    Always,
+    /// Only fields containing a delimiter, quote or line break.
+    #[default]
+    Necessary,
+    /// Every field that does not look like a number.
+    NonNumeric,
+    /// No field, even if that produces invalid CSV.
+    Never,
+}
+
+/// How CSV records end.
+#[cfg(feature = "csv")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvTerminator {
+    /// `\r\n` when writing; any of `\r`, `\n` or `\r\n` when reading.
+    Crlf,
+    /// A single byte.
+    Any(u8),
+}
+
+/// YAML write settings.
+///
+/// Output is in block style with two-space indentation by default.
+#[cfg(feature = "yaml")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct YamlWriteOptions {
+    /// Write multiple records as a stream of documents, each starting with
+    /// `---`, instead of as the entries of a single sequence.
+    pub documents: bool,
+    /// Number of spaces per level of block mappings, at least one. Sequences
+    /// in mappings start at the column of their key, as `serde_yaml` writes
+    /// them.
+    pub indent: usize,
+    /// Write each record on one line in flow style, such as
+    /// `{x: 1, tags: [a, b]}`. Multiple records are still the entries of a
+    /// block sequence, or separate documents.
+    pub flow: bool,
+}
+
+#[cfg(feature = "yaml")]
+impl Default for YamlWriteOptions {
+    fn default() -> Self {
+        Self {
+            documents: false,
+            indent: 2,
+            flow: false,
+        }
+    }
+}
+
+/// Key of the array of tables holding multiple TOML records.
+#[cfg(feature = "toml")]
+const TOML_RECORDS_KEY: &str = "record";
+
+/// TOML read settings.
+#[cfg(feature = "toml")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlReadOptions {
+    /// Key of the array of tables that multi-record reads take the records
+    /// from, such as `record` for `[[record]]` tables. A non-empty document
+    /// without this key is an error.
+    pub key: String,
+}
+
+#[cfg(feature = "toml")]
+impl Default for TomlReadOptions {
+    fn default() -> Self {
+        Self {
+            key: TOML_RECORDS_KEY.to_string(),
+        }
+    }
+}
+
+/// TOML write settings.
+#[cfg(feature = "toml")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlWriteOptions {
+    /// Write arrays of two or more elements with one element per line, as
+    /// `toml::to_string_pretty` does.
+    pub pretty: bool,
+    /// Key of the array of tables that multi-record writes put the records
+    /// under, such as `record` for `[[record]]` tables.
+    pub key: String,
+}
+
+#[cfg(feature = "toml")]
+impl Default for TomlWriteOptions {
+    fn default() -> Self {
+        Self {
+            pretty: false,
+            key: TOML_RECORDS_KEY.to_string(),
+        }
+    }
+}
+
+/// MessagePack read settings.
+#[cfg(feature = "messagepack")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessagePackReadOptions {
+    /// Deepest nesting of arrays and maps to decode before failing, which
+    /// bounds the stack used by untrusted input. Values above 65535 are
+    /// capped to it.
+    pub max_depth: usize,
+}
+
+#[cfg(feature = "messagepack")]
+impl Default for MessagePackReadOptions {
+    fn default() -> Self {
+        Self { max_depth: 1024 }
+    }
+}
+
+/// MessagePack write settings.
+#[cfg(feature = "messagepack")]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MessagePackWriteOptions {
+    /// Encode structs as maps keyed by field name instead of compact arrays
+    /// of field values, so other MessagePack readers can see the names.
+    pub named_fields: bool,
+}
@@ -0,0 +1,139 @@
+use serde::de::DeserializeOwned;
+
+use crate::{
+    Error,
+    backend::field_path,
+    value::{Map, Value},
+};
+
+/// Deserializes the value `pointer` refers to within `document` as one record.
+pub(crate) fn record<T: DeserializeOwned>(document: Value, pointer: &str) -> Result<T, Error> {
+    field_path::deserialize(take(document, pointer)?).map_err(|e| e.attach(Error::from))
+}
+
+/// Deserializes the sequence `pointer` refers to within `document` as records,
+/// so a failing element is reported as the record at its index.
+pub(crate) fn records<T: DeserializeOwned>(
+    document: Value,
+    pointer: &str,
+) -> Result<Vec<T>, Error> {
+    field_path::deserialize(take(document, pointer)?).map_err(|e| e.attach_record(0, Error::from))
+}
+
+fn take(mut document: Value, pointer: &str) -> Result<Value, Error> {
+    let not_found = || Error::PointerNotFound(pointer.to_string());
+    let mut target = &mut document;
+    for token in tokens(pointer).ok_or_else(not_found)? {
+        target = match target {
+            Value::Object(members) => members.get_mut(&token),
+            Value::Array(elements) => parse_index(&token).and_then(|index| elements.get_mut(index)),
+            _ => None,
+        }
+        .ok_or_else(not_found)?;
+    }
+    Ok(std::mem::take(target))
+}
+
+/// Puts `value` at `pointer` within `document`, replacing what is there.
+///
+/// Missing object members along the way are created, and so is an object in
+/// place of a null. Array elements must exist, except that `-` appends a new
+/// one.
+pub(crate) fn place(document: &mut Value, pointer: &str, value: Value) -> Result<(), Error> {
+    let not_found = || Error::PointerNotFound(pointer.to_string());
+    let mut target = document;
+    for token in tokens(pointer).ok_or_else(not_found)? {
+        if target.is_null() {
+            *target = Value::Object(Map::new());
+        }
+        target = match target {
+            Value::Object(members) => members.entry(token).or_insert(Value::Null),
+            Value::Array(elements) => {
+                if token == "-" {
+                    elements.push(Value::Null);
+                    elements.last_mut().expect("an element was just pushed")
+                } else {
+                    parse_index(&token)
+                        .and_then(|index| elements.get_mut(index))
+                        .ok_or_else(not_found)?
+                }
+            }
+            _ => return Err(not_found()),
+        };
+    }
+    *target = value;
+    Ok(())
+}
+
+/// Splits `pointer` into its unescaped reference tokens, or returns `None` if
+/// it is neither empty nor starts with `/`.
+fn tokens(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+    let tokens = pointer.strip_prefix('/')?;
+    Some(
+        tokens
+            .split('/')
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+    )
+}
+
+/// Parses an array index token, which RFC 6901 spells without a sign or
+/// leading zeros.
+fn parse_index(token: &str) -> Option<usize> {
+    if token.starts_with('+') || (token.starts_with('0') && token.len() != 1) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_place() {
+        let mut document = Value::from(json!({"meta": {"count": 2}, "pages": [{}]}));
+        place(&mut document, "/data/items", json!([1, 2]).into()).unwrap();
+        place(&mut document, "/pages/0/a~1b", Value::Bool(true)).unwrap();
+        place(&mut document, "/pages/-", Value::String("next".to_string())).unwrap();
+        assert_eq!(
+            document,
+            Value::from(json!({
+                "meta": {"count": 2},
+                "pages": [{"a/b": true}, "next"],
+                "data": {"items": [1, 2]},
+            }))
+        );
+
+        for pointer in ["data", "/pages/2", "/pages/01", "/meta/count/x"] {
+            let error = place(&mut document, pointer, Value::Null).unwrap_err();
+            assert!(matches!(error, Error::PointerNotFound(p) if p == pointer));
+        }
+
+        place(&mut document, "", Value::Array(Vec::new())).unwrap();
+        assert_eq!(document, Value::Array(Vec::new()));
+    }
+
+    #[test]
+    fn test_records_error() {
+        #[allow(dead_code)]
+        #[derive(Debug, serde::Deserialize)]
+        struct Item {
+            id: u32,
+        }
+
+        let document = Value::from(json!({"data": {"items": [{"id": 1}, {"id": "two"}]}}));
+        let error = records::<Item>(document.clone(), "/data/items").unwrap_err();
+        let context = error.context().unwrap();
+        assert_eq!(context.record(), Some(1));
+        assert_eq!(context.field(), Some("id"));
+
+        let error = record::<Item>(document, "/data/item").unwrap_err();
+        assert!(matches!(error, Error::PointerNotFound(_)));
+    }
+}
@@ -6,14 +6,51 @@ use std::{
 
 use serde::de::DeserializeOwned;
 
+#[cfg(feature = "csv")]
+use crate::options::CsvReadOptions;
 use crate::{
-    Error, backend,
-    types::{DataFormat, resolve_format},
+    Error, backend, compression, detect,
+    lenient::{self, LenientOptions, LenientRecords},
+    options::ReadOptions,
+    pointer,
+    types::{Compression, DataFormat},
+    value::Value,
 };
 
-fn open_buf_reader(path: impl AsRef<Path>) -> Result<BufReader<File>, Error> {
-    let file = File::open(path.as_ref())?;
-    Ok(BufReader::new(file))
+/// Opens a file for reading, decompressing it when its extension names a
+/// compression codec, and resolves its data format.
+pub(crate) fn open_reader(
+    path: &Path,
+    data_format: DataFormat,
+) -> Result<(Box<dyn Read>, DataFormat), Error> {
+    let file = File::open(path)?;
+    let reader = compression::decoder(BufReader::new(file), Compression::from_path(path))?;
+    detect::resolve_or_sniff(path, data_format, reader)
+}
+
+/// Iterator over records decoded lazily from an underlying reader.
+///
+/// Returned by [`read_records_iter_from_reader`] and [`read_records_iter_from_file`].
+/// Each item is the result of decoding one record, so a malformed record
+/// surfaces as an `Err` item instead of aborting the construction of the iterator.
+pub struct RecordIter<'a, T> {
+    inner: Box<dyn Iterator<Item = Result<T, Error>> + 'a>,
+}
+
+impl<'a, T: 'a> RecordIter<'a, T> {
+    fn map_err(self, f: impl Fn(Error) -> Error + 'a) -> Self {
+        Self {
+            inner: Box::new(self.inner.map(move |result| result.map_err(&f))),
+        }
+    }
+}
+
+impl<T> Iterator for RecordIter<'_, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
 }
 
 /// Reads a single record from a reader and deserializes it into the specified type.
@@ -27,10 +64,13 @@ fn open_buf_reader(path: impl AsRef<Path>) -> Result<BufReader<File>, Error> {
 /// - MessagePack (requires `messagepack` feature)
 /// - TOML (requires `toml` feature)
 ///
+/// With `DataFormat::Auto` the format, and any compression, is detected from
+/// the content; see [`crate::detect_format`].
+///
 /// # Errors
 ///
 /// Returns an error if the data format is not supported for single records,
-/// or if deserialization fails.
+/// cannot be detected, or if deserialization fails.
 ///
 /// # Examples
 ///
@@ -55,18 +95,40 @@ pub fn read_record_from_reader<T: DeserializeOwned>(
     reader: impl Read,
     data_format: DataFormat,
 ) -> Result<T, Error> {
-    match data_format {
-        DataFormat::Auto => Err(Error::AutoNotSupported),
+    read_record_from_reader_with_options(reader, data_format, &ReadOptions::default())
+}
+
+/// Reads a single record from a reader with backend settings from `options`.
+///
+/// See [`read_record_from_reader`] for the supported formats. Of those, only
+/// MessagePack has read settings so far.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for single records,
+/// cannot be detected, or if deserialization fails.
+#[cfg_attr(not(feature = "messagepack"), allow(clippy::only_used_in_recursion))]
+pub fn read_record_from_reader_with_options<T: DeserializeOwned>(
+    reader: impl Read,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<T, Error> {
+    let result = match data_format {
+        DataFormat::Auto => {
+            let (reader, data_format) = detect::sniff_reader(reader)?;
+            return read_record_from_reader_with_options(reader, data_format, options);
+        }
         DataFormat::Json => backend::json::read(reader),
         #[cfg(feature = "yaml")]
         DataFormat::Yaml => backend::yaml::read(reader),
         #[cfg(feature = "messagepack")]
-        DataFormat::MessagePack => backend::messagepack::read(reader),
+        DataFormat::MessagePack => backend::messagepack::read(reader, &options.messagepack),
         #[cfg(feature = "toml")]
         DataFormat::Toml => backend::toml::read(reader),
         #[allow(unreachable_patterns)]
-        _ => Err(Error::UnsupportedFormat(data_format)),
-    }
+        _ => return Err(Error::UnsupportedFormat(data_format)),
+    };
+    result.map_err(|e| e.with_format(data_format))
 }
 
 /// Reads multiple records from a reader and deserializes them into a vector of the specified type.
@@ -78,13 +140,19 @@ pub fn read_record_from_reader<T: DeserializeOwned>(
 /// - JSON (always available, as an array)
 /// - JSON Lines (always available)
 /// - CSV (requires `csv` feature)
-/// - YAML (requires `yaml` feature, as an array)
+/// - TSV (requires `csv` feature)
+/// - YAML (requires `yaml` feature, as a sequence or a stream of documents)
 /// - MessagePack (requires `messagepack` feature, as an array)
+/// - TOML (requires `toml` feature, as the array of tables under `toml.key`,
+///   `[[record]]` by default)
+///
+/// With `DataFormat::Auto` the format, and any compression, is detected from
+/// the content; see [`crate::detect_format`].
 ///
 /// # Errors
 ///
 /// Returns an error if the data format is not supported for multiple records,
-/// or if deserialization fails.
+/// cannot be detected, or if deserialization fails.
 ///
 /// # Examples
 ///
@@ -105,35 +173,271 @@ pub fn read_record_from_reader<T: DeserializeOwned>(
 /// let users: Vec<User> = read_records_from_reader(reader, DataFormat::Json).unwrap();
 /// assert_eq!(users.len(), 2);
 /// ```
+pub fn read_records_from_reader<T: DeserializeOwned>(
+    reader: impl Read,
+    data_format: DataFormat,
+) -> Result<Vec<T>, Error> {
+    read_records_from_reader_with_options(reader, data_format, &ReadOptions::default())
+}
+
+/// Reads multiple records from a reader with backend settings from `options`.
 ///
-/// # Note
+/// See [`read_records_from_reader`] for the supported formats.
 ///
-/// TOML is not supported for multi-record operations. The TOML specification
-/// requires the root to be a table, so a bare array of records is not
-/// representable. Use the single-record TOML functions instead.
-pub fn read_records_from_reader<T: DeserializeOwned>(
+/// # Errors
+///
+/// Returns an error if the data format is not supported for multiple records,
+/// cannot be detected, or if deserialization fails.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "csv")] {
+/// use std::io::Cursor;
+///
+/// use serde::Deserialize;
+/// use serdeio::{read_records_from_reader_with_options, DataFormat, ReadOptions};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// let mut options = ReadOptions::default();
+/// options.csv.has_headers = false;
+/// let users: Vec<User> = read_records_from_reader_with_options(
+///     Cursor::new("Alice,30\nBob,25\n"),
+///     DataFormat::Csv,
+///     &options,
+/// )
+/// .unwrap();
+/// assert_eq!(users[1].name, "Bob");
+/// # }
+/// ```
+#[allow(clippy::only_used_in_recursion)]
+pub fn read_records_from_reader_with_options<T: DeserializeOwned>(
     reader: impl Read,
     data_format: DataFormat,
+    options: &ReadOptions,
 ) -> Result<Vec<T>, Error> {
-    match data_format {
-        DataFormat::Auto => Err(Error::AutoNotSupported),
-        DataFormat::Json => backend::json::read(reader),
+    let result = match data_format {
+        DataFormat::Auto => {
+            let (reader, data_format) = detect::sniff_reader(reader)?;
+            return read_records_from_reader_with_options(reader, data_format, options);
+        }
+        DataFormat::Json => backend::json::read_iter(reader).collect(),
         DataFormat::JsonLines => backend::jsonlines::read(reader),
         #[cfg(feature = "csv")]
-        DataFormat::Csv => backend::csv::read(reader),
+        DataFormat::Csv => backend::csv::read(reader, &options.csv),
+        #[cfg(feature = "csv")]
+        DataFormat::Tsv => backend::csv::read_tsv(reader, &options.csv),
         #[cfg(feature = "yaml")]
-        DataFormat::Yaml => backend::yaml::read(reader),
+        DataFormat::Yaml => backend::yaml::read_seq(reader),
         #[cfg(feature = "messagepack")]
-        DataFormat::MessagePack => backend::messagepack::read(reader),
+        DataFormat::MessagePack => {
+            backend::messagepack::read_iter(reader, &options.messagepack).collect()
+        }
+        #[cfg(feature = "toml")]
+        DataFormat::Toml => backend::toml::read_seq(reader, &options.toml),
         #[allow(unreachable_patterns)]
-        _ => Err(Error::UnsupportedFormat(data_format)),
-    }
+        _ => return Err(Error::UnsupportedFormat(data_format)),
+    };
+    result.map_err(|e| e.with_format(data_format))
+}
+
+/// Reads multiple records from a reader lazily, yielding them one at a time.
+///
+/// Unlike [`read_records_from_reader`], records are decoded on demand, so
+/// line- or row-oriented formats can be processed with bounded memory.
+///
+/// # Supported Formats
+///
+/// - JSON (always available, streamed element by element from an array)
+/// - JSON Lines (always available, streamed line by line)
+/// - CSV (requires `csv` feature, streamed row by row)
+/// - TSV (requires `csv` feature, streamed row by row)
+/// - YAML (requires `yaml` feature, streamed document by document, or entry
+///   by entry from a block sequence)
+/// - MessagePack (requires `messagepack` feature, as an array)
+/// - TOML (requires `toml` feature, as an array of tables)
+///
+/// MessagePack arrays, TOML documents and YAML flow sequences are decoded up
+/// front and then yielded record by record.
+///
+/// With `DataFormat::Auto` the format, and any compression, is detected from
+/// the content; see [`crate::detect_format`].
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for multiple records
+/// or cannot be detected.
+/// Decoding errors of individual records are yielded by the iterator.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::Cursor;
+///
+/// use serde::Deserialize;
+/// use serdeio::{read_records_iter_from_reader, DataFormat};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// let jsonl_data = "{\"name\": \"Alice\", \"age\": 30}\n{\"name\": \"Bob\", \"age\": 25}\n";
+/// let reader = Cursor::new(jsonl_data);
+/// for user in read_records_iter_from_reader::<User>(reader, DataFormat::JsonLines).unwrap() {
+///     let user = user.unwrap();
+///     println!("{} is {}", user.name, user.age);
+/// }
+/// ```
+pub fn read_records_iter_from_reader<'a, T: DeserializeOwned + 'a>(
+    reader: impl Read + 'a,
+    data_format: DataFormat,
+) -> Result<RecordIter<'a, T>, Error> {
+    read_records_iter_from_reader_with_options(reader, data_format, &ReadOptions::default())
+}
+
+/// Reads multiple records from a reader lazily with backend settings from `options`.
+///
+/// See [`read_records_iter_from_reader`] for the supported formats.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for multiple records
+/// or cannot be detected.
+/// Decoding errors of individual records are yielded by the iterator.
+#[allow(clippy::only_used_in_recursion)]
+pub fn read_records_iter_from_reader_with_options<'a, T: DeserializeOwned + 'a>(
+    reader: impl Read + 'a,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<RecordIter<'a, T>, Error> {
+    let inner: Box<dyn Iterator<Item = Result<T, Error>> + 'a> = match data_format {
+        DataFormat::Auto => {
+            let (reader, data_format) = detect::sniff_reader(reader)?;
+            return read_records_iter_from_reader_with_options(reader, data_format, options);
+        }
+        DataFormat::Json => Box::new(backend::json::read_iter(reader)),
+        DataFormat::JsonLines => Box::new(backend::jsonlines::read_iter(reader)),
+        #[cfg(feature = "csv")]
+        DataFormat::Csv => Box::new(backend::csv::read_iter(reader, &options.csv)),
+        #[cfg(feature = "csv")]
+        DataFormat::Tsv => Box::new(backend::csv::read_tsv_iter(reader, &options.csv)),
+        #[cfg(feature = "yaml")]
+        DataFormat::Yaml => Box::new(backend::yaml::read_iter(reader)),
+        #[cfg(feature = "messagepack")]
+        DataFormat::MessagePack => Box::new(backend::messagepack::read_iter(
+            reader,
+            &options.messagepack,
+        )),
+        #[cfg(feature = "toml")]
+        DataFormat::Toml => Box::new(backend::toml::read_iter(reader, &options.toml)),
+        #[allow(unreachable_patterns)]
+        _ => return Err(Error::UnsupportedFormat(data_format)),
+    };
+    Ok(RecordIter { inner }.map_err(move |e| e.with_format(data_format)))
+}
+
+/// Reads multiple records from a reader, setting aside the ones that fail to decode.
+///
+/// Instead of aborting at the first malformed record, the decoded records are
+/// returned together with a report of the rejected ones (index, line, raw text
+/// and error). `options` sets how many rejected records are tolerated.
+///
+/// # Supported Formats
+///
+/// - JSON (always available, as an array)
+/// - JSON Lines (always available)
+/// - CSV (requires `csv` feature)
+/// - TSV (requires `csv` feature)
+/// - YAML (requires `yaml` feature, as a sequence or a stream of documents)
+/// - MessagePack (requires `messagepack` feature, as an array)
+/// - TOML (requires `toml` feature, as an array of tables)
+///
+/// MessagePack arrays, TOML documents and YAML flow sequences are decoded as
+/// a whole, so a bad record there fails the read as in
+/// [`read_records_from_reader`]. The documents of a YAML stream, and the
+/// entries of a YAML block sequence, are rejected one at a time.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for multiple records
+/// or cannot be detected, if the input is malformed beyond a single record,
+/// or [`Error::TooManyRejected`] if a threshold in `options` is exceeded.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::Cursor;
+///
+/// use serde::Deserialize;
+/// use serdeio::{read_records_lenient_from_reader, DataFormat, LenientOptions};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// let jsonl_data = "{\"name\": \"Alice\", \"age\": 30}\nnot json\n";
+/// let options = LenientOptions { max_errors: Some(10), ..Default::default() };
+/// let result = read_records_lenient_from_reader::<User>(
+///     Cursor::new(jsonl_data),
+///     DataFormat::JsonLines,
+///     &options,
+/// )
+/// .unwrap();
+/// assert_eq!(result.records.len(), 1);
+/// assert_eq!(result.rejected[0].line, Some(2));
+/// ```
+pub fn read_records_lenient_from_reader<T: DeserializeOwned>(
+    reader: impl Read,
+    data_format: DataFormat,
+    options: &LenientOptions,
+) -> Result<LenientRecords<T>, Error> {
+    read_records_lenient_from_reader_with_options(
+        reader,
+        data_format,
+        options,
+        &ReadOptions::default(),
+    )
+}
+
+/// Reads multiple records from a reader, setting aside the ones that fail to
+/// decode, with backend settings from `options`.
+///
+/// See [`read_records_lenient_from_reader`] for which records can be rejected;
+/// `lenient` sets how many rejected records are tolerated.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for multiple records
+/// or cannot be detected, if the input is malformed beyond a single record,
+/// or [`Error::TooManyRejected`] if a threshold in `lenient` is exceeded.
+pub fn read_records_lenient_from_reader_with_options<T: DeserializeOwned>(
+    reader: impl Read,
+    data_format: DataFormat,
+    lenient: &LenientOptions,
+    options: &ReadOptions,
+) -> Result<LenientRecords<T>, Error> {
+    lenient::collect(
+        read_records_iter_from_reader_with_options(reader, data_format, options)?,
+        lenient,
+    )
 }
 
 /// Reads a single record from a file and deserializes it into the specified type.
 ///
 /// The data format is automatically inferred from the file extension.
 /// If `data_format` is set to a format other than `Auto`, that format is used as-is and the file extension is ignored.
+/// A trailing compression extension (`.gz`, `.zst`, `.bz2`, `.xz`) is decompressed
+/// transparently when the matching feature is enabled. When the extension is
+/// missing or unknown, the format is detected from the content instead.
 ///
 /// # Supported Formats
 ///
@@ -144,8 +448,8 @@ pub fn read_records_from_reader<T: DeserializeOwned>(
 ///
 /// # Errors
 ///
-/// Returns an error if the file cannot be opened, the extension is unknown,
-/// or deserialization fails.
+/// Returns an error if the file cannot be opened, the format can be determined
+/// neither from the extension nor from the content, or deserialization fails.
 ///
 /// # Examples
 ///
@@ -164,30 +468,53 @@ pub fn read_records_from_reader<T: DeserializeOwned>(
 pub fn read_record_from_file<T: DeserializeOwned>(
     path: impl AsRef<Path>,
     data_format: DataFormat,
+) -> Result<T, Error> {
+    read_record_from_file_with_options(path, data_format, &ReadOptions::default())
+}
+
+/// Reads a single record from a file with backend settings from `options`.
+///
+/// See [`read_record_from_file`] for how the format is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the format can be determined
+/// neither from the extension nor from the content, or deserialization fails.
+pub fn read_record_from_file_with_options<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    options: &ReadOptions,
 ) -> Result<T, Error> {
     let path = path.as_ref();
-    let final_format = resolve_format(path, data_format)?;
-    let rdr = open_buf_reader(path)?;
-    read_record_from_reader(rdr, final_format)
+    open_reader(path, data_format)
+        .and_then(|(rdr, final_format)| {
+            read_record_from_reader_with_options(rdr, final_format, options)
+        })
+        .map_err(|e| e.with_path(path))
 }
 
 /// Reads multiple records from a file and deserializes them into a vector of the specified type.
 ///
 /// The data format is automatically inferred from the file extension.
 /// If `data_format` is set to a format other than `Auto`, that format is used as-is and the file extension is ignored.
+/// A trailing compression extension (`.gz`, `.zst`, `.bz2`, `.xz`) is decompressed
+/// transparently when the matching feature is enabled. When the extension is
+/// missing or unknown, the format is detected from the content instead.
 ///
 /// # Supported Formats
 ///
 /// - JSON (.json)
 /// - JSON Lines (.jsonl, .jsl)
 /// - CSV (.csv) - requires `csv` feature
+/// - TSV (.tsv, .tab) - requires `csv` feature
 /// - YAML (.yaml, .yml) - requires `yaml` feature
 /// - MessagePack (.msgpack, .mpack, .mpk) - requires `messagepack` feature
+/// - TOML (.toml) - requires `toml` feature
 ///
 /// # Errors
 ///
-/// Returns an error if the file cannot be opened, the extension is unknown,
-/// or deserialization fails.
+/// Returns an error if the file cannot be opened, the format can be determined
+/// neither from the extension nor from the content, or deserialization fails.
 ///
 /// # Examples
 ///
@@ -207,10 +534,645 @@ pub fn read_records_from_file<T: DeserializeOwned>(
     path: impl AsRef<Path>,
     data_format: DataFormat,
 ) -> Result<Vec<T>, Error> {
+    read_records_from_file_with_options(path, data_format, &ReadOptions::default())
+}
+
+/// Reads multiple records from a file with backend settings from `options`.
+///
+/// See [`read_records_from_file`] for how the format is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the format can be determined
+/// neither from the extension nor from the content, or deserialization fails.
+pub fn read_records_from_file_with_options<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<Vec<T>, Error> {
+    let path = path.as_ref();
+    open_reader(path, data_format)
+        .and_then(|(rdr, final_format)| {
+            read_records_from_reader_with_options(rdr, final_format, options)
+        })
+        .map_err(|e| e.with_path(path))
+}
+
+/// Reads multiple records from a file lazily, yielding them one at a time.
+///
+/// The data format is automatically inferred from the file extension.
+/// If `data_format` is set to a format other than `Auto`, that format is used as-is and the file extension is ignored.
+/// A trailing compression extension (`.gz`, `.zst`, `.bz2`, `.xz`) is decompressed
+/// transparently when the matching feature is enabled. When the extension is
+/// missing or unknown, the format is detected from the content instead.
+///
+/// # Supported Formats
+///
+/// - JSON (.json)
+/// - JSON Lines (.jsonl, .jsl)
+/// - CSV (.csv) - requires `csv` feature
+/// - TSV (.tsv, .tab) - requires `csv` feature
+/// - YAML (.yaml, .yml) - requires `yaml` feature
+/// - MessagePack (.msgpack, .mpack, .mpk) - requires `messagepack` feature
+/// - TOML (.toml) - requires `toml` feature
+///
+/// See [`read_records_iter_from_reader`] for which formats are streamed.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, or the format can be
+/// determined neither from the extension nor from the content.
+/// Decoding errors of individual records are yielded by the iterator.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serde::Deserialize;
+/// use serdeio::{read_records_iter_from_file, DataFormat};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// let mut count = 0;
+/// for user in read_records_iter_from_file::<User>("users.jsonl", DataFormat::Auto).unwrap() {
+///     let _user = user.unwrap();
+///     count += 1;
+/// }
+/// ```
+pub fn read_records_iter_from_file<T: DeserializeOwned + 'static>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+) -> Result<RecordIter<'static, T>, Error> {
+    read_records_iter_from_file_with_options(path, data_format, &ReadOptions::default())
+}
+
+/// Reads multiple records from a file lazily with backend settings from `options`.
+///
+/// See [`read_records_iter_from_file`] for how the format is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, or the format can be
+/// determined neither from the extension nor from the content.
+/// Decoding errors of individual records are yielded by the iterator.
+pub fn read_records_iter_from_file_with_options<T: DeserializeOwned + 'static>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<RecordIter<'static, T>, Error> {
     let path = path.as_ref();
-    let final_format = resolve_format(path, data_format)?;
-    let rdr = open_buf_reader(path)?;
-    read_records_from_reader(rdr, final_format)
+    let records = open_reader(path, data_format)
+        .and_then(|(rdr, final_format)| {
+            read_records_iter_from_reader_with_options(rdr, final_format, options)
+        })
+        .map_err(|e| e.with_path(path))?;
+    let path = path.to_path_buf();
+    Ok(records.map_err(move |e| e.with_path(path.clone())))
+}
+
+/// Reads multiple records from a file, setting aside the ones that fail to decode.
+///
+/// The data format is automatically inferred from the file extension.
+/// If `data_format` is set to a format other than `Auto`, that format is used as-is and the file extension is ignored.
+/// A trailing compression extension (`.gz`, `.zst`, `.bz2`, `.xz`) is decompressed
+/// transparently when the matching feature is enabled. When the extension is
+/// missing or unknown, the format is detected from the content instead.
+///
+/// See [`read_records_lenient_from_reader`] for which records can be rejected.
+///
+/// # Supported Formats
+///
+/// - JSON (.json)
+/// - JSON Lines (.jsonl, .jsl)
+/// - CSV (.csv) - requires `csv` feature
+/// - TSV (.tsv, .tab) - requires `csv` feature
+/// - YAML (.yaml, .yml) - requires `yaml` feature
+/// - MessagePack (.msgpack, .mpack, .mpk) - requires `messagepack` feature
+/// - TOML (.toml) - requires `toml` feature
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the format can be determined
+/// neither from the extension nor from the content, the input is malformed
+/// beyond a single record, or a threshold in `options` is exceeded.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serde::Deserialize;
+/// use serdeio::{read_records_lenient_from_file, DataFormat, LenientOptions};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// let options = LenientOptions { max_error_ratio: Some(0.01), ..Default::default() };
+/// let result =
+///     read_records_lenient_from_file::<User>("users.csv", DataFormat::Auto, &options).unwrap();
+/// for rejected in &result.rejected {
+///     eprintln!("skipped record {}: {}", rejected.index, rejected.error);
+/// }
+/// ```
+pub fn read_records_lenient_from_file<T: DeserializeOwned + 'static>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    options: &LenientOptions,
+) -> Result<LenientRecords<T>, Error> {
+    read_records_lenient_from_file_with_options(path, data_format, options, &ReadOptions::default())
+}
+
+/// Reads multiple records from a file, setting aside the ones that fail to
+/// decode, with backend settings from `options`.
+///
+/// See [`read_records_lenient_from_file`] for how the format is resolved, and
+/// [`read_records_lenient_from_reader`] for which records can be rejected.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the format can be determined
+/// neither from the extension nor from the content, the input is malformed
+/// beyond a single record, or a threshold in `lenient` is exceeded.
+pub fn read_records_lenient_from_file_with_options<T: DeserializeOwned + 'static>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    lenient: &LenientOptions,
+    options: &ReadOptions,
+) -> Result<LenientRecords<T>, Error> {
+    lenient::collect(
+        read_records_iter_from_file_with_options(path, data_format, options)?,
+        lenient,
+    )
+}
+
+/// Reads a single record nested inside a document from a reader.
+///
+/// The whole document is decoded, as with [`read_record_from_reader`], and
+/// only the value `pointer` refers to is deserialized into the specified type.
+/// `pointer` is a JSON Pointer (RFC 6901) such as `/data/user`; the empty
+/// pointer refers to the whole document.
+///
+/// # Supported Formats
+///
+/// - JSON (always available)
+/// - YAML (requires `yaml` feature)
+/// - MessagePack (requires `messagepack` feature)
+/// - TOML (requires `toml` feature)
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for single records or
+/// cannot be detected, if decoding the document fails,
+/// [`Error::PointerNotFound`] if `pointer` refers to nothing, or an error if
+/// deserializing the value fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::Cursor;
+///
+/// use serde::Deserialize;
+/// use serdeio::{read_record_at_pointer_from_reader, DataFormat};
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// let json_data = r#"{"data": {"user": {"name": "Alice", "age": 30}}}"#;
+/// let user: User =
+///     read_record_at_pointer_from_reader(Cursor::new(json_data), DataFormat::Json, "/data/user")
+///         .unwrap();
+/// assert_eq!(user.name, "Alice");
+/// ```
+pub fn read_record_at_pointer_from_reader<T: DeserializeOwned>(
+    reader: impl Read,
+    data_format: DataFormat,
+    pointer: &str,
+) -> Result<T, Error> {
+    read_record_at_pointer_from_reader_with_options(
+        reader,
+        data_format,
+        pointer,
+        &ReadOptions::default(),
+    )
+}
+
+/// Reads a single record nested inside a document from a reader with backend
+/// settings from `options`.
+///
+/// See [`read_record_at_pointer_from_reader`] for how `pointer` is used.
+///
+/// # Errors
+///
+/// Returns an error as [`read_record_at_pointer_from_reader`] does.
+pub fn read_record_at_pointer_from_reader_with_options<T: DeserializeOwned>(
+    reader: impl Read,
+    data_format: DataFormat,
+    pointer: &str,
+    options: &ReadOptions,
+) -> Result<T, Error> {
+    let document = read_record_from_reader_with_options(reader, data_format, options)?;
+    pointer::record(document, pointer)
+}
+
+/// Reads multiple records nested inside a document from a reader.
+///
+/// The whole document is decoded, as with [`read_record_from_reader`], and
+/// the sequence `pointer` refers to is deserialized into a vector of the
+/// specified type. `pointer` is a JSON Pointer (RFC 6901) such as
+/// `/data/items`.
+///
+/// # Supported Formats
+///
+/// - JSON (always available)
+/// - YAML (requires `yaml` feature)
+/// - MessagePack (requires `messagepack` feature)
+/// - TOML (requires `toml` feature)
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for single records or
+/// cannot be detected, if decoding the document fails,
+/// [`Error::PointerNotFound`] if `pointer` refers to nothing, or an error if
+/// the value is not a sequence or deserializing a record fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::Cursor;
+///
+/// use serde::Deserialize;
+/// use serdeio::{read_records_at_pointer_from_reader, DataFormat};
+///
+/// #[derive(Deserialize)]
+/// struct Item {
+///     id: u32,
+/// }
+///
+/// let json_data = r#"{"data": {"items": [{"id": 1}, {"id": 2}]}, "meta": {"page": 1}}"#;
+/// let items: Vec<Item> =
+///     read_records_at_pointer_from_reader(Cursor::new(json_data), DataFormat::Json, "/data/items")
+///         .unwrap();
+/// assert_eq!(items.len(), 2);
+/// ```
+pub fn read_records_at_pointer_from_reader<T: DeserializeOwned>(
+    reader: impl Read,
+    data_format: DataFormat,
+    pointer: &str,
+) -> Result<Vec<T>, Error> {
+    read_records_at_pointer_from_reader_with_options(
+        reader,
+        data_format,
+        pointer,
+        &ReadOptions::default(),
+    )
+}
+
+/// Reads multiple records nested inside a document from a reader with
+/// backend settings from `options`.
+///
+/// See [`read_records_at_pointer_from_reader`] for how `pointer` is used.
+///
+/// # Errors
+///
+/// Returns an error as [`read_records_at_pointer_from_reader`] does.
+pub fn read_records_at_pointer_from_reader_with_options<T: DeserializeOwned>(
+    reader: impl Read,
+    data_format: DataFormat,
+    pointer: &str,
+    options: &ReadOptions,
+) -> Result<Vec<T>, Error> {
+    let document = read_record_from_reader_with_options(reader, data_format, options)?;
+    pointer::records(document, pointer)
+}
+
+/// Reads a single record nested inside a document from a file.
+///
+/// See [`read_record_from_file`] for how the format is resolved and
+/// [`read_record_at_pointer_from_reader`] for how `pointer` is used.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the format can be determined
+/// neither from the extension nor from the content, decoding the document
+/// fails, `pointer` refers to nothing, or deserializing the value fails.
+pub fn read_record_at_pointer_from_file<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    pointer: &str,
+) -> Result<T, Error> {
+    read_record_at_pointer_from_file_with_options(
+        path,
+        data_format,
+        pointer,
+        &ReadOptions::default(),
+    )
+}
+
+/// Reads a single record nested inside a document from a file with backend
+/// settings from `options`.
+///
+/// See [`read_record_at_pointer_from_file`] for how the format is resolved
+/// and `pointer` is used.
+///
+/// # Errors
+///
+/// Returns an error as [`read_record_at_pointer_from_file`] does.
+pub fn read_record_at_pointer_from_file_with_options<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    pointer: &str,
+    options: &ReadOptions,
+) -> Result<T, Error> {
+    let path = path.as_ref();
+    read_record_from_file_with_options(path, data_format, options)
+        .and_then(|document| pointer::record(document, pointer))
+        .map_err(|e| e.with_path(path))
+}
+
+/// Reads multiple records nested inside a document from a file.
+///
+/// See [`read_record_from_file`] for how the format is resolved and
+/// [`read_records_at_pointer_from_reader`] for how `pointer` is used.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the format can be determined
+/// neither from the extension nor from the content, decoding the document
+/// fails, `pointer` refers to nothing, or deserializing a record fails.
+pub fn read_records_at_pointer_from_file<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    pointer: &str,
+) -> Result<Vec<T>, Error> {
+    read_records_at_pointer_from_file_with_options(
+        path,
+        data_format,
+        pointer,
+        &ReadOptions::default(),
+    )
+}
+
+/// Reads multiple records nested inside a document from a file with backend
+/// settings from `options`.
+///
+/// See [`read_records_at_pointer_from_file`] for how the format is resolved
+/// and `pointer` is used.
+///
+/// # Errors
+///
+/// Returns an error as [`read_records_at_pointer_from_file`] does.
+pub fn read_records_at_pointer_from_file_with_options<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    pointer: &str,
+    options: &ReadOptions,
+) -> Result<Vec<T>, Error> {
+    let path = path.as_ref();
+    read_record_from_file_with_options(path, data_format, options)
+        .and_then(|document| pointer::records(document, pointer))
+        .map_err(|e| e.with_path(path))
+}
+
+/// Reads the content of a reader as a [`Value`], without a type to
+/// deserialize into.
+///
+/// Formats that hold a single record give that record. Formats that only hold
+/// multiple records give an array of them; each CSV or TSV row becomes an
+/// object keyed by the header row, with numbers and booleans recognized when
+/// they are written as they would be written back, so that `01234` or `1.50`
+/// stays a string.
+/// With `csv.flatten` set in the options, dotted columns such as
+/// `address.city` or `tags.0` are rebuilt into nested objects and arrays. A
+/// YAML stream of several documents gives an array of the documents.
+///
+/// # Supported Formats
+///
+/// - JSON (always available)
+/// - JSON Lines (always available)
+/// - CSV (requires `csv` feature)
+/// - TSV (requires `csv` feature)
+/// - YAML (requires `yaml` feature)
+/// - MessagePack (requires `messagepack` feature)
+/// - TOML (requires `toml` feature)
+///
+/// With `DataFormat::Auto` the format, and any compression, is detected from
+/// the content; see [`crate::detect_format`].
+///
+/// # Errors
+///
+/// Returns an error if the data format cannot be detected, or if decoding
+/// fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::Cursor;
+///
+/// use serdeio::{read_value_from_reader, DataFormat};
+///
+/// let jsonl_data = "{\"name\": \"Alice\"}\n{\"name\": \"Bob\"}\n";
+/// let value = read_value_from_reader(Cursor::new(jsonl_data), DataFormat::JsonLines).unwrap();
+/// assert_eq!(value.as_array().unwrap()[1].get("name").unwrap().as_str(), Some("Bob"));
+/// ```
+pub fn read_value_from_reader(reader: impl Read, data_format: DataFormat) -> Result<Value, Error> {
+    read_value_from_reader_with_options(reader, data_format, &ReadOptions::default())
+}
+
+/// Reads the content of a reader as a [`Value`] with backend settings from
+/// `options`.
+///
+/// See [`read_value_from_reader`] for the supported formats and the shape of
+/// the value.
+///
+/// # Errors
+///
+/// Returns an error if the data format cannot be detected, or if decoding
+/// fails.
+pub fn read_value_from_reader_with_options(
+    reader: impl Read,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<Value, Error> {
+    match data_format {
+        DataFormat::Auto => {
+            let (reader, data_format) = detect::sniff_reader(reader)?;
+            read_value_from_reader_with_options(reader, data_format, options)
+        }
+        #[cfg(feature = "csv")]
+        DataFormat::Csv | DataFormat::Tsv => read_value_records(reader, data_format, options)?
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        DataFormat::JsonLines => read_value_records(reader, data_format, options)?
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        #[cfg(feature = "yaml")]
+        DataFormat::Yaml => {
+            let mut documents =
+                backend::yaml::read_documents(reader).map_err(|e| e.with_format(data_format))?;
+            Ok(match documents.len() {
+                0 => Value::Null,
+                1 => documents.remove(0),
+                _ => Value::Array(documents),
+            })
+        }
+        _ => read_record_from_reader_with_options(reader, data_format, options),
+    }
+}
+
+/// Lazily reads records as [`Value`]s. Each CSV or TSV row becomes an object
+/// keyed by the header row, since a row read as an untyped value would only
+/// give its first field. With `csv.flatten`, its dotted columns are rebuilt
+/// into nested values.
+pub(crate) fn read_value_records<'a>(
+    reader: impl Read + 'a,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<Box<dyn Iterator<Item = Result<Value, Error>> + 'a>, Error> {
+    let records = read_located_value_records(reader, data_format, options)?;
+    Ok(Box::new(
+        records.map(|record| record.map(|(_, record)| record)),
+    ))
+}
+
+/// Records paired with the one-based line each one starts on, if known.
+pub(crate) type LocatedValues<'a> =
+    Box<dyn Iterator<Item = Result<(Option<u64>, Value), Error>> + 'a>;
+
+/// Lazily reads records as [`Value`]s together with the one-based line each
+/// one starts on, which only JSON Lines, CSV and TSV report.
+pub(crate) fn read_located_value_records<'a>(
+    reader: impl Read + 'a,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<LocatedValues<'a>, Error> {
+    let records: LocatedValues<'a> = match data_format {
+        DataFormat::Auto => {
+            let (reader, data_format) = detect::sniff_reader(reader)?;
+            return read_located_value_records(reader, data_format, options);
+        }
+        DataFormat::JsonLines => Box::new(
+            backend::jsonlines::read_located_iter(reader)
+                .map(|record| record.map(|(line, record)| (Some(line), record))),
+        ),
+        #[cfg(feature = "csv")]
+        DataFormat::Csv | DataFormat::Tsv => {
+            // Rows are read flat and unflattened as values, so that arrays
+            // come out as arrays.
+            let rows = read_located_rows(reader, data_format, &options.csv);
+            let unflatten = options.csv.flatten;
+            Box::new(rows.map(move |row| {
+                row.and_then(|(line, row)| Ok((line, row_value(line, &row, unflatten)?)))
+            }))
+        }
+        _ => {
+            let records = read_records_iter_from_reader_with_options(reader, data_format, options)?;
+            return Ok(Box::new(
+                records.map(|record| record.map(|record| (None, record))),
+            ));
+        }
+    };
+    Ok(Box::new(records.map(move |record| {
+        record.map_err(|e| e.with_format(data_format))
+    })))
+}
+
+/// CSV or TSV rows as their raw cells, keyed by the header row.
+#[cfg(feature = "csv")]
+pub(crate) type LocatedRows<'a> =
+    Box<dyn Iterator<Item = Result<(Option<u64>, indexmap::IndexMap<String, String>), Error>> + 'a>;
+
+/// Reads CSV or TSV rows as their raw cells, whatever `options.flatten` says.
+#[cfg(feature = "csv")]
+pub(crate) fn read_located_rows<'a>(
+    reader: impl Read + 'a,
+    data_format: DataFormat,
+    options: &CsvReadOptions,
+) -> LocatedRows<'a> {
+    let flat = CsvReadOptions {
+        flatten: false,
+        ..options.clone()
+    };
+    if data_format == DataFormat::Csv {
+        Box::new(backend::csv::read_located_iter(reader, &flat))
+    } else {
+        Box::new(backend::csv::read_tsv_located_iter(reader, &flat))
+    }
+}
+
+/// Types the cells of a CSV or TSV row as [`backend::flatten::cell_value`]
+/// does and, when `unflatten` is set, rebuilds the nested values of the row
+/// from its dotted columns, as [`crate::write_value_to_writer`] writes them.
+#[cfg(feature = "csv")]
+pub(crate) fn row_value(
+    line: Option<u64>,
+    row: &indexmap::IndexMap<String, String>,
+    unflatten: bool,
+) -> Result<Value, Error> {
+    let row: crate::Map = row
+        .iter()
+        .map(|(column, cell)| (column.clone(), backend::flatten::cell_value(cell)))
+        .collect();
+    if !unflatten {
+        return Ok(Value::Object(row));
+    }
+    backend::flatten::unflatten_row(row)
+        .map(Value::Object)
+        .map_err(|e| Error::from(e).with_position(line, None, None))
+}
+
+/// Reads the content of a file as a [`Value`], without a type to deserialize
+/// into.
+///
+/// See [`read_record_from_file`] for how the format is resolved and
+/// [`read_value_from_reader`] for the shape of the value.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the format can be determined
+/// neither from the extension nor from the content, or decoding fails.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serdeio::{read_value_from_file, DataFormat};
+///
+/// let value = read_value_from_file("config.toml", DataFormat::Auto).unwrap();
+/// println!("{:?}", value.get("server"));
+/// ```
+pub fn read_value_from_file(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+) -> Result<Value, Error> {
+    read_value_from_file_with_options(path, data_format, &ReadOptions::default())
+}
+
+/// Reads the content of a file as a [`Value`] with backend settings from
+/// `options`.
+///
+/// See [`read_value_from_file`] for how the format is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, the format can be determined
+/// neither from the extension nor from the content, or decoding fails.
+pub fn read_value_from_file_with_options(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<Value, Error> {
+    let path = path.as_ref();
+    open_reader(path, data_format)
+        .and_then(|(rdr, final_format)| {
+            read_value_from_reader_with_options(rdr, final_format, options)
+        })
+        .map_err(|e| e.with_path(path))
 }
 
 #[cfg(test)]
@@ -229,19 +1191,146 @@ mod tests {
     }
 
     #[test]
-    fn test_read_record_from_reader_auto_not_supported() {
+    fn test_read_record_from_reader_auto_detects_format() {
         let json_data = r#"{"name": "test", "value": 42}"#;
         let reader = Cursor::new(json_data);
-        let result: Result<TestRecord, Error> = read_record_from_reader(reader, DataFormat::Auto);
-        assert!(matches!(result, Err(Error::AutoNotSupported)));
+        let record: TestRecord = read_record_from_reader(reader, DataFormat::Auto).unwrap();
+        assert_eq!(record.value, 42);
     }
 
     #[test]
-    fn test_read_records_from_reader_auto_not_supported() {
+    fn test_read_records_from_reader_auto_detects_format() {
         let json_data = r#"[{"name": "test1", "value": 1}, {"name": "test2", "value": 2}]"#;
         let reader = Cursor::new(json_data);
+        let records: Vec<TestRecord> = read_records_from_reader(reader, DataFormat::Auto).unwrap();
+        assert_eq!(records.len(), 2);
+
+        let jsonl_data =
+            "{\"name\": \"test1\", \"value\": 1}\n{\"name\": \"test2\", \"value\": 2}\n";
+        let reader = Cursor::new(jsonl_data);
+        let records: Vec<TestRecord> = read_records_from_reader(reader, DataFormat::Auto).unwrap();
+        assert_eq!(records.len(), 2);
+    }
+
+    #[test]
+    fn test_read_records_from_reader_auto_undetected() {
+        let reader = Cursor::new("plain text");
         let result: Result<Vec<TestRecord>, Error> =
             read_records_from_reader(reader, DataFormat::Auto);
-        assert!(matches!(result, Err(Error::AutoNotSupported)));
+        assert!(matches!(
+            result,
+            Err(Error::DataFormat(crate::DataFormatError::Undetected))
+        ));
+    }
+
+    #[test]
+    fn test_read_records_iter_from_reader_jsonl() {
+        let jsonl_data =
+            "{\"name\": \"test1\", \"value\": 1}\nnot json\n{\"name\": \"test2\", \"value\": 2}\n";
+        let reader = Cursor::new(jsonl_data);
+        let results: Vec<Result<TestRecord, Error>> =
+            read_records_iter_from_reader(reader, DataFormat::JsonLines)
+                .unwrap()
+                .collect();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().value, 1);
+        let error = results[1].as_ref().err().unwrap();
+        assert!(matches!(error.kind(), Error::Json(_)));
+        let context = error.context().unwrap();
+        assert_eq!(context.format(), Some(DataFormat::JsonLines));
+        assert_eq!(context.record(), Some(1));
+        assert_eq!(context.line(), Some(2));
+        assert_eq!(results[2].as_ref().unwrap().value, 2);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_read_value_csv_unflattens_only_with_flatten() {
+        let data = "host,host.port\nexample.com,8080\n";
+        let value = read_value_from_reader(Cursor::new(data), DataFormat::Csv).unwrap();
+        let row = value.as_array().unwrap()[0].as_object().unwrap();
+        assert_eq!(row.keys().collect::<Vec<_>>(), ["host", "host.port"]);
+        assert_eq!(row["host.port"], Value::Integer(8080));
+
+        let mut options = ReadOptions::default();
+        options.csv.flatten = true;
+        let data = "id,tags.1,tags.0\n1,b,a\n";
+        let value =
+            read_value_from_reader_with_options(Cursor::new(data), DataFormat::Csv, &options)
+                .unwrap();
+        let tags = value.as_array().unwrap()[0].get("tags").unwrap();
+        assert_eq!(
+            tags,
+            &Value::Array(vec![
+                Value::String("a".to_string()),
+                Value::String("b".to_string())
+            ])
+        );
+    }
+
+    #[cfg(feature = "messagepack")]
+    #[test]
+    fn test_read_at_pointer_keeps_bytes_and_keys() {
+        let mut blob = crate::Map::new();
+        blob.insert("bin".to_string(), Value::Bytes(vec![0, 255]));
+        let document = Value::Object(crate::Map::from_iter([(
+            "data".to_string(),
+            Value::Array(vec![Value::Object(blob)]),
+        )]));
+        let encoded = rmp_serde::to_vec_named(&document).unwrap();
+
+        #[derive(Deserialize)]
+        struct Blob {
+            bin: Vec<u8>,
+        }
+        let blobs: Vec<Blob> =
+            read_records_at_pointer_from_reader(&encoded[..], DataFormat::MessagePack, "/data")
+                .unwrap();
+        assert_eq!(blobs[0].bin, [0, 255]);
+        let value: Value =
+            read_record_at_pointer_from_reader(&encoded[..], DataFormat::MessagePack, "/data/0")
+                .unwrap();
+        assert_eq!(value.get("bin"), Some(&Value::Bytes(vec![0, 255])));
+
+        // An integer key is read as its text form.
+        let mut encoded = Vec::new();
+        rmp::encode::write_map_len(&mut encoded, 1).unwrap();
+        rmp::encode::write_uint(&mut encoded, 7).unwrap();
+        rmp::encode::write_str(&mut encoded, "seven").unwrap();
+        let seven: String =
+            read_record_at_pointer_from_reader(&encoded[..], DataFormat::MessagePack, "/7")
+                .unwrap();
+        assert_eq!(seven, "seven");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_read_at_pointer_keeps_toml_datetimes() {
+        let data = "[meta]\nwhen = 1979-05-27T07:32:00Z\n";
+        let when: toml::value::Datetime =
+            read_record_at_pointer_from_reader(Cursor::new(data), DataFormat::Toml, "/meta/when")
+                .unwrap();
+        assert_eq!(when.to_string(), "1979-05-27T07:32:00Z");
+        let when: String =
+            read_record_at_pointer_from_reader(Cursor::new(data), DataFormat::Toml, "/meta/when")
+                .unwrap();
+        assert_eq!(when, "1979-05-27T07:32:00Z");
+        let meta: Value =
+            read_record_at_pointer_from_reader(Cursor::new(data), DataFormat::Toml, "/meta")
+                .unwrap();
+        assert_eq!(
+            meta.get("when"),
+            Some(&Value::Datetime("1979-05-27T07:32:00Z".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_read_records_iter_from_reader_auto_undetected() {
+        let reader = Cursor::new("");
+        let result = read_records_iter_from_reader::<TestRecord>(reader, DataFormat::Auto);
+        assert!(matches!(
+            result,
+            Err(Error::DataFormat(crate::DataFormatError::Undetected))
+        ));
     }
 }
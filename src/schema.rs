@@ -0,0 +1,1060 @@
+use std::{fmt, io::Read, path::Path};
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    Error,
+    options::ReadOptions,
+    read::{
+        open_reader, read_located_value_records, read_record_from_reader_with_options,
+        read_value_from_file,
+    },
+    types::DataFormat,
+    value::Value,
+};
+#[cfg(feature = "csv")]
+use crate::{
+    backend::csv::deserialize_cells,
+    read::{read_located_rows, row_value},
+};
+
+/// A compiled JSON Schema to validate records against.
+///
+/// The draft is taken from the `$schema` keyword, and defaults to 2020-12.
+/// References to other documents are not resolved.
+pub struct Schema {
+    validator: jsonschema::Validator,
+}
+
+impl fmt::Debug for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Schema").finish_non_exhaustive()
+    }
+}
+
+impl Schema {
+    /// Compiles a JSON Schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidSchema`] if `schema` is not a valid JSON Schema.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serdeio::{Schema, Value};
+    ///
+    /// let schema: Value = serde_json::json!({"type": "object", "required": ["id"]}).into();
+    /// let schema = Schema::new(&schema).unwrap();
+    /// ```
+    pub fn new(schema: &Value) -> Result<Self, Error> {
+        let schema = serde_json::Value::from(schema.clone());
+        jsonschema::validator_for(&schema)
+            .map(|validator| Self { validator })
+            .map_err(|e| Error::InvalidSchema(e.to_string()))
+    }
+
+    /// Reads and compiles a JSON Schema from a file in any supported format,
+    /// such as `schema.json` or `schema.yaml`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, or
+    /// [`Error::InvalidSchema`] if it does not hold a valid JSON Schema.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let schema = read_value_from_file(path, DataFormat::Auto)?;
+        Self::new(&schema).map_err(|e| e.with_path(path))
+    }
+
+    /// Returns an [`Error::SchemaViolation`] for each place where `value` does
+    /// not match the schema, with the path to the offending field, in
+    /// document order.
+    pub(crate) fn violations(&self, value: &Value) -> Vec<Error> {
+        let instance = serde_json::Value::from(value.clone());
+        let mut violations: Vec<_> = self
+            .validator
+            .iter_errors(&instance)
+            .map(|e| {
+                let pointer = e.instance_path().as_str();
+                let error = Error::SchemaViolation(e.to_string());
+                let error = match field_path(&instance, pointer) {
+                    Some(field) => error.with_field(field),
+                    None => error,
+                };
+                (document_position(value, pointer), error)
+            })
+            .collect();
+        violations.sort_by(|(a, _), (b, _)| a.cmp(b));
+        violations.into_iter().map(|(_, error)| error).collect()
+    }
+}
+
+/// Locates the JSON Pointer `pointer` in `value` by the index of each member
+/// or element along the way, so that places sort in document order.
+fn document_position(value: &Value, pointer: &str) -> Vec<usize> {
+    let mut position = Vec::new();
+    let mut target = Some(value);
+    for token in pointer.split('/').skip(1) {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        let (index, next) = match target {
+            Some(Value::Object(members)) => members
+                .get_full(&token)
+                .map_or((usize::MAX, None), |(index, _, member)| {
+                    (index, Some(member))
+                }),
+            Some(Value::Array(elements)) => {
+                token.parse().map_or((usize::MAX, None), |index: usize| {
+                    (index, elements.get(index))
+                })
+            }
+            _ => (usize::MAX, None),
+        };
+        position.push(index);
+        target = next;
+    }
+    position
+}
+
+/// Spells the JSON Pointer `pointer` into `instance` as a field path such as
+/// `servers[3].tls.port`, or `None` for the whole instance.
+fn field_path(instance: &serde_json::Value, pointer: &str) -> Option<String> {
+    let mut path = String::new();
+    let mut target = Some(instance);
+    for token in pointer.split('/').skip(1) {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        match target {
+            Some(serde_json::Value::Array(elements)) => {
+                target = token
+                    .parse()
+                    .ok()
+                    .and_then(|index: usize| elements.get(index));
+                path.push_str(&format!("[{token}]"));
+            }
+            _ => {
+                target = target.and_then(|target| target.get(&token));
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(&token);
+            }
+        }
+    }
+    (!path.is_empty()).then_some(path)
+}
+
+/// Returns true for errors that stop a validation instead of being reported
+/// as a problem with the input.
+fn is_fatal(error: &Error) -> bool {
+    matches!(
+        error.kind(),
+        Error::Io(_)
+            | Error::AutoNotSupported
+            | Error::DataFormat(_)
+            | Error::UnsupportedFormat(_)
+            | Error::UnsupportedCompression(_)
+    )
+}
+
+/// Checks that a reader holds a well-formed record and, if `schema` is given,
+/// that the record matches it.
+///
+/// Instead of failing at the first problem, every problem found is returned:
+/// a syntax or decoding error, with its line and column where the backend
+/// reports them, or an [`Error::SchemaViolation`] for each place the record
+/// does not match the schema, with the path to the field. An empty list means
+/// the input is valid. JSON Lines, CSV and TSV only hold multiple records, so
+/// they are checked as by [`validate_records_from_reader`].
+///
+/// # Errors
+///
+/// Returns an error if the data format cannot be detected or is not
+/// supported, or if reading fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::Cursor;
+///
+/// use serdeio::{validate_record_from_reader, DataFormat, Schema, Value};
+///
+/// let schema: Value = serde_json::json!({"properties": {"port": {"maximum": 65535}}}).into();
+/// let schema = Schema::new(&schema).unwrap();
+/// let problems = validate_record_from_reader(
+///     Cursor::new(r#"{"port": 80000}"#),
+///     DataFormat::Json,
+///     Some(&schema),
+/// )
+/// .unwrap();
+/// assert_eq!(problems.len(), 1);
+/// assert_eq!(problems[0].context().unwrap().field(), Some("port"));
+/// ```
+pub fn validate_record_from_reader(
+    reader: impl Read,
+    data_format: DataFormat,
+    schema: Option<&Schema>,
+) -> Result<Vec<Error>, Error> {
+    validate_record_from_reader_with_options(reader, data_format, schema, &ReadOptions::default())
+}
+
+/// Checks that a reader holds a well-formed record, and that it matches
+/// `schema`, with backend settings from `options`.
+///
+/// See [`validate_record_from_reader`] for what is reported.
+///
+/// # Errors
+///
+/// Returns an error if the data format cannot be detected or is not
+/// supported, or if reading fails.
+pub fn validate_record_from_reader_with_options(
+    reader: impl Read,
+    data_format: DataFormat,
+    schema: Option<&Schema>,
+    options: &ReadOptions,
+) -> Result<Vec<Error>, Error> {
+    if data_format == DataFormat::Auto {
+        let (reader, data_format) = crate::detect::sniff_reader(reader)?;
+        return validate_record(reader, data_format, schema, options);
+    }
+    validate_record(reader, data_format, schema, options)
+}
+
+fn validate_record(
+    reader: impl Read,
+    data_format: DataFormat,
+    schema: Option<&Schema>,
+    options: &ReadOptions,
+) -> Result<Vec<Error>, Error> {
+    if data_format.holds_only_records() {
+        return validate_records(reader, data_format, schema, options);
+    }
+    match read_record_from_reader_with_options::<Value>(reader, data_format, options) {
+        Ok(record) => Ok(schema.map_or_else(Vec::new, |schema| {
+            schema
+                .violations(&record)
+                .into_iter()
+                .map(|e| e.with_format(data_format))
+                .collect()
+        })),
+        Err(e) if is_fatal(&e) => Err(e),
+        Err(e) => Ok(vec![e]),
+    }
+}
+
+/// Checks that a reader holds well-formed records and, if `schema` is given,
+/// that each record matches it.
+///
+/// See [`validate_record_from_reader`] for what is reported. Each problem
+/// carries the index of its record, and the line where the format has lines.
+/// A record that fails to decode is reported and checking carries on with the
+/// next one where the format delimits each record on its own (JSON arrays,
+/// JSON Lines, CSV, TSV, YAML document streams); any other syntax error ends
+/// the check. CSV and TSV cells are untyped, so they are checked as numbers or
+/// booleans when they look like them, and empty cells as nulls.
+///
+/// # Errors
+///
+/// Returns an error if the data format cannot be detected or is not
+/// supported for multiple records, or if reading fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::Cursor;
+///
+/// use serdeio::{validate_records_from_reader, DataFormat, Schema, Value};
+///
+/// let schema: Value = serde_json::json!({"required": ["id"]}).into();
+/// let schema = Schema::new(&schema).unwrap();
+/// let problems = validate_records_from_reader(
+///     Cursor::new("{\"id\": 1}\n{\"name\": \"b\"}\nnot json\n"),
+///     DataFormat::JsonLines,
+///     Some(&schema),
+/// )
+/// .unwrap();
+/// let lines: Vec<_> = problems.iter().map(|e| e.context().unwrap().line()).collect();
+/// assert_eq!(lines, vec![Some(2), Some(3)]);
+/// ```
+pub fn validate_records_from_reader(
+    reader: impl Read,
+    data_format: DataFormat,
+    schema: Option<&Schema>,
+) -> Result<Vec<Error>, Error> {
+    validate_records_from_reader_with_options(reader, data_format, schema, &ReadOptions::default())
+}
+
+/// Checks that a reader holds well-formed records, and that they match
+/// `schema`, with backend settings from `options`.
+///
+/// See [`validate_records_from_reader`] for what is reported.
+///
+/// # Errors
+///
+/// Returns an error if the data format cannot be detected or is not
+/// supported for multiple records, or if reading fails.
+pub fn validate_records_from_reader_with_options(
+    reader: impl Read,
+    data_format: DataFormat,
+    schema: Option<&Schema>,
+    options: &ReadOptions,
+) -> Result<Vec<Error>, Error> {
+    if data_format == DataFormat::Auto {
+        let (reader, data_format) = crate::detect::sniff_reader(reader)?;
+        return validate_records(reader, data_format, schema, options);
+    }
+    validate_records(reader, data_format, schema, options)
+}
+
+fn validate_records(
+    reader: impl Read,
+    data_format: DataFormat,
+    schema: Option<&Schema>,
+    options: &ReadOptions,
+) -> Result<Vec<Error>, Error> {
+    match decode_records(reader, data_format, options) {
+        Ok(records) => check_records(records, data_format, schema, |_, _, _| {}),
+        Err(e) if is_fatal(&e) => Err(e),
+        Err(e) => Ok(vec![e]),
+    }
+}
+
+/// Where a checked record is deserialized from.
+enum Source {
+    /// The value that was checked.
+    Value,
+    /// The raw cells of a CSV or TSV row, so that each cell is read as the
+    /// type of its field rather than as the value it was checked as.
+    #[cfg(feature = "csv")]
+    Cells {
+        cells: indexmap::IndexMap<String, String>,
+        unflatten: bool,
+    },
+}
+
+impl Source {
+    fn deserialize<T: DeserializeOwned>(self, value: Value) -> Result<T, Error> {
+        match self {
+            Source::Value => {
+                crate::backend::field_path::deserialize(value).map_err(|e| e.attach(Error::Json))
+            }
+            #[cfg(feature = "csv")]
+            Source::Cells { cells, unflatten } => deserialize_cells(&cells, unflatten),
+        }
+    }
+}
+
+/// Records as the values they are checked as, with the line each one starts
+/// on and where it is deserialized from.
+type Decoded<'a> = Box<dyn Iterator<Item = Result<(Option<u64>, Value, Source), Error>> + 'a>;
+
+fn decode_records<'a>(
+    reader: impl Read + 'a,
+    data_format: DataFormat,
+    options: &ReadOptions,
+) -> Result<Decoded<'a>, Error> {
+    match data_format {
+        #[cfg(feature = "csv")]
+        DataFormat::Csv | DataFormat::Tsv => {
+            let unflatten = options.csv.flatten;
+            let rows = read_located_rows(reader, data_format, &options.csv);
+            Ok(Box::new(rows.map(move |row| {
+                let (line, cells) = row.map_err(|e| e.with_format(data_format))?;
+                let value =
+                    row_value(line, &cells, unflatten).map_err(|e| e.with_format(data_format))?;
+                let source = Source::Cells { cells, unflatten };
+                Ok((line, empty_cells_as_null(value), source))
+            })))
+        }
+        _ => {
+            let records = read_located_value_records(reader, data_format, options)?;
+            Ok(Box::new(records.map(|record| {
+                record.map(|(line, value)| (line, value, Source::Value))
+            })))
+        }
+    }
+}
+
+/// Checks each record against `schema` and hands the ones that decode to
+/// `keep`, returning the problems found as [`validate_records_from_reader`]
+/// reports them.
+fn check_records(
+    records: Decoded<'_>,
+    data_format: DataFormat,
+    schema: Option<&Schema>,
+    mut keep: impl FnMut(Option<u64>, Value, Source),
+) -> Result<Vec<Error>, Error> {
+    let mut problems = Vec::new();
+    for (index, record) in records.enumerate() {
+        match record {
+            Ok((line, record, source)) => {
+                if let Some(schema) = schema {
+                    problems.extend(schema.violations(&record).into_iter().map(|e| {
+                        e.with_record(index)
+                            .with_position(line, None, None)
+                            .with_format(data_format)
+                    }));
+                }
+                keep(line, record, source);
+            }
+            Err(e) if is_fatal(&e) => return Err(e),
+            // Only a record delimited on its own can be skipped.
+            Err(e) if e.context().and_then(|c| c.raw()).is_some() => problems.push(e),
+            Err(e) => {
+                problems.push(e);
+                break;
+            }
+        }
+    }
+    Ok(problems)
+}
+
+/// Checks that a file holds a well-formed record and, if `schema` is given,
+/// that the record matches it.
+///
+/// The format is resolved as in [`crate::read_record_from_file`]. See
+/// [`validate_record_from_reader`] for what is reported; each problem also
+/// carries the path of the file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or read, or its format
+/// cannot be determined or is not supported.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serdeio::{validate_record_from_file, DataFormat, Schema};
+///
+/// let schema = Schema::from_file("config.schema.json").unwrap();
+/// for problem in validate_record_from_file("config.yaml", DataFormat::Auto, Some(&schema)).unwrap()
+/// {
+///     eprintln!("{problem}");
+/// }
+/// ```
+pub fn validate_record_from_file(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    schema: Option<&Schema>,
+) -> Result<Vec<Error>, Error> {
+    validate_record_from_file_with_options(path, data_format, schema, &ReadOptions::default())
+}
+
+/// Checks that a file holds a well-formed record, and that it matches
+/// `schema`, with backend settings from `options`.
+///
+/// See [`validate_record_from_file`] for what is reported.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or read, or its format
+/// cannot be determined or is not supported.
+pub fn validate_record_from_file_with_options(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    schema: Option<&Schema>,
+    options: &ReadOptions,
+) -> Result<Vec<Error>, Error> {
+    let path = path.as_ref();
+    let (reader, data_format) = open_reader(path, data_format).map_err(|e| e.with_path(path))?;
+    validate_record(reader, data_format, schema, options)
+        .map(|problems| with_path(problems, path))
+        .map_err(|e| e.with_path(path))
+}
+
+/// Checks that a file holds well-formed records and, if `schema` is given,
+/// that each record matches it.
+///
+/// The format is resolved as in [`crate::read_records_from_file`]. See
+/// [`validate_records_from_reader`] for what is reported; each problem also
+/// carries the path of the file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or read, or its format
+/// cannot be determined or is not supported for multiple records.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serdeio::{validate_records_from_file, DataFormat};
+///
+/// let problems = validate_records_from_file("users.jsonl", DataFormat::Auto, None).unwrap();
+/// assert!(problems.is_empty(), "users.jsonl has syntax errors");
+/// ```
+pub fn validate_records_from_file(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    schema: Option<&Schema>,
+) -> Result<Vec<Error>, Error> {
+    validate_records_from_file_with_options(path, data_format, schema, &ReadOptions::default())
+}
+
+/// Checks that a file holds well-formed records, and that they match
+/// `schema`, with backend settings from `options`.
+///
+/// See [`validate_records_from_file`] for what is reported.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or read, or its format
+/// cannot be determined or is not supported for multiple records.
+pub fn validate_records_from_file_with_options(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    schema: Option<&Schema>,
+    options: &ReadOptions,
+) -> Result<Vec<Error>, Error> {
+    let path = path.as_ref();
+    let (reader, data_format) = open_reader(path, data_format).map_err(|e| e.with_path(path))?;
+    validate_records(reader, data_format, schema, options)
+        .map(|problems| with_path(problems, path))
+        .map_err(|e| e.with_path(path))
+}
+
+/// Reads the empty cells of a CSV or TSV row as nulls, as serde does for an
+/// `Option` field, down through the records and arrays rebuilt from dotted
+/// columns.
+#[cfg(feature = "csv")]
+fn empty_cells_as_null(row: Value) -> Value {
+    match row {
+        Value::String(cell) if cell.is_empty() => Value::Null,
+        Value::Object(cells) => Value::Object(
+            cells
+                .into_iter()
+                .map(|(column, cell)| (column, empty_cells_as_null(cell)))
+                .collect(),
+        ),
+        Value::Array(cells) => Value::Array(cells.into_iter().map(empty_cells_as_null).collect()),
+        row => row,
+    }
+}
+
+fn with_path(problems: Vec<Error>, path: &Path) -> Vec<Error> {
+    problems.into_iter().map(|e| e.with_path(path)).collect()
+}
+
+/// Reads the whole input, detecting its format first if it is `Auto`.
+fn read_input<'a>(
+    reader: impl Read + 'a,
+    data_format: DataFormat,
+) -> Result<(Vec<u8>, DataFormat), Error> {
+    let (mut reader, data_format): (Box<dyn Read + 'a>, _) = match data_format {
+        DataFormat::Auto => crate::detect::sniff_reader(reader)?,
+        _ => (Box::new(reader), data_format),
+    };
+    let mut input = Vec::new();
+    reader.read_to_end(&mut input)?;
+    Ok((input, data_format))
+}
+
+/// Fails with the first syntax error among `problems`, or else with all the
+/// schema violations.
+fn check(mut problems: Vec<Error>) -> Result<(), Error> {
+    let syntax_error = problems
+        .iter()
+        .position(|e| !matches!(e.kind(), Error::SchemaViolation(_)));
+    if let Some(index) = syntax_error {
+        return Err(problems.swap_remove(index));
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::SchemaViolations(problems))
+    }
+}
+
+fn read_record_checked<T: DeserializeOwned>(
+    input: &[u8],
+    data_format: DataFormat,
+    schema: &Schema,
+) -> Result<T, Error> {
+    if data_format.holds_only_records() {
+        return Err(Error::UnsupportedFormat(data_format));
+    }
+    let options = ReadOptions::default();
+    let record: Value = read_record_from_reader_with_options(input, data_format, &options)?;
+    let violations = schema.violations(&record);
+    check(
+        violations
+            .into_iter()
+            .map(|e| e.with_format(data_format))
+            .collect(),
+    )?;
+    Source::Value
+        .deserialize(record)
+        .map_err(|e| e.with_format(data_format))
+}
+
+/// Checks every record, then deserializes each one from what was checked.
+fn read_records_checked<T: DeserializeOwned>(
+    input: &[u8],
+    data_format: DataFormat,
+    schema: &Schema,
+    options: &ReadOptions,
+) -> Result<Vec<T>, Error> {
+    let mut records = Vec::new();
+    let decoded = decode_records(input, data_format, options)?;
+    check(check_records(
+        decoded,
+        data_format,
+        Some(schema),
+        |line, record, source| records.push((line, record, source)),
+    )?)?;
+    records
+        .into_iter()
+        .enumerate()
+        .map(|(index, (line, record, source))| {
+            source.deserialize(record).map_err(|e| {
+                e.with_record(index)
+                    .with_position(line, None, None)
+                    .with_format(data_format)
+            })
+        })
+        .collect()
+}
+
+/// Reads a single record from a reader, checking it against `schema` before
+/// deserializing it into `T`.
+///
+/// The record is decoded once, as by [`crate::read_value_from_reader`], and
+/// every schema violation in that value is reported at once; `T` is then
+/// deserialized from the same value. Schema violations catch what the type
+/// of `T` cannot express, such as ranges, enumerations or patterns.
+///
+/// # Errors
+///
+/// Returns [`Error::SchemaViolations`] with an [`Error::SchemaViolation`] for
+/// each place where the record does not match the schema, each carrying the
+/// path to its field. Otherwise returns an error as
+/// [`crate::read_record_from_reader`] does.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::Cursor;
+///
+/// use serde::Deserialize;
+/// use serdeio::{read_record_from_reader_with_schema, DataFormat, Error, Schema, Value};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Config {
+///     port: u32,
+/// }
+///
+/// let schema: Value = serde_json::json!({"properties": {"port": {"maximum": 65535}}}).into();
+/// let schema = Schema::new(&schema).unwrap();
+/// let error = read_record_from_reader_with_schema::<Config>(
+///     Cursor::new(r#"{"port": 80000}"#),
+///     DataFormat::Json,
+///     &schema,
+/// )
+/// .unwrap_err();
+/// let Error::SchemaViolations(violations) = error.kind() else { panic!() };
+/// assert_eq!(violations[0].context().unwrap().field(), Some("port"));
+/// ```
+pub fn read_record_from_reader_with_schema<T: DeserializeOwned>(
+    reader: impl Read,
+    data_format: DataFormat,
+    schema: &Schema,
+) -> Result<T, Error> {
+    let (input, data_format) = read_input(reader, data_format)?;
+    read_record_checked(&input, data_format, schema)
+}
+
+/// Reads multiple records from a reader, checking each one against `schema`
+/// before deserializing them into a vector of `T`.
+///
+/// See [`read_record_from_reader_with_schema`] for how the input is checked;
+/// every record is checked before any is deserialized, and each violation
+/// carries the index of its record, and its line where the format has lines.
+/// CSV and TSV records are deserialized from the same cells they are checked
+/// as: numbers or booleans only where they read back unchanged, and empty
+/// cells as nulls.
+///
+/// # Errors
+///
+/// Returns [`Error::SchemaViolations`] if any record does not match the
+/// schema. Otherwise returns an error as [`crate::read_records_from_reader`]
+/// does.
+pub fn read_records_from_reader_with_schema<T: DeserializeOwned>(
+    reader: impl Read,
+    data_format: DataFormat,
+    schema: &Schema,
+) -> Result<Vec<T>, Error> {
+    read_records_from_reader_with_options_and_schema(
+        reader,
+        data_format,
+        schema,
+        &ReadOptions::default(),
+    )
+}
+
+/// Reads multiple records from a reader with backend settings from
+/// `options`, checking each one against `schema` before deserializing them
+/// into a vector of `T`.
+///
+/// See [`read_records_from_reader_with_schema`] for how the records are
+/// checked.
+///
+/// # Errors
+///
+/// Returns [`Error::SchemaViolations`] if any record does not match the
+/// schema. Otherwise returns an error as
+/// [`crate::read_records_from_reader_with_options`] does.
+pub fn read_records_from_reader_with_options_and_schema<T: DeserializeOwned>(
+    reader: impl Read,
+    data_format: DataFormat,
+    schema: &Schema,
+    options: &ReadOptions,
+) -> Result<Vec<T>, Error> {
+    let (input, data_format) = read_input(reader, data_format)?;
+    read_records_checked(&input, data_format, schema, options)
+}
+
+/// Reads a single record from a file, checking it against `schema` before
+/// deserializing it into `T`.
+///
+/// The format is resolved as in [`crate::read_record_from_file`]. See
+/// [`read_record_from_reader_with_schema`] for how the record is checked.
+///
+/// # Errors
+///
+/// Returns [`Error::SchemaViolations`] if the record does not match the
+/// schema. Otherwise returns an error as [`crate::read_record_from_file`]
+/// does.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serde::Deserialize;
+/// use serdeio::{read_record_from_file_with_schema, DataFormat, Schema};
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     port: u32,
+/// }
+///
+/// let schema = Schema::from_file("config.schema.json").unwrap();
+/// let config: Config =
+///     read_record_from_file_with_schema("config.yaml", DataFormat::Auto, &schema).unwrap();
+/// ```
+pub fn read_record_from_file_with_schema<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    schema: &Schema,
+) -> Result<T, Error> {
+    let path = path.as_ref();
+    open_reader(path, data_format)
+        .and_then(|(reader, data_format)| read_input(reader, data_format))
+        .and_then(|(input, data_format)| read_record_checked(&input, data_format, schema))
+        .map_err(|e| e.with_path(path))
+}
+
+/// Reads multiple records from a file, checking each one against `schema`
+/// before deserializing them into a vector of `T`.
+///
+/// The format is resolved as in [`crate::read_records_from_file`]. See
+/// [`read_records_from_reader_with_schema`] for how the records are checked.
+///
+/// # Errors
+///
+/// Returns [`Error::SchemaViolations`] if any record does not match the
+/// schema. Otherwise returns an error as [`crate::read_records_from_file`]
+/// does.
+pub fn read_records_from_file_with_schema<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    schema: &Schema,
+) -> Result<Vec<T>, Error> {
+    read_records_from_file_with_options_and_schema(
+        path,
+        data_format,
+        schema,
+        &ReadOptions::default(),
+    )
+}
+
+/// Reads multiple records from a file with backend settings from `options`,
+/// checking each one against `schema` before deserializing them into a
+/// vector of `T`.
+///
+/// See [`read_records_from_file_with_schema`] for how the format is resolved
+/// and the records are checked.
+///
+/// # Errors
+///
+/// Returns [`Error::SchemaViolations`] if any record does not match the
+/// schema. Otherwise returns an error as
+/// [`crate::read_records_from_file_with_options`] does.
+pub fn read_records_from_file_with_options_and_schema<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    data_format: DataFormat,
+    schema: &Schema,
+    options: &ReadOptions,
+) -> Result<Vec<T>, Error> {
+    let path = path.as_ref();
+    open_reader(path, data_format)
+        .and_then(|(reader, data_format)| read_input(reader, data_format))
+        .and_then(|(input, data_format)| read_records_checked(&input, data_format, schema, options))
+        .map_err(|e| e.with_path(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn schema(schema: serde_json::Value) -> Schema {
+        Schema::new(&schema.into()).unwrap()
+    }
+
+    #[test]
+    fn test_field_path() {
+        let instance = json!({"servers": [{"tls": {"port": 1}}], "a/b": {"0": true}});
+        assert_eq!(
+            field_path(&instance, "/servers/0/tls/port").as_deref(),
+            Some("servers[0].tls.port")
+        );
+        assert_eq!(field_path(&instance, "/a~1b/0").as_deref(), Some("a/b.0"));
+        assert_eq!(field_path(&instance, ""), None);
+    }
+
+    #[test]
+    fn test_validate_record() {
+        let schema = schema(json!({
+            "properties": {
+                "name": {"type": "string"},
+                "servers": {"items": {"properties": {"port": {"maximum": 65535}}}},
+            },
+        }));
+        let input = r#"{"name": 1, "servers": [{"port": 80}, {"port": 80000}]}"#;
+        let problems =
+            validate_record_from_reader(input.as_bytes(), DataFormat::Auto, Some(&schema)).unwrap();
+        let fields: Vec<_> = problems
+            .iter()
+            .map(|e| e.context().unwrap().field().unwrap())
+            .collect();
+        assert_eq!(fields, vec!["name", "servers[1].port"]);
+        assert!(matches!(problems[0].kind(), Error::SchemaViolation(_)));
+
+        let problems =
+            validate_record_from_reader("{\n  \"name\": }".as_bytes(), DataFormat::Json, None)
+                .unwrap();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].context().unwrap().line(), Some(2));
+
+        let error = Schema::new(&json!({"type": 1}).into()).unwrap_err();
+        assert!(matches!(error, Error::InvalidSchema(_)));
+    }
+
+    #[test]
+    fn test_validate_records() {
+        let schema = schema(json!({"properties": {"id": {"type": "integer"}}}));
+        let input = "{\"id\": 1}\n\n{\"id\": \"2\"}\nnot json\n{\"id\": 4.5}\n";
+        let problems =
+            validate_records_from_reader(input.as_bytes(), DataFormat::JsonLines, Some(&schema))
+                .unwrap();
+        let located: Vec<_> = problems
+            .iter()
+            .map(|e| {
+                let context = e.context().unwrap();
+                (context.record(), context.line(), context.field())
+            })
+            .collect();
+        assert_eq!(
+            located,
+            vec![
+                (Some(1), Some(3), Some("id")),
+                (Some(2), Some(4), None),
+                (Some(3), Some(5), Some("id")),
+            ]
+        );
+        assert!(matches!(problems[1].kind(), Error::Json(_)));
+
+        let problems =
+            validate_records_from_reader("[{\"id\": }, {}, {".as_bytes(), DataFormat::Json, None)
+                .unwrap();
+        let records: Vec<_> = problems
+            .iter()
+            .map(|e| e.context().unwrap().record())
+            .collect();
+        assert_eq!(records, vec![Some(0), Some(2), None]);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_validate_csv_records() {
+        let schema = schema(json!({"properties": {
+            "score": {"minimum": 0},
+            "note": {"type": ["string", "null"]},
+        }}));
+        let input = "id,score,note\n1,0.5,a\n2,-1,\"two\nlines\"\n3,-2,\n";
+        let problems =
+            validate_record_from_reader(input.as_bytes(), DataFormat::Csv, Some(&schema)).unwrap();
+        let located: Vec<_> = problems
+            .iter()
+            .map(|e| (e.context().unwrap().record(), e.context().unwrap().line()))
+            .collect();
+        assert_eq!(located, vec![(Some(1), Some(3)), (Some(2), Some(5))]);
+    }
+
+    #[derive(Debug, serde::Deserialize, PartialEq)]
+    struct Item {
+        id: u32,
+        code: String,
+    }
+
+    #[test]
+    fn test_read_records_with_schema() {
+        let schema =
+            schema(json!({"properties": {"id": {"minimum": 1}, "code": {"pattern": "^[a-z]+$"}}}));
+        let input = "{\"id\": 1, \"code\": \"a\"}\n{\"id\": 0, \"code\": \"B\"}\n";
+        let error = read_records_from_reader_with_schema::<Item>(
+            input.as_bytes(),
+            DataFormat::Auto,
+            &schema,
+        )
+        .unwrap_err();
+        let Error::SchemaViolations(violations) = error.kind() else {
+            panic!("expected schema violations, got {error}");
+        };
+        let located: Vec<_> = violations
+            .iter()
+            .map(|e| {
+                let context = e.context().unwrap();
+                (context.record(), context.line(), context.field())
+            })
+            .collect();
+        assert_eq!(
+            located,
+            vec![
+                (Some(1), Some(2), Some("id")),
+                (Some(1), Some(2), Some("code"))
+            ]
+        );
+
+        // A syntax error is reported as such, rather than as a violation.
+        let error = read_records_from_reader_with_schema::<Item>(
+            "{\"id\": 1}\n{".as_bytes(),
+            DataFormat::JsonLines,
+            &schema,
+        )
+        .unwrap_err();
+        assert!(matches!(error.kind(), Error::Json(_)));
+
+        let error = read_record_from_reader_with_schema::<Item>(
+            "".as_bytes(),
+            DataFormat::JsonLines,
+            &schema,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::UnsupportedFormat(DataFormat::JsonLines)
+        ));
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_read_csv_records_with_schema() {
+        // A cell with leading zeros is checked as the string it is read as.
+        let schema = schema(json!({"properties": {"code": {"type": "string"}}}));
+        let items: Vec<Item> = read_records_from_reader_with_schema(
+            "id,code\n1,007\n".as_bytes(),
+            DataFormat::Csv,
+            &schema,
+        )
+        .unwrap();
+        assert_eq!(
+            items,
+            vec![Item {
+                id: 1,
+                code: "007".to_string()
+            }]
+        );
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_read_csv_values_with_schema() {
+        let schema = schema(json!({
+            "properties": {
+                "id": {"type": "integer"},
+                "code": {"type": "string"},
+                "note": {"type": "null"}
+            }
+        }));
+        let values: Vec<serde_json::Value> = read_records_from_reader_with_schema(
+            "id,code,note\n1,01234,\n".as_bytes(),
+            DataFormat::Csv,
+            &schema,
+        )
+        .unwrap();
+        assert_eq!(
+            values,
+            vec![json!({"id": 1, "code": "01234", "note": null})]
+        );
+
+        let schema = self::schema(json!({
+            "properties": {"tags": {"type": "array", "items": {"type": "integer"}}}
+        }));
+        let mut options = ReadOptions::default();
+        options.csv.flatten = true;
+        let values: Vec<serde_json::Value> = read_records_from_reader_with_options_and_schema(
+            "id,tags.0,tags.1\n1,2,3\n".as_bytes(),
+            DataFormat::Csv,
+            &schema,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(values, vec![json!({"id": 1, "tags": [2, 3]})]);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_read_csv_records_with_schema_reports_cell_errors() {
+        let schema = schema(json!({}));
+        let error = read_records_from_reader_with_schema::<Item>(
+            "id,code\n1,a\nx,b\n".as_bytes(),
+            DataFormat::Csv,
+            &schema,
+        )
+        .unwrap_err();
+        assert!(matches!(error.kind(), Error::Csv(_)), "{error:?}");
+        let context = error.context().unwrap();
+        assert_eq!(context.record(), Some(1));
+        assert_eq!(context.field(), Some("id"));
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_read_records_with_schema_with_options() {
+        let schema = schema(json!({"properties": {"id": {"maximum": 1}}}));
+        let mut options = ReadOptions::default();
+        options.csv.delimiter = b';';
+        let input = "id;code\n1;a\n2;b\n";
+        let error = read_records_from_reader_with_options_and_schema::<Item>(
+            input.as_bytes(),
+            DataFormat::Csv,
+            &schema,
+            &options,
+        )
+        .unwrap_err();
+        let Error::SchemaViolations(violations) = error.kind() else {
+            panic!("expected schema violations, got {error:?}");
+        };
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].context().unwrap().record(), Some(1));
+
+        let schema = self::schema(json!({"properties": {"id": {"maximum": 2}}}));
+        let items: Vec<Item> = read_records_from_reader_with_options_and_schema(
+            input.as_bytes(),
+            DataFormat::Csv,
+            &schema,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(items.len(), 2);
+    }
+}
@@ -14,6 +14,7 @@ use crate::Error;
 /// - `Json`: JSON format (always available)
 /// - `JsonLines`: JSON Lines format (always available)
 /// - `Csv`: CSV format (requires `csv` feature)
+/// - `Tsv`: tab-separated values (requires `csv` feature)
 /// - `Yaml`: YAML format (requires `yaml` feature)
 /// - `MessagePack`: MessagePack format (requires `messagepack` feature)
 /// - `Toml`: TOML format (requires `toml` feature)
@@ -33,6 +34,8 @@ pub enum DataFormat {
     JsonLines,
     #[cfg(feature = "csv")]
     Csv,
+    #[cfg(feature = "csv")]
+    Tsv,
     #[cfg(feature = "yaml")]
     Yaml,
     #[cfg(feature = "messagepack")]
@@ -49,6 +52,16 @@ pub enum DataFormatError {
     NoExtension(std::path::PathBuf),
     #[error("Invalid extension")]
     InvalidExtension,
+    #[error("Could not detect data format from content")]
+    Undetected,
+    #[error(
+        "Could not determine data format of {}: tried file extension ({extension}) and content detection (no known format matched)",
+        .path.display()
+    )]
+    Unresolved {
+        path: std::path::PathBuf,
+        extension: Box<DataFormatError>,
+    },
 }
 
 impl TryFrom<&str> for DataFormat {
@@ -60,6 +73,8 @@ impl TryFrom<&str> for DataFormat {
             "jsonl" | "jsl" => Ok(DataFormat::JsonLines),
             #[cfg(feature = "csv")]
             "csv" => Ok(DataFormat::Csv),
+            #[cfg(feature = "csv")]
+            "tsv" | "tab" => Ok(DataFormat::Tsv),
             #[cfg(feature = "yaml")]
             "yaml" | "yml" => Ok(DataFormat::Yaml),
             #[cfg(feature = "messagepack")]
@@ -74,15 +89,36 @@ impl TryFrom<&str> for DataFormat {
 impl TryFrom<&Path> for DataFormat {
     type Error = DataFormatError;
 
+    /// Infers the data format from the file extension.
+    ///
+    /// A trailing compression extension such as `.gz` is skipped, so
+    /// `users.jsonl.gz` resolves to `DataFormat::JsonLines`.
     fn try_from(value: &Path) -> std::result::Result<Self, Self::Error> {
-        let ext = value
-            .extension()
+        let inner = match Compression::from_path(value) {
+            Some(_) => value.file_stem().map(Path::new),
+            None => Some(value),
+        };
+        let ext = inner
+            .and_then(Path::extension)
             .ok_or_else(|| DataFormatError::NoExtension(value.to_path_buf()))
             .and_then(|v| v.to_str().ok_or(DataFormatError::InvalidExtension))?;
         Self::try_from(ext)
     }
 }
 
+impl DataFormat {
+    /// Returns true for formats that only hold multiple records, which cannot
+    /// hold a single record on its own and are read one record at a time.
+    pub(crate) fn holds_only_records(self) -> bool {
+        match self {
+            DataFormat::JsonLines => true,
+            #[cfg(feature = "csv")]
+            DataFormat::Csv | DataFormat::Tsv => true,
+            _ => false,
+        }
+    }
+}
+
 impl Display for DataFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -91,6 +127,8 @@ impl Display for DataFormat {
             DataFormat::JsonLines => write!(f, "jsonl"),
             #[cfg(feature = "csv")]
             DataFormat::Csv => write!(f, "csv"),
+            #[cfg(feature = "csv")]
+            DataFormat::Tsv => write!(f, "tsv"),
             #[cfg(feature = "yaml")]
             DataFormat::Yaml => write!(f, "yaml"),
             #[cfg(feature = "messagepack")]
@@ -101,6 +139,86 @@ impl Display for DataFormat {
     }
 }
 
+/// Compression codecs applied on top of a data format.
+///
+/// File-based functions recognize a trailing compression extension and
+/// transparently decompress on read and compress on write. Each codec is
+/// feature-gated.
+///
+/// # Supported Codecs
+///
+/// - `Gzip`: `.gz`, `.gzip` (requires `gzip` feature)
+/// - `Zstd`: `.zst`, `.zstd` (requires `zstd` feature)
+/// - `Bzip2`: `.bz2`, `.bzip2` (requires `bzip2` feature)
+/// - `Xz`: `.xz` (requires `xz` feature)
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "gzip")] {
+/// use std::path::Path;
+///
+/// use serdeio::{Compression, DataFormat};
+///
+/// let path = Path::new("users.jsonl.gz");
+/// assert_eq!(Compression::from_path(path), Some(Compression::Gzip));
+/// assert_eq!(DataFormat::try_from(path).unwrap(), DataFormat::JsonLines);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Compression {
+    #[cfg(feature = "gzip")]
+    Gzip,
+    #[cfg(feature = "zstd")]
+    Zstd,
+    #[cfg(feature = "bzip2")]
+    Bzip2,
+    #[cfg(feature = "xz")]
+    Xz,
+}
+
+impl Compression {
+    /// Returns the codec matching a file extension, ignoring case.
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.trim().to_lowercase().as_str() {
+            #[cfg(feature = "gzip")]
+            "gz" | "gzip" => Some(Compression::Gzip),
+            #[cfg(feature = "zstd")]
+            "zst" | "zstd" => Some(Compression::Zstd),
+            #[cfg(feature = "bzip2")]
+            "bz2" | "bzip2" => Some(Compression::Bzip2),
+            #[cfg(feature = "xz")]
+            "xz" => Some(Compression::Xz),
+            _ => None,
+        }
+    }
+
+    /// Returns the codec matching the last extension of `path`, if any.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        path.as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension)
+    }
+}
+
+impl Display for Compression {
+    // `f` is unused when no compression feature is enabled.
+    #[allow(unused_variables)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => write!(f, "gzip"),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => write!(f, "zstd"),
+            #[cfg(feature = "bzip2")]
+            Compression::Bzip2 => write!(f, "bzip2"),
+            #[cfg(feature = "xz")]
+            Compression::Xz => write!(f, "xz"),
+        }
+    }
+}
+
 /// Resolves the effective data format, inferring it from the file extension
 /// when `data_format` is `DataFormat::Auto`.
 pub(crate) fn resolve_format(
@@ -116,9 +234,9 @@ pub(crate) fn resolve_format(
 
 #[cfg(test)]
 mod test {
-    use std::convert::TryFrom;
+    use std::{convert::TryFrom, path::Path};
 
-    use super::DataFormat;
+    use super::{Compression, DataFormat};
     #[test]
     fn test_data_format() {
         assert_eq!(DataFormat::try_from("json").unwrap(), DataFormat::Json);
@@ -132,7 +250,12 @@ mod test {
             DataFormat::JsonLines
         );
         #[cfg(feature = "csv")]
-        assert_eq!(DataFormat::try_from("csv").unwrap(), DataFormat::Csv);
+        {
+            assert_eq!(DataFormat::try_from("csv").unwrap(), DataFormat::Csv);
+            assert_eq!(DataFormat::try_from("tsv").unwrap(), DataFormat::Tsv);
+            assert_eq!(DataFormat::try_from("tab").unwrap(), DataFormat::Tsv);
+            assert_eq!(DataFormat::Tsv.to_string(), "tsv");
+        }
         #[cfg(feature = "yaml")]
         assert_eq!(DataFormat::try_from("yaml").unwrap(), DataFormat::Yaml);
         #[cfg(feature = "messagepack")]
@@ -155,4 +278,28 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_data_format_from_path() {
+        assert_eq!(
+            DataFormat::try_from(Path::new("users.JSON")).unwrap(),
+            DataFormat::Json
+        );
+        assert!(DataFormat::try_from(Path::new("users")).is_err());
+        assert_eq!(Compression::from_path("users.json"), None);
+        #[cfg(feature = "gzip")]
+        {
+            assert_eq!(
+                Compression::from_path("users.jsonl.GZ"),
+                Some(Compression::Gzip)
+            );
+            assert_eq!(
+                DataFormat::try_from(Path::new("dir/users.jsonl.gz")).unwrap(),
+                DataFormat::JsonLines
+            );
+            assert!(DataFormat::try_from(Path::new("users.gz")).is_err());
+        }
+        #[cfg(not(feature = "gzip"))]
+        assert!(DataFormat::try_from(Path::new("users.jsonl.gz")).is_err());
+    }
 }
@@ -0,0 +1,960 @@
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{
+        self, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+        value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer},
+    },
+    ser,
+};
+
+/// Key under which TOML passes a datetime through serde, as a one-entry map.
+const TOML_DATETIME_KEY: &str = "$__toml_private_datetime";
+
+/// Members of a [`Value::Object`], in the order they were read or inserted.
+pub type Map = IndexMap<String, Value>;
+
+/// A record of any shape, read from or written to any [`crate::DataFormat`]
+/// without defining a struct for it.
+///
+/// Unlike [`serde_json::Value`], it keeps object members in document order,
+/// tells integers from floats, and holds binary data (MessagePack) and
+/// datetimes (TOML) as such, so they survive a read and a write.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::Cursor;
+///
+/// use serdeio::{read_value_from_reader, DataFormat, Value};
+///
+/// let value = read_value_from_reader(Cursor::new(r#"{"b": 1, "a": 2.5}"#), DataFormat::Json)
+///     .unwrap();
+/// let object = value.as_object().unwrap();
+/// assert_eq!(object.keys().collect::<Vec<_>>(), ["b", "a"]);
+/// assert_eq!(value.get("b"), Some(&Value::Integer(1)));
+/// assert_eq!(value.get("a"), Some(&Value::Float(2.5)));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Value {
+    #[default]
+    Null,
+    Bool(bool),
+    /// An integer that fits in an `i64`.
+    Integer(i64),
+    /// An integer above `i64::MAX`.
+    Unsigned(u64),
+    Float(f64),
+    String(String),
+    /// Binary data, such as a MessagePack `bin` value.
+    Bytes(Vec<u8>),
+    /// A TOML date, time or datetime, in its RFC 3339 text form such as
+    /// `1979-05-27T07:32:00Z`. Formats without datetimes write it as a string.
+    Datetime(String),
+    Array(Vec<Value>),
+    Object(Map),
+}
+
+impl Value {
+    /// Returns the member `key` of an object.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_object()?.get(key)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns an integer that fits in an `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Returns a float, or an integer converted to one.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Integer(i) => Some(*i as f64),
+            Value::Unsigned(u) => Some(*u as f64),
+            Value::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Returns a string, or the text of a datetime.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) | Value::Datetime(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Map> {
+        match self {
+            Value::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+}
+
+/// Serializes a [`Value`] as TOML, keeping object members in order. Null
+/// members of objects are left out, as TOML has no null; any other null is an
+/// error.
+#[cfg(feature = "toml")]
+pub(crate) struct TomlValue<'a>(pub &'a Value);
+
+#[cfg(feature = "toml")]
+impl Serialize for TomlValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error as _;
+
+        match self.0 {
+            Value::Null => Err(S::Error::custom("TOML cannot represent null")),
+            Value::Unsigned(u) => serializer.serialize_i64(
+                i64::try_from(*u).map_err(|_| S::Error::custom("integer out of range"))?,
+            ),
+            Value::Bytes(bytes) => serializer.collect_seq(bytes),
+            Value::Datetime(s) => s
+                .parse::<toml::value::Datetime>()
+                .map_err(S::Error::custom)?
+                .serialize(serializer),
+            Value::Array(items) => serializer.collect_seq(items.iter().map(TomlValue)),
+            Value::Object(members) => serializer.collect_map(
+                members
+                    .iter()
+                    .filter(|(_, value)| !value.is_null())
+                    .map(|(key, value)| (key, TomlValue(value))),
+            ),
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+/// Serializes `value` into a [`Value`], keeping the fields of structs and the
+/// entries of maps in the order they are serialized, bytes as bytes, and TOML
+/// datetimes as datetimes.
+pub(crate) fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, serde_json::Error> {
+    value.serialize(ValueSerializer)
+}
+
+/// Serializer whose output is a [`Value`].
+struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = serde_json::Error;
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeVariant<SerializeArray>;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeVariant<SerializeObject>;
+
+    fn serialize_bool(self, b: bool) -> Result<Value, Self::Error> {
+        Ok(Value::Bool(b))
+    }
+
+    fn serialize_i8(self, i: i8) -> Result<Value, Self::Error> {
+        Ok(Value::Integer(i.into()))
+    }
+
+    fn serialize_i16(self, i: i16) -> Result<Value, Self::Error> {
+        Ok(Value::Integer(i.into()))
+    }
+
+    fn serialize_i32(self, i: i32) -> Result<Value, Self::Error> {
+        Ok(Value::Integer(i.into()))
+    }
+
+    fn serialize_i64(self, i: i64) -> Result<Value, Self::Error> {
+        Ok(Value::Integer(i))
+    }
+
+    fn serialize_i128(self, i: i128) -> Result<Value, Self::Error> {
+        if let Ok(i) = i64::try_from(i) {
+            Ok(Value::Integer(i))
+        } else if let Ok(u) = u64::try_from(i) {
+            Ok(Value::Unsigned(u))
+        } else {
+            Err(ser::Error::custom("integer out of range"))
+        }
+    }
+
+    fn serialize_u8(self, u: u8) -> Result<Value, Self::Error> {
+        Ok(Value::Integer(u.into()))
+    }
+
+    fn serialize_u16(self, u: u16) -> Result<Value, Self::Error> {
+        Ok(Value::Integer(u.into()))
+    }
+
+    fn serialize_u32(self, u: u32) -> Result<Value, Self::Error> {
+        Ok(Value::Integer(u.into()))
+    }
+
+    fn serialize_u64(self, u: u64) -> Result<Value, Self::Error> {
+        Ok(i64::try_from(u).map_or(Value::Unsigned(u), Value::Integer))
+    }
+
+    fn serialize_u128(self, u: u128) -> Result<Value, Self::Error> {
+        u64::try_from(u)
+            .map_err(|_| ser::Error::custom("integer out of range"))
+            .and_then(|u| self.serialize_u64(u))
+    }
+
+    fn serialize_f32(self, f: f32) -> Result<Value, Self::Error> {
+        Ok(Value::Float(f.into()))
+    }
+
+    fn serialize_f64(self, f: f64) -> Result<Value, Self::Error> {
+        Ok(Value::Float(f))
+    }
+
+    fn serialize_char(self, c: char) -> Result<Value, Self::Error> {
+        Ok(Value::String(c.to_string()))
+    }
+
+    fn serialize_str(self, s: &str) -> Result<Value, Self::Error> {
+        Ok(Value::String(s.to_string()))
+    }
+
+    fn serialize_bytes(self, bytes: &[u8]) -> Result<Value, Self::Error> {
+        Ok(Value::Bytes(bytes.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Value, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Value, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Value, Self::Error> {
+        Ok(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        Ok(variant_object(variant, to_value(value)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, Self::Error> {
+        Ok(SerializeArray(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeObject, Self::Error> {
+        Ok(SerializeObject {
+            members: Map::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _: &'static str, len: usize) -> Result<SerializeObject, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+struct SerializeArray(Vec<Value>);
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Value::Array(self.0))
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeObject {
+    members: Map,
+    key: Option<String>,
+}
+
+impl SerializeObject {
+    /// Turns the one-member map TOML serializes a datetime as back into a
+    /// [`Value::Datetime`].
+    fn finish(self) -> Value {
+        match self.members.get(TOML_DATETIME_KEY) {
+            Some(Value::String(datetime)) if self.members.len() == 1 => {
+                Value::Datetime(datetime.clone())
+            }
+            _ => Value::Object(self.members),
+        }
+    }
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        let key = match to_value(key)? {
+            Value::String(s) => s,
+            Value::Bool(b) => b.to_string(),
+            Value::Integer(i) => i.to_string(),
+            Value::Unsigned(u) => u.to_string(),
+            Value::Float(f) => f.to_string(),
+            _ => {
+                return Err(ser::Error::custom(
+                    "key must be a string, number or boolean",
+                ));
+            }
+        };
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self.key.take().expect("serialize_key is called first");
+        self.members.insert(key, to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.members.insert(key.to_string(), to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+/// An enum variant with fields.
+struct SerializeVariant<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+/// Externally tags `value` with the name of its enum variant.
+fn variant_object(variant: &str, value: Value) -> Value {
+    let mut members = Map::new();
+    members.insert(variant.to_string(), value);
+    Value::Object(members)
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeArray> {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(variant_object(self.variant, Value::Array(self.inner.0)))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeObject> {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(variant_object(
+            self.variant,
+            Value::Object(self.inner.members),
+        ))
+    }
+}
+
+impl From<serde_json::Value> for Value {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(b),
+            serde_json::Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    Value::Integer(i)
+                } else if let Some(u) = n.as_u64() {
+                    Value::Unsigned(u)
+                } else {
+                    Value::Float(n.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            serde_json::Value::String(s) => Value::String(s),
+            serde_json::Value::Array(items) => {
+                Value::Array(items.into_iter().map(Value::from).collect())
+            }
+            serde_json::Value::Object(members) => Value::Object(
+                members
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Bytes become an array of numbers, datetimes a string, and non-finite
+/// floats null, as JSON has none of them.
+impl From<Value> for serde_json::Value {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(b) => serde_json::Value::Bool(b),
+            Value::Integer(i) => serde_json::Value::from(i),
+            Value::Unsigned(u) => serde_json::Value::from(u),
+            Value::Float(f) => serde_json::Value::from(f),
+            Value::String(s) | Value::Datetime(s) => serde_json::Value::String(s),
+            Value::Bytes(bytes) => serde_json::Value::from(bytes),
+            Value::Array(items) => {
+                serde_json::Value::Array(items.into_iter().map(serde_json::Value::from).collect())
+            }
+            Value::Object(members) => serde_json::Value::Object(
+                members
+                    .into_iter()
+                    .map(|(key, value)| (key, serde_json::Value::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Integer(i) => serializer.serialize_i64(*i),
+            Value::Unsigned(u) => serializer.serialize_u64(*u),
+            Value::Float(f) => serializer.serialize_f64(*f),
+            Value::String(s) | Value::Datetime(s) => serializer.serialize_str(s),
+            Value::Bytes(bytes) => serializer.serialize_bytes(bytes),
+            Value::Array(items) => serializer.collect_seq(items),
+            Value::Object(members) => serializer.collect_map(members),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<Value, E> {
+        Ok(Value::Bool(b))
+    }
+
+    fn visit_i64<E>(self, i: i64) -> Result<Value, E> {
+        Ok(Value::Integer(i))
+    }
+
+    fn visit_u64<E>(self, u: u64) -> Result<Value, E> {
+        Ok(i64::try_from(u).map_or(Value::Unsigned(u), Value::Integer))
+    }
+
+    fn visit_f64<E>(self, f: f64) -> Result<Value, E> {
+        Ok(Value::Float(f))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Value, E> {
+        Ok(Value::String(s.to_string()))
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<Value, E> {
+        Ok(Value::String(s))
+    }
+
+    fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Value, E> {
+        Ok(Value::Bytes(bytes.to_vec()))
+    }
+
+    fn visit_byte_buf<E>(self, bytes: Vec<u8>) -> Result<Value, E> {
+        Ok(Value::Bytes(bytes))
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut members = Map::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(Key(key)) = map.next_key()? {
+            if members.is_empty() && key == TOML_DATETIME_KEY {
+                return Ok(Value::Datetime(map.next_value()?));
+            }
+            let value = map.next_value()?;
+            members.insert(key, value);
+        }
+        Ok(Value::Object(members))
+    }
+}
+
+/// Deserializes a record from a [`Value`], as from the document it was read
+/// from: a datetime is handed over as TOML does, unless a string is asked
+/// for, and bytes are also a sequence.
+impl<'de> Deserializer<'de> for Value {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Integer(i) => visitor.visit_i64(i),
+            Value::Unsigned(u) => visitor.visit_u64(u),
+            Value::Float(f) => visitor.visit_f64(f),
+            Value::String(s) => visitor.visit_string(s),
+            Value::Bytes(bytes) => visitor.visit_byte_buf(bytes),
+            Value::Datetime(s) => visit_object(
+                Map::from_iter([(TOML_DATETIME_KEY.to_string(), Value::String(s))]),
+                visitor,
+            ),
+            Value::Array(items) => visit_array(items, visitor),
+            Value::Object(members) => visit_object(members, visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Value::Datetime(s) => visitor.visit_string(s),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Value::Bytes(bytes) => visit_array(
+                bytes
+                    .into_iter()
+                    .map(|b| Value::Integer(b.into()))
+                    .collect(),
+                visitor,
+            ),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self {
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Value::Object(members) if members.len() == 1 => visitor.visit_enum(
+                MapAccessDeserializer::new(MapDeserializer::new(members.into_iter())),
+            ),
+            value => Err(de::Error::invalid_type(
+                value.unexpected(),
+                &"an enum variant",
+            )),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char bytes byte_buf
+        unit unit_struct tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, serde_json::Error> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl Value {
+    fn unexpected(&self) -> de::Unexpected<'_> {
+        match self {
+            Value::Null => de::Unexpected::Unit,
+            Value::Bool(b) => de::Unexpected::Bool(*b),
+            Value::Integer(i) => de::Unexpected::Signed(*i),
+            Value::Unsigned(u) => de::Unexpected::Unsigned(*u),
+            Value::Float(f) => de::Unexpected::Float(*f),
+            Value::String(s) | Value::Datetime(s) => de::Unexpected::Str(s),
+            Value::Bytes(bytes) => de::Unexpected::Bytes(bytes),
+            Value::Array(_) => de::Unexpected::Seq,
+            Value::Object(_) => de::Unexpected::Map,
+        }
+    }
+}
+
+fn visit_array<'de, V: Visitor<'de>>(
+    items: Vec<Value>,
+    visitor: V,
+) -> Result<V::Value, serde_json::Error> {
+    let mut items = SeqDeserializer::new(items.into_iter());
+    let value = visitor.visit_seq(&mut items)?;
+    items.end()?;
+    Ok(value)
+}
+
+fn visit_object<'de, V: Visitor<'de>>(
+    members: Map,
+    visitor: V,
+) -> Result<V::Value, serde_json::Error> {
+    let mut members = MapDeserializer::new(members.into_iter());
+    let value = visitor.visit_map(&mut members)?;
+    members.end()?;
+    Ok(value)
+}
+
+/// An object key. Keys that are not strings, such as the integer keys
+/// YAML and MessagePack allow, are converted to their text form.
+struct Key(String);
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(KeyVisitor)
+    }
+}
+
+struct KeyVisitor;
+
+impl Visitor<'_> for KeyVisitor {
+    type Value = Key;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a string, number or boolean key")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<Key, E> {
+        Ok(Key(b.to_string()))
+    }
+
+    fn visit_i64<E>(self, i: i64) -> Result<Key, E> {
+        Ok(Key(i.to_string()))
+    }
+
+    fn visit_u64<E>(self, u: u64) -> Result<Key, E> {
+        Ok(Key(u.to_string()))
+    }
+
+    fn visit_f64<E>(self, f: f64) -> Result<Key, E> {
+        Ok(Key(f.to_string()))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Key, E> {
+        Ok(Key(s.to_string()))
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<Key, E> {
+        Ok(Key(s))
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Key, E> {
+        String::from_utf8(bytes.to_vec())
+            .map(Key)
+            .map_err(|_| E::invalid_value(de::Unexpected::Bytes(bytes), &self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_conversion() {
+        let json = serde_json::json!({"id": 1, "big": u64::MAX, "ratio": 0.5, "tags": ["a", null]});
+        let value = Value::from(json.clone());
+        assert_eq!(value.get("id"), Some(&Value::Integer(1)));
+        assert_eq!(value.get("big"), Some(&Value::Unsigned(u64::MAX)));
+        assert_eq!(value.get("ratio"), Some(&Value::Float(0.5)));
+        assert_eq!(serde_json::Value::from(value), json);
+
+        let bytes = serde_json::Value::from(Value::Bytes(vec![1, 2]));
+        assert_eq!(bytes, serde_json::json!([1, 2]));
+    }
+
+    #[test]
+    fn test_keeps_member_order() {
+        let value: Value =
+            serde_json::from_str(r#"{"z": 1, "a": {"y": true, "b": null}}"#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"z":1,"a":{"y":true,"b":null}}"#
+        );
+    }
+
+    #[test]
+    fn test_to_value() {
+        #[derive(Serialize)]
+        enum Shape {
+            Point,
+            Circle(f32),
+            Rect { w: u8, h: u8 },
+        }
+
+        #[derive(Serialize)]
+        struct Record<'a> {
+            z: u64,
+            #[serde(with = "serde_bytes_slice")]
+            blob: &'a [u8],
+            shapes: Vec<Shape>,
+            a: Option<char>,
+        }
+
+        mod serde_bytes_slice {
+            pub fn serialize<S: serde::Serializer>(b: &[u8], s: S) -> Result<S::Ok, S::Error> {
+                s.serialize_bytes(b)
+            }
+        }
+
+        let record = Record {
+            z: u64::MAX,
+            blob: &[0, 255],
+            shapes: vec![Shape::Point, Shape::Circle(0.5), Shape::Rect { w: 1, h: 2 }],
+            a: None,
+        };
+        let value = to_value(&record).unwrap();
+        let object = value.as_object().unwrap();
+        assert_eq!(
+            object.keys().collect::<Vec<_>>(),
+            ["z", "blob", "shapes", "a"]
+        );
+        assert_eq!(value.get("z"), Some(&Value::Unsigned(u64::MAX)));
+        assert_eq!(value.get("blob"), Some(&Value::Bytes(vec![0, 255])));
+        assert_eq!(value.get("a"), Some(&Value::Null));
+        assert_eq!(
+            serde_json::to_string(value.get("shapes").unwrap()).unwrap(),
+            r#"["Point",{"Circle":0.5},{"Rect":{"w":1,"h":2}}]"#
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_datetime() {
+        let value: Value = toml::from_str("when = 1979-05-27T07:32:00Z\n").unwrap();
+        assert_eq!(
+            value.get("when"),
+            Some(&Value::Datetime("1979-05-27T07:32:00Z".to_string()))
+        );
+        let toml = toml::to_string(&TomlValue(&value)).unwrap();
+        assert_eq!(toml, "when = 1979-05-27T07:32:00Z\n");
+
+        let datetime: toml::value::Datetime = "1979-05-27T07:32:00Z".parse().unwrap();
+        assert_eq!(
+            to_value(&datetime).unwrap(),
+            Value::Datetime("1979-05-27T07:32:00Z".to_string())
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_keeps_member_order() {
+        let mut members = Map::new();
+        members.insert("z".to_string(), Value::Integer(1));
+        members.insert("skipped".to_string(), Value::Null);
+        members.insert("a".to_string(), Value::String("x".to_string()));
+        let toml = toml::to_string(&TomlValue(&Value::Object(members))).unwrap();
+        assert_eq!(toml, "z = 1\na = \"x\"\n");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_read_keeps_member_order() {
+        let value: Value = toml::from_str("z = 1\na = \"x\"\n\n[m]\ny = 2\nb = 3\n").unwrap();
+        let Value::Object(members) = &value else {
+            panic!("expected an object");
+        };
+        assert_eq!(members.keys().collect::<Vec<_>>(), ["z", "a", "m"]);
+        let Some(Value::Object(table)) = value.get("m") else {
+            panic!("expected a table");
+        };
+        assert_eq!(table.keys().collect::<Vec<_>>(), ["y", "b"]);
+    }
+
+    #[cfg(feature = "messagepack")]
+    #[test]
+    fn test_messagepack_bytes() {
+        let mut members = Map::new();
+        members.insert("blob".to_string(), Value::Bytes(vec![0, 255]));
+        let value = Value::Object(members);
+        let encoded = rmp_serde::to_vec_named(&value).unwrap();
+        let decoded: Value = rmp_serde::from_slice(&encoded).unwrap();
+        assert_eq!(decoded, value);
+    }
+}
@@ -1,19 +1,28 @@
 use std::{
     fs::File,
     io::{BufWriter, Write},
+    marker::PhantomData,
     path::Path,
 };
 
 use serde::Serialize;
 
+#[cfg(feature = "toml")]
+use crate::value::TomlValue;
 use crate::{
     Error, backend,
-    types::{DataFormat, resolve_format},
+    compression::Encoder,
+    options::WriteOptions,
+    pointer,
+    types::{Compression, DataFormat, resolve_format},
+    value::{Value, to_value},
 };
 
-fn create_buf_writer(path: impl AsRef<Path>) -> Result<BufWriter<File>, Error> {
-    let file = File::create(path.as_ref())?;
-    Ok(BufWriter::new(file))
+/// Creates a file for writing, compressing it when its extension names a
+/// compression codec. The returned encoder must be finished.
+pub(crate) fn create_writer(path: &Path) -> Result<Encoder<BufWriter<File>>, Error> {
+    let file = File::create(path)?;
+    Encoder::new(BufWriter::new(file), Compression::from_path(path))
 }
 
 /// Writes a single record to a writer in the specified data format.
@@ -56,16 +65,35 @@ pub fn write_record_to_writer<T: Serialize>(
     writer: impl Write,
     record: &T,
     data_format: DataFormat,
+) -> Result<(), Error> {
+    write_record_to_writer_with_options(writer, record, data_format, &WriteOptions::default())
+}
+
+/// Writes a single record to a writer with backend settings from `options`.
+///
+/// See [`write_record_to_writer`] for the supported formats.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for single records,
+/// or if serialization fails.
+pub fn write_record_to_writer_with_options<T: Serialize>(
+    writer: impl Write,
+    record: &T,
+    data_format: DataFormat,
+    options: &WriteOptions,
 ) -> Result<(), Error> {
     match data_format {
         DataFormat::Auto => Err(Error::AutoNotSupported),
-        DataFormat::Json => backend::json::write(writer, record),
+        DataFormat::Json => backend::json::write(writer, record, &options.json),
         #[cfg(feature = "yaml")]
-        DataFormat::Yaml => backend::yaml::write(writer, record),
+        DataFormat::Yaml => backend::yaml::write(writer, record, &options.yaml),
         #[cfg(feature = "messagepack")]
-        DataFormat::MessagePack => backend::messagepack::write(writer, record),
+        DataFormat::MessagePack => {
+            backend::messagepack::write(writer, record, &options.messagepack)
+        }
         #[cfg(feature = "toml")]
-        DataFormat::Toml => backend::toml::write(writer, record),
+        DataFormat::Toml => backend::toml::write(writer, record, &options.toml),
         _ => Err(Error::UnsupportedFormat(data_format)),
     }
 }
@@ -79,8 +107,12 @@ pub fn write_record_to_writer<T: Serialize>(
 /// - JSON (always available, as an array)
 /// - JSON Lines (always available)
 /// - CSV (requires `csv` feature)
-/// - YAML (requires `yaml` feature, as an array)
+/// - TSV (requires `csv` feature)
+/// - YAML (requires `yaml` feature, as an array, or as a stream of documents
+///   with `yaml.documents`)
 /// - MessagePack (requires `messagepack` feature, as an array)
+/// - TOML (requires `toml` feature, as an array of tables under `toml.key`,
+///   `[[record]]` by default)
 ///
 /// # Errors
 ///
@@ -110,29 +142,66 @@ pub fn write_record_to_writer<T: Serialize>(
 /// let json = String::from_utf8(buffer).unwrap();
 /// assert!(json.contains("Alice") && json.contains("Bob"));
 /// ```
+pub fn write_records_to_writer<'a, T: Serialize + 'a>(
+    writer: impl Write,
+    records: impl IntoIterator<Item = &'a T>,
+    data_format: DataFormat,
+) -> Result<(), Error> {
+    write_records_to_writer_with_options(writer, records, data_format, &WriteOptions::default())
+}
+
+/// Writes multiple records to a writer with backend settings from `options`.
 ///
-/// # Note
+/// See [`write_records_to_writer`] for the supported formats.
 ///
-/// TOML is not supported for multi-record operations. The TOML specification
-/// requires the root to be a table, so a bare array of records is not
-/// representable. Use the single-record TOML functions instead.
-pub fn write_records_to_writer<'a, T: Serialize + 'a>(
+/// # Errors
+///
+/// Returns an error if the data format is not supported for multiple records,
+/// or if serialization fails.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "csv")] {
+/// use serde::Serialize;
+/// use serdeio::{write_records_to_writer_with_options, DataFormat, WriteOptions};
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// let users = vec![User { name: "Alice".to_string(), age: 30 }];
+/// let mut options = WriteOptions::default();
+/// options.csv.has_headers = false;
+/// let mut buffer = Vec::new();
+/// write_records_to_writer_with_options(&mut buffer, &users, DataFormat::Csv, &options).unwrap();
+/// assert_eq!(String::from_utf8(buffer).unwrap(), "Alice,30\n");
+/// # }
+/// ```
+pub fn write_records_to_writer_with_options<'a, T: Serialize + 'a>(
     writer: impl Write,
     records: impl IntoIterator<Item = &'a T>,
     data_format: DataFormat,
+    options: &WriteOptions,
 ) -> Result<(), Error> {
     match data_format {
         DataFormat::Auto => Err(Error::AutoNotSupported),
-        DataFormat::Json => backend::json::write(writer, &records.into_iter().collect::<Vec<_>>()),
+        DataFormat::Json => backend::json::write_seq(writer, records, &options.json),
         DataFormat::JsonLines => backend::jsonlines::write(writer, records),
         #[cfg(feature = "csv")]
-        DataFormat::Csv => backend::csv::write(writer, records),
+        DataFormat::Csv => backend::csv::write(writer, records, &options.csv),
+        #[cfg(feature = "csv")]
+        DataFormat::Tsv => backend::csv::write_tsv(writer, records, &options.csv),
         #[cfg(feature = "yaml")]
-        DataFormat::Yaml => backend::yaml::write(writer, &records.into_iter().collect::<Vec<_>>()),
+        DataFormat::Yaml => backend::yaml::write_seq(writer, records, &options.yaml),
         #[cfg(feature = "messagepack")]
         DataFormat::MessagePack => {
-            backend::messagepack::write(writer, &records.into_iter().collect::<Vec<_>>())
+            backend::messagepack::write_seq(writer, records, &options.messagepack)
         }
+        #[cfg(feature = "toml")]
+        DataFormat::Toml => backend::toml::write_seq(writer, records, &options.toml),
         #[allow(unreachable_patterns)]
         _ => Err(Error::UnsupportedFormat(data_format)),
     }
@@ -142,6 +211,8 @@ pub fn write_records_to_writer<'a, T: Serialize + 'a>(
 ///
 /// The data format is automatically inferred from the file extension.
 /// If `data_format` is set to a format other than `Auto`, that format is used as-is and the file extension is ignored.
+/// A trailing compression extension (`.gz`, `.zst`, `.bz2`, `.xz`) compresses
+/// the output transparently when the matching feature is enabled.
 ///
 /// # Supported Formats
 ///
@@ -174,25 +245,51 @@ pub fn write_record_to_file<T: Serialize>(
     path: impl AsRef<Path>,
     record: &T,
     data_format: DataFormat,
+) -> Result<(), Error> {
+    write_record_to_file_with_options(path, record, data_format, &WriteOptions::default())
+}
+
+/// Writes a single record to a file with backend settings from `options`.
+///
+/// See [`write_record_to_file`] for how the format is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created, the extension is unknown,
+/// or serialization fails.
+pub fn write_record_to_file_with_options<T: Serialize>(
+    path: impl AsRef<Path>,
+    record: &T,
+    data_format: DataFormat,
+    options: &WriteOptions,
 ) -> Result<(), Error> {
     let path = path.as_ref();
-    let final_format = resolve_format(path, data_format)?;
-    let writer = create_buf_writer(path)?;
-    write_record_to_writer(writer, record, final_format)
+    let write = || {
+        let final_format = resolve_format(path, data_format)?;
+        let mut writer = create_writer(path)?;
+        write_record_to_writer_with_options(&mut writer, record, final_format, options)?;
+        writer.finish()?;
+        Ok(())
+    };
+    write().map_err(|e: Error| e.with_path(path))
 }
 
 /// Writes multiple records to a file in the data format inferred from the file extension.
 ///
 /// The data format is automatically inferred from the file extension.
 /// If `data_format` is set to a format other than `Auto`, that format is used as-is and the file extension is ignored.
+/// A trailing compression extension (`.gz`, `.zst`, `.bz2`, `.xz`) compresses
+/// the output transparently when the matching feature is enabled.
 ///
 /// # Supported Formats
 ///
 /// - JSON (.json)
 /// - JSON Lines (.jsonl, .jsl)
 /// - CSV (.csv) - requires `csv` feature
+/// - TSV (.tsv, .tab) - requires `csv` feature
 /// - YAML (.yaml, .yml) - requires `yaml` feature
 /// - MessagePack (.msgpack, .mpack, .mpk) - requires `messagepack` feature
+/// - TOML (.toml) - requires `toml` feature
 ///
 /// # Errors
 ///
@@ -221,11 +318,616 @@ pub fn write_records_to_file<'a, T: Serialize + 'a, I: IntoIterator<Item = &'a T
     path: impl AsRef<Path>,
     records: I,
     data_format: DataFormat,
+) -> Result<(), Error> {
+    write_records_to_file_with_options(path, records, data_format, &WriteOptions::default())
+}
+
+/// Writes multiple records to a file with backend settings from `options`.
+///
+/// See [`write_records_to_file`] for how the format is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created, the extension is unknown,
+/// or serialization fails.
+pub fn write_records_to_file_with_options<'a, T: Serialize + 'a, I: IntoIterator<Item = &'a T>>(
+    path: impl AsRef<Path>,
+    records: I,
+    data_format: DataFormat,
+    options: &WriteOptions,
 ) -> Result<(), Error> {
     let path = path.as_ref();
-    let final_format = resolve_format(path, data_format)?;
-    let writer = create_buf_writer(path)?;
-    write_records_to_writer(writer, records, final_format)
+    let write = || {
+        let final_format = resolve_format(path, data_format)?;
+        let mut writer = create_writer(path)?;
+        write_records_to_writer_with_options(&mut writer, records, final_format, options)?;
+        writer.finish()?;
+        Ok(())
+    };
+    write().map_err(|e: Error| e.with_path(path))
+}
+
+/// Places `records`, as a sequence, at `pointer` within `envelope`.
+fn envelop<'a, T: Serialize + 'a>(
+    envelope: &impl Serialize,
+    records: impl IntoIterator<Item = &'a T>,
+    pointer: &str,
+) -> Result<Value, Error> {
+    let records = records
+        .into_iter()
+        .map(to_value)
+        .collect::<Result<_, _>>()?;
+    let mut document = to_value(envelope)?;
+    pointer::place(&mut document, pointer, Value::Array(records))?;
+    Ok(document)
+}
+
+/// Writes an envelope document as a single record.
+fn write_document(
+    writer: impl Write,
+    document: &Value,
+    data_format: DataFormat,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    #[cfg(feature = "toml")]
+    if data_format == DataFormat::Toml {
+        return write_record_to_writer_with_options(
+            writer,
+            &TomlValue(document),
+            data_format,
+            options,
+        );
+    }
+    write_record_to_writer_with_options(writer, document, data_format, options)
+}
+
+/// Writes multiple records nested inside an envelope document to a writer.
+///
+/// The records are placed, as a sequence, at `pointer` within `envelope`, and
+/// the resulting document is written as with [`write_record_to_writer`].
+/// `pointer` is a JSON Pointer (RFC 6901) such as `/data/items`. Object members
+/// it names are created when missing, `-` appends to an array, and the empty
+/// pointer makes the sequence the whole document.
+///
+/// # Supported Formats
+///
+/// - JSON (always available)
+/// - YAML (requires `yaml` feature)
+/// - MessagePack (requires `messagepack` feature)
+/// - TOML (requires `toml` feature)
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for single records,
+/// [`Error::PointerNotFound`] if `pointer` cannot be placed within
+/// `envelope`, or an error if serialization fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde::Serialize;
+/// use serde_json::json;
+/// use serdeio::{write_records_at_pointer_to_writer, DataFormat};
+///
+/// #[derive(Serialize)]
+/// struct Item {
+///     id: u32,
+/// }
+///
+/// let items = vec![Item { id: 1 }, Item { id: 2 }];
+/// let envelope = json!({"meta": {"page": 1}});
+/// let mut buffer = Vec::new();
+/// write_records_at_pointer_to_writer(&mut buffer, &items, DataFormat::Json, &envelope, "/data/items")
+///     .unwrap();
+/// let json = String::from_utf8(buffer).unwrap();
+/// assert!(json.contains(r#""data":{"items":[{"id":1},{"id":2}]}"#));
+/// ```
+pub fn write_records_at_pointer_to_writer<'a, T: Serialize + 'a>(
+    writer: impl Write,
+    records: impl IntoIterator<Item = &'a T>,
+    data_format: DataFormat,
+    envelope: &impl Serialize,
+    pointer: &str,
+) -> Result<(), Error> {
+    write_records_at_pointer_to_writer_with_options(
+        writer,
+        records,
+        data_format,
+        envelope,
+        pointer,
+        &WriteOptions::default(),
+    )
+}
+
+/// Writes multiple records nested inside an envelope document to a writer
+/// with backend settings from `options`.
+///
+/// See [`write_records_at_pointer_to_writer`] for how `pointer` is used.
+///
+/// # Errors
+///
+/// Returns an error if the data format is not supported for single records,
+/// [`Error::PointerNotFound`] if `pointer` cannot be placed within
+/// `envelope`, or an error if serialization fails.
+pub fn write_records_at_pointer_to_writer_with_options<'a, T: Serialize + 'a>(
+    writer: impl Write,
+    records: impl IntoIterator<Item = &'a T>,
+    data_format: DataFormat,
+    envelope: &impl Serialize,
+    pointer: &str,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let document = envelop(envelope, records, pointer)?;
+    write_document(writer, &document, data_format, options)
+}
+
+/// Writes multiple records nested inside an envelope document to a file.
+///
+/// See [`write_record_to_file`] for how the format is resolved and
+/// [`write_records_at_pointer_to_writer`] for how `pointer` is used.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created, the extension is unknown,
+/// `pointer` cannot be placed within `envelope`, or serialization fails.
+pub fn write_records_at_pointer_to_file<'a, T: Serialize + 'a>(
+    path: impl AsRef<Path>,
+    records: impl IntoIterator<Item = &'a T>,
+    data_format: DataFormat,
+    envelope: &impl Serialize,
+    pointer: &str,
+) -> Result<(), Error> {
+    write_records_at_pointer_to_file_with_options(
+        path,
+        records,
+        data_format,
+        envelope,
+        pointer,
+        &WriteOptions::default(),
+    )
+}
+
+/// Writes multiple records nested inside an envelope document to a file with
+/// backend settings from `options`.
+///
+/// See [`write_records_at_pointer_to_file`] for how the format is resolved
+/// and `pointer` is used.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created, the extension is unknown,
+/// `pointer` cannot be placed within `envelope`, or serialization fails.
+pub fn write_records_at_pointer_to_file_with_options<'a, T: Serialize + 'a>(
+    path: impl AsRef<Path>,
+    records: impl IntoIterator<Item = &'a T>,
+    data_format: DataFormat,
+    envelope: &impl Serialize,
+    pointer: &str,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let path = path.as_ref();
+    let write = || {
+        let final_format = resolve_format(path, data_format)?;
+        let document = envelop(envelope, records, pointer)?;
+        let mut writer = create_writer(path)?;
+        write_document(&mut writer, &document, final_format, options)?;
+        writer.finish()?;
+        Ok(())
+    };
+    write().map_err(|e: Error| e.with_path(path))
+}
+
+/// Writes a [`Value`] to a writer in the specified data format.
+///
+/// Formats that only hold multiple records, and TOML, whose root must be a
+/// table, write the elements of an array as the records; any other value is
+/// written as a single record. Other formats write the value as it is. CSV and
+/// TSV spread nested values over dotted columns, as with
+/// [`crate::CsvWriteOptions::flatten`].
+///
+/// # Supported Formats
+///
+/// - JSON (always available)
+/// - JSON Lines (always available)
+/// - CSV (requires `csv` feature)
+/// - TSV (requires `csv` feature)
+/// - YAML (requires `yaml` feature)
+/// - MessagePack (requires `messagepack` feature)
+/// - TOML (requires `toml` feature)
+///
+/// # Errors
+///
+/// Returns an error if the data format is `Auto`, or if the value cannot be
+/// represented in the data format, such as a null in TOML.
+///
+/// # Examples
+///
+/// ```rust
+/// use serdeio::{write_value_to_writer, DataFormat, Value};
+///
+/// let value = Value::Array(vec![Value::Integer(1), Value::String("two".to_string())]);
+/// let mut buffer = Vec::new();
+/// write_value_to_writer(&mut buffer, &value, DataFormat::JsonLines).unwrap();
+/// assert_eq!(String::from_utf8(buffer).unwrap(), "1\n\"two\"\n");
+/// ```
+pub fn write_value_to_writer(
+    writer: impl Write,
+    value: &Value,
+    data_format: DataFormat,
+) -> Result<(), Error> {
+    write_value_to_writer_with_options(writer, value, data_format, &WriteOptions::default())
+}
+
+/// Writes a [`Value`] to a writer with backend settings from `options`.
+///
+/// See [`write_value_to_writer`] for the supported formats and how the value
+/// is laid out. Records with nested members are only written to CSV and TSV
+/// with `csv.flatten` set.
+///
+/// # Errors
+///
+/// Returns an error if the data format is `Auto`, or if the value cannot be
+/// represented in the data format, such as a null in TOML.
+pub fn write_value_to_writer_with_options(
+    writer: impl Write,
+    value: &Value,
+    data_format: DataFormat,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let records = match value {
+        Value::Array(records) => records.as_slice(),
+        record => std::slice::from_ref(record),
+    };
+    #[cfg(feature = "csv")]
+    if matches!(data_format, DataFormat::Csv | DataFormat::Tsv) {
+        for (index, record) in records.iter().enumerate() {
+            check_flat(record, index, options).map_err(|e| e.with_format(data_format))?;
+        }
+    }
+    let options = value_options(options);
+    match data_format {
+        DataFormat::JsonLines => {
+            write_records_to_writer_with_options(writer, records, data_format, &options)
+        }
+        #[cfg(feature = "csv")]
+        DataFormat::Csv | DataFormat::Tsv => {
+            write_records_to_writer_with_options(writer, records, data_format, &options)
+        }
+        #[cfg(feature = "toml")]
+        DataFormat::Toml => match value {
+            Value::Array(records) => {
+                let records: Vec<_> = records.iter().map(TomlValue).collect();
+                write_records_to_writer_with_options(writer, &records, data_format, &options)
+            }
+            record => write_record_to_writer_with_options(
+                writer,
+                &TomlValue(record),
+                data_format,
+                &options,
+            ),
+        },
+        _ => write_record_to_writer_with_options(writer, value, data_format, &options),
+    }
+}
+
+/// Adapts write settings to [`Value`] records. The CSV backend cannot write
+/// maps, so objects always go through its flattening writer; without
+/// `csv.flatten`, [`check_flat`] keeps nested members out of it.
+pub(crate) fn value_options(options: &WriteOptions) -> WriteOptions {
+    #[allow(unused_mut)]
+    let mut options = options.clone();
+    #[cfg(feature = "csv")]
+    {
+        options.csv.flatten = true;
+    }
+    options
+}
+
+/// Rejects a CSV or TSV record with a nested member, which only `csv.flatten`
+/// writes, into dotted columns.
+#[cfg(feature = "csv")]
+pub(crate) fn check_flat(
+    record: &Value,
+    index: usize,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let Value::Object(members) = record else {
+        return Ok(());
+    };
+    if options.csv.flatten {
+        return Ok(());
+    }
+    let nested = members
+        .iter()
+        .find(|(_, member)| matches!(member, Value::Object(_) | Value::Array(_) | Value::Bytes(_)));
+    match nested {
+        Some((column, _)) => {
+            let message = format!(
+                "column `{column}` holds a nested value; set `csv.flatten` to write it in \
+                 dotted columns"
+            );
+            Err(Error::Flatten(serde::de::Error::custom(message))
+                .with_record(index)
+                .with_field(column.clone()))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Writes a [`Value`] to a file in the data format inferred from the file
+/// extension.
+///
+/// See [`write_record_to_file`] for how the format is resolved and
+/// [`write_value_to_writer`] for how the value is laid out.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created, the extension is unknown,
+/// or the value cannot be represented in the data format.
+pub fn write_value_to_file(
+    path: impl AsRef<Path>,
+    value: &Value,
+    data_format: DataFormat,
+) -> Result<(), Error> {
+    write_value_to_file_with_options(path, value, data_format, &WriteOptions::default())
+}
+
+/// Writes a [`Value`] to a file with backend settings from `options`.
+///
+/// See [`write_value_to_file`] for how the format is resolved.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created, the extension is unknown,
+/// or the value cannot be represented in the data format.
+pub fn write_value_to_file_with_options(
+    path: impl AsRef<Path>,
+    value: &Value,
+    data_format: DataFormat,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    let path = path.as_ref();
+    let write = || {
+        let final_format = resolve_format(path, data_format)?;
+        let mut writer = create_writer(path)?;
+        write_value_to_writer_with_options(&mut writer, value, final_format, options)?;
+        writer.finish()?;
+        Ok(())
+    };
+    write().map_err(|e: Error| e.with_path(path))
+}
+
+enum Sink<W: Write> {
+    Json(backend::json::SeqWriter<W>),
+    JsonLines(backend::jsonlines::SeqWriter<W>),
+    #[cfg(feature = "csv")]
+    Csv(Box<backend::csv::SeqWriter<W>>),
+    #[cfg(feature = "yaml")]
+    Yaml(backend::yaml::SeqWriter<W>),
+    #[cfg(feature = "messagepack")]
+    MessagePack(backend::messagepack::SeqWriter<W>),
+    #[cfg(feature = "toml")]
+    Toml(backend::toml::SeqWriter<W>),
+}
+
+/// Stateful sink that writes records one at a time in a multi-record format.
+///
+/// The writer emits the framing of the format incrementally: the brackets and
+/// commas of a JSON array, the header row of a CSV file, one line per record in
+/// JSON Lines, and so on. [`RecordWriter::finish`] must be called once all
+/// records are written to close the framing and flush the underlying writer.
+///
+/// # Supported Formats
+///
+/// - JSON (always available, as an array)
+/// - JSON Lines (always available)
+/// - CSV (requires `csv` feature)
+/// - TSV (requires `csv` feature)
+/// - YAML (requires `yaml` feature, as an array, or as a stream of documents
+///   with `yaml.documents`)
+/// - MessagePack (requires `messagepack` feature, as an array; records are
+///   buffered in encoded form until `finish` because the array is length-prefixed)
+/// - TOML (requires `toml` feature, as an array of tables)
+///
+/// # Examples
+///
+/// ```rust
+/// use serde::Serialize;
+/// use serdeio::{DataFormat, RecordWriter};
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// let mut writer = RecordWriter::new(Vec::new(), DataFormat::Json).unwrap();
+/// writer.write(&User { name: "Alice".to_string(), age: 30 }).unwrap();
+/// writer.write(&User { name: "Bob".to_string(), age: 25 }).unwrap();
+/// let buffer = writer.finish().unwrap();
+/// assert_eq!(
+///     String::from_utf8(buffer).unwrap(),
+///     r#"[{"name":"Alice","age":30},{"name":"Bob","age":25}]"#
+/// );
+/// ```
+pub struct RecordWriter<W: Write, T> {
+    sink: Sink<Encoder<W>>,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<W: Write, T: Serialize> RecordWriter<W, T> {
+    /// Creates a record writer that writes to `writer` in the specified data format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data format is not supported for multiple records,
+    /// or if writing the opening framing fails.
+    pub fn new(writer: W, data_format: DataFormat) -> Result<Self, Error> {
+        Self::with_options(writer, data_format, &WriteOptions::default())
+    }
+
+    /// Creates a record writer with backend settings from `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data format is not supported for multiple records,
+    /// or if writing the opening framing fails.
+    pub fn with_options(
+        writer: W,
+        data_format: DataFormat,
+        options: &WriteOptions,
+    ) -> Result<Self, Error> {
+        Self::with_encoder(Encoder::new(writer, None)?, data_format, options)
+    }
+
+    fn with_encoder(
+        writer: Encoder<W>,
+        data_format: DataFormat,
+        options: &WriteOptions,
+    ) -> Result<Self, Error> {
+        let sink = match data_format {
+            DataFormat::Auto => return Err(Error::AutoNotSupported),
+            DataFormat::Json => Sink::Json(backend::json::SeqWriter::new(writer, &options.json)?),
+            DataFormat::JsonLines => Sink::JsonLines(backend::jsonlines::SeqWriter::new(writer)),
+            #[cfg(feature = "csv")]
+            DataFormat::Csv => {
+                Sink::Csv(Box::new(backend::csv::SeqWriter::new(writer, &options.csv)))
+            }
+            #[cfg(feature = "csv")]
+            DataFormat::Tsv => {
+                Sink::Csv(Box::new(backend::csv::SeqWriter::tsv(writer, &options.csv)))
+            }
+            #[cfg(feature = "yaml")]
+            DataFormat::Yaml => Sink::Yaml(backend::yaml::SeqWriter::new(writer, &options.yaml)),
+            #[cfg(feature = "messagepack")]
+            DataFormat::MessagePack => Sink::MessagePack(backend::messagepack::SeqWriter::new(
+                writer,
+                &options.messagepack,
+            )),
+            #[cfg(feature = "toml")]
+            DataFormat::Toml => Sink::Toml(backend::toml::SeqWriter::new(writer, &options.toml)),
+            #[allow(unreachable_patterns)]
+            _ => return Err(Error::UnsupportedFormat(data_format)),
+        };
+        Ok(Self {
+            sink,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Writes a single record.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization or writing fails.
+    pub fn write(&mut self, record: &T) -> Result<(), Error> {
+        match &mut self.sink {
+            Sink::Json(writer) => writer.write(record),
+            Sink::JsonLines(writer) => writer.write(record),
+            #[cfg(feature = "csv")]
+            Sink::Csv(writer) => writer.write(record),
+            #[cfg(feature = "yaml")]
+            Sink::Yaml(writer) => writer.write(record),
+            #[cfg(feature = "messagepack")]
+            Sink::MessagePack(writer) => writer.write(record),
+            #[cfg(feature = "toml")]
+            Sink::Toml(writer) => writer.write(record),
+        }
+    }
+
+    /// Writes every record yielded by `records`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first serialization or write error; records before it have
+    /// already been written.
+    pub fn write_all<'a>(&mut self, records: impl IntoIterator<Item = &'a T>) -> Result<(), Error>
+    where
+        T: 'a,
+    {
+        for record in records {
+            self.write(record)?;
+        }
+        Ok(())
+    }
+
+    /// Closes the framing of the format, flushes, and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing the closing framing or flushing fails.
+    pub fn finish(self) -> Result<W, Error> {
+        let writer = match self.sink {
+            Sink::Json(writer) => writer.finish(),
+            Sink::JsonLines(writer) => writer.finish(),
+            #[cfg(feature = "csv")]
+            Sink::Csv(writer) => writer.finish(),
+            #[cfg(feature = "yaml")]
+            Sink::Yaml(writer) => writer.finish(),
+            #[cfg(feature = "messagepack")]
+            Sink::MessagePack(writer) => writer.finish(),
+            #[cfg(feature = "toml")]
+            Sink::Toml(writer) => writer.finish(),
+        }?;
+        writer.finish()
+    }
+}
+
+impl<T: Serialize> RecordWriter<BufWriter<File>, T> {
+    /// Creates a record writer that writes to a newly created file through a
+    /// buffer.
+    ///
+    /// The data format is automatically inferred from the file extension.
+    /// If `data_format` is set to a format other than `Auto`, that format is used as-is and the file extension is ignored.
+    /// A trailing compression extension (`.gz`, `.zst`, `.bz2`, `.xz`) compresses
+    /// the output transparently when the matching feature is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created, the extension is unknown,
+    /// or the data format is not supported for multiple records.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use serde::Serialize;
+    /// use serdeio::{DataFormat, RecordWriter};
+    ///
+    /// #[derive(Serialize)]
+    /// struct User {
+    ///     name: String,
+    ///     age: u32,
+    /// }
+    ///
+    /// let mut writer = RecordWriter::create("users.csv", DataFormat::Auto).unwrap();
+    /// for age in 20..30 {
+    ///     writer.write(&User { name: format!("user{age}"), age }).unwrap();
+    /// }
+    /// writer.finish().unwrap();
+    /// ```
+    pub fn create(path: impl AsRef<Path>, data_format: DataFormat) -> Result<Self, Error> {
+        Self::create_with_options(path, data_format, &WriteOptions::default())
+    }
+
+    /// Creates a record writer for a newly created file with backend settings
+    /// from `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created, the extension is unknown,
+    /// or the data format is not supported for multiple records.
+    pub fn create_with_options(
+        path: impl AsRef<Path>,
+        data_format: DataFormat,
+        options: &WriteOptions,
+    ) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let create = || {
+            let final_format = resolve_format(path, data_format)?;
+            Self::with_encoder(create_writer(path)?, final_format, options)
+        };
+        create().map_err(|e| e.with_path(path))
+    }
 }
 
 #[cfg(test)]
@@ -266,4 +968,300 @@ mod tests {
         let result = write_records_to_writer(&mut buffer, &records, DataFormat::Auto);
         assert!(matches!(result, Err(Error::AutoNotSupported)));
     }
+
+    #[test]
+    fn test_record_writer_matches_write_records() {
+        let records = vec![
+            TestRecord {
+                name: "test1".to_string(),
+                value: 1,
+            },
+            TestRecord {
+                name: "test2".to_string(),
+                value: 2,
+            },
+        ];
+        #[allow(unused_mut)]
+        let mut formats = vec![DataFormat::Json, DataFormat::JsonLines];
+        #[cfg(feature = "csv")]
+        formats.extend([DataFormat::Csv, DataFormat::Tsv]);
+        #[cfg(feature = "yaml")]
+        formats.push(DataFormat::Yaml);
+        #[cfg(feature = "messagepack")]
+        formats.push(DataFormat::MessagePack);
+        #[cfg(feature = "toml")]
+        formats.push(DataFormat::Toml);
+
+        for data_format in formats {
+            let mut expected = Vec::new();
+            write_records_to_writer(&mut expected, &records, data_format).unwrap();
+
+            let mut writer = RecordWriter::new(Vec::new(), data_format).unwrap();
+            writer.write(&records[0]).unwrap();
+            writer.write_all(&records[1..]).unwrap();
+            let actual = writer.finish().unwrap();
+            assert_eq!(expected, actual, "{data_format}");
+        }
+    }
+
+    #[test]
+    fn test_value_round_trip() {
+        let value: Value = serde_json::from_str(
+            r#"[{"id": 1, "name": "a", "score": 1.5}, {"id": 2, "name": "b", "score": -3.0}]"#,
+        )
+        .unwrap();
+        #[allow(unused_mut)]
+        let mut formats = vec![DataFormat::Json, DataFormat::JsonLines];
+        #[cfg(feature = "csv")]
+        formats.extend([DataFormat::Csv, DataFormat::Tsv]);
+        #[cfg(feature = "yaml")]
+        formats.push(DataFormat::Yaml);
+        #[cfg(feature = "messagepack")]
+        formats.push(DataFormat::MessagePack);
+
+        for data_format in formats {
+            let mut buffer = Vec::new();
+            write_value_to_writer(&mut buffer, &value, data_format).unwrap();
+            let actual = crate::read_value_from_reader(buffer.as_slice(), data_format).unwrap();
+            assert_eq!(actual, value, "{data_format}");
+        }
+
+        #[cfg(feature = "toml")]
+        {
+            let mut buffer = Vec::new();
+            write_value_to_writer(&mut buffer, &value, DataFormat::Toml).unwrap();
+            let actual =
+                crate::read_value_from_reader(buffer.as_slice(), DataFormat::Toml).unwrap();
+            assert_eq!(actual.get("record"), Some(&value));
+        }
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_value_csv_nested_round_trip() {
+        let value: Value = serde_json::from_str(
+            r#"[{"id": 1, "address": {"city": "Oslo", "zip": 150}, "tags": ["a", "b"]},
+                {"id": 2, "address": {"city": "Bergen", "zip": 5003}, "tags": ["c"]}]"#,
+        )
+        .unwrap();
+        let mut write_options = WriteOptions::default();
+        write_options.csv.flatten = true;
+        for data_format in [DataFormat::Csv, DataFormat::Tsv] {
+            let error = write_value_to_writer(Vec::new(), &value, data_format).unwrap_err();
+            assert!(matches!(error.kind(), Error::Flatten(_)), "{error}");
+            let context = error.context().unwrap();
+            assert_eq!(context.record(), Some(0));
+            assert_eq!(context.field(), Some("address"));
+            let mut buffer = Vec::new();
+            write_value_to_writer_with_options(&mut buffer, &value, data_format, &write_options)
+                .unwrap();
+            let mut options = crate::ReadOptions::default();
+            options.csv.flatten = true;
+            let actual = crate::read_value_from_reader_with_options(
+                buffer.as_slice(),
+                data_format,
+                &options,
+            )
+            .unwrap();
+            assert_eq!(actual, value, "{data_format}");
+        }
+    }
+
+    #[test]
+    fn test_records_at_pointer_round_trip() {
+        let records = vec![
+            TestRecord {
+                name: "test1".to_string(),
+                value: 1,
+            },
+            TestRecord {
+                name: "test2".to_string(),
+                value: 2,
+            },
+        ];
+        let envelope = serde_json::json!({"meta": {"page": 1}});
+        #[allow(unused_mut)]
+        let mut formats = vec![DataFormat::Json];
+        #[cfg(feature = "yaml")]
+        formats.push(DataFormat::Yaml);
+        #[cfg(feature = "messagepack")]
+        formats.push(DataFormat::MessagePack);
+        #[cfg(feature = "toml")]
+        formats.push(DataFormat::Toml);
+
+        for data_format in formats {
+            let mut buffer = Vec::new();
+            write_records_at_pointer_to_writer(
+                &mut buffer,
+                &records,
+                data_format,
+                &envelope,
+                "/data/items",
+            )
+            .unwrap();
+            let items: Vec<serde_json::Value> = crate::read_records_at_pointer_from_reader(
+                buffer.as_slice(),
+                data_format,
+                "/data/items",
+            )
+            .unwrap();
+            assert_eq!(items[1]["name"], "test2", "{data_format}");
+            let page: u32 = crate::read_record_at_pointer_from_reader(
+                buffer.as_slice(),
+                data_format,
+                "/meta/page",
+            )
+            .unwrap();
+            assert_eq!(page, 1, "{data_format}");
+        }
+    }
+
+    #[test]
+    fn test_records_at_pointer_keeps_envelope() {
+        let records = vec![TestRecord {
+            name: "test1".to_string(),
+            value: 1,
+        }];
+        let envelope = Value::from(serde_json::json!({"z": {"page": 1}}));
+        let mut buffer = Vec::new();
+        write_records_at_pointer_to_writer(
+            &mut buffer,
+            &records,
+            DataFormat::Json,
+            &envelope,
+            "/a",
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            r#"{"z":{"page":1},"a":[{"name":"test1","value":1}]}"#
+        );
+
+        #[cfg(feature = "toml")]
+        {
+            let envelope: toml::Table = toml::from_str("when = 1979-05-27T07:32:00Z\n").unwrap();
+            let mut options = WriteOptions::default();
+            options.toml.pretty = false;
+            let mut buffer = Vec::new();
+            write_records_at_pointer_to_writer_with_options(
+                &mut buffer,
+                &records,
+                DataFormat::Toml,
+                &envelope,
+                "/items",
+                &options,
+            )
+            .unwrap();
+            let toml = String::from_utf8(buffer).unwrap();
+            assert!(toml.starts_with("when = 1979-05-27T07:32:00Z\n"), "{toml}");
+        }
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_record_writer_with_options() {
+        let records = vec![TestRecord {
+            name: "test1".to_string(),
+            value: 1,
+        }];
+        let mut options = WriteOptions::default();
+        options.csv.has_headers = false;
+
+        let mut expected = Vec::new();
+        write_records_to_writer_with_options(&mut expected, &records, DataFormat::Csv, &options)
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&expected), "test1,1\n");
+
+        let mut writer = RecordWriter::with_options(Vec::new(), DataFormat::Csv, &options).unwrap();
+        writer.write_all(&records).unwrap();
+        assert_eq!(writer.finish().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_write_record_pretty_json() {
+        let record = TestRecord {
+            name: "test1".to_string(),
+            value: 1,
+        };
+        let mut options = WriteOptions::default();
+        options.json.pretty = true;
+        let mut buffer = Vec::new();
+        write_record_to_writer_with_options(&mut buffer, &record, DataFormat::Json, &options)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{\n  \"name\": \"test1\",\n  \"value\": 1\n}"
+        );
+    }
+
+    #[test]
+    fn test_write_records_to_file_round_trip() {
+        #[derive(Debug, serde::Deserialize, PartialEq, Eq)]
+        struct ReadRecord {
+            name: String,
+            value: i32,
+        }
+
+        let records = vec![TestRecord {
+            name: "test1".to_string(),
+            value: 1,
+        }];
+        #[allow(unused_mut)]
+        let mut extensions = vec!["jsonl"];
+        #[cfg(feature = "gzip")]
+        extensions.push("jsonl.gz");
+        #[cfg(feature = "zstd")]
+        extensions.push("json.zst");
+
+        for extension in extensions {
+            let file_name = format!(
+                "serdeio_write_round_trip_test_{}.{extension}",
+                std::process::id()
+            );
+            let path = std::env::temp_dir().join(&file_name);
+            write_records_to_file(&path, &records, DataFormat::Auto).unwrap();
+            let read: Vec<ReadRecord> =
+                crate::read_records_from_file(&path, DataFormat::Auto).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(
+                read,
+                vec![ReadRecord {
+                    name: "test1".to_string(),
+                    value: 1
+                }],
+                "{file_name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_record_writer_create_is_buffered() {
+        let path = std::env::temp_dir().join(format!(
+            "serdeio_record_writer_create_test_{}.jsonl",
+            std::process::id()
+        ));
+        let mut writer = RecordWriter::create(&path, DataFormat::Auto).unwrap();
+        for value in 0..3 {
+            writer
+                .write(&TestRecord {
+                    name: format!("test{value}"),
+                    value,
+                })
+                .unwrap();
+        }
+        // Nothing reaches the file until the buffer is flushed.
+        let before = std::fs::read_to_string(&path).unwrap();
+        let file: BufWriter<File> = writer.finish().unwrap();
+        drop(file);
+        let after = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(before, "");
+        assert_eq!(after.lines().count(), 3);
+    }
+
+    #[test]
+    fn test_record_writer_auto_not_supported() {
+        let result = RecordWriter::<_, TestRecord>::new(Vec::new(), DataFormat::Auto);
+        assert!(matches!(result, Err(Error::AutoNotSupported)));
+    }
 }